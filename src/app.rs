@@ -91,8 +91,14 @@ pub enum Error {
 	MiscSettingsNotFound,
 	#[error("Index album art pattern is not a valid regex")]
 	IndexAlbumArtPatternInvalid,
+	#[error("Index artist art pattern is not a valid regex")]
+	IndexArtistArtPatternInvalid,
 	#[error("DDNS update URL is invalid")]
 	DDNSUpdateURLInvalid,
+	#[error("Genre `{0}` is its own ancestor")]
+	GenreHierarchyCycle(String),
+	#[error("Transliteration `{0}` does not apply to a single character")]
+	TransliterationInvalid(String),
 
 	#[error("Could not deserialize configuration: `{0}`")]
 	ConfigDeserialization(toml::de::Error),
@@ -119,12 +125,30 @@ pub enum Error {
 	AlbumNotFound,
 	#[error("Genre not found")]
 	GenreNotFound,
+	#[error("Label not found")]
+	LabelNotFound,
+	#[error("Decade not found")]
+	DecadeNotFound,
 	#[error("Song not found")]
 	SongNotFound,
-	#[error("Invalid search query syntax")]
-	SearchQueryParseError,
+	#[error("Invalid search query syntax: {}", .0.hint)]
+	SearchQueryParseError(index::QueryError),
 	#[error("Playlist not found")]
 	PlaylistNotFound,
+	#[error("Could not parse XSPF playlist: {0}")]
+	PlaylistXspfParse(quick_xml::Error),
+	#[error("Playlist was modified since it was read (current version is {0})")]
+	PlaylistVersionConflict(u64),
+	#[error("Playlist position is out of range")]
+	PlaylistPositionOutOfRange,
+	#[error("Smart playlists cannot be edited")]
+	SmartPlaylistNotEditable,
+	#[error("Playlist is not shared for editing")]
+	PlaylistAccessDenied,
+	#[error("Playlist has no cover")]
+	PlaylistCoverNotFound,
+	#[error("Could not decode playlist cover:\n\n{0}")]
+	PlaylistCoverDecoding(image::error::ImageError),
 	#[error("No embedded artwork was found in `{0}`")]
 	EmbeddedArtworkNotFound(PathBuf),
 
@@ -186,9 +210,20 @@ impl App {
 		let ddns_manager = ddns::Manager::new(config_manager.clone());
 		let ndb_manager = ndb::Manager::new(&paths.data_dir_path)?;
 		let index_manager = index::Manager::new(&paths.data_dir_path).await?;
-		let scanner = scanner::Scanner::new(index_manager.clone(), config_manager.clone()).await?;
 		let peaks_manager = peaks::Manager::new(peaks_dir_path);
-		let playlist_manager = playlist::Manager::new(ndb_manager);
+		let playlist_manager = playlist::Manager::new(
+			ndb_manager,
+			index_manager.clone(),
+			config_manager.clone(),
+			paths.data_dir_path.join("playlist_covers"),
+		);
+		let scanner = scanner::Scanner::new(
+			index_manager.clone(),
+			config_manager.clone(),
+			playlist_manager.clone(),
+			peaks_manager.clone(),
+		)
+		.await?;
 		let thumbnail_manager = thumbnail::Manager::new(thumbnails_dir_path);
 
 		let app = Self {
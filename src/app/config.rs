@@ -1,4 +1,5 @@
 use std::{
+	collections::HashMap,
 	path::{Path, PathBuf},
 	sync::Arc,
 	time::Duration,
@@ -12,6 +13,7 @@ use tokio::sync::{futures::Notified, Notify, RwLock};
 
 use crate::app::Error;
 
+mod genres;
 mod mounts;
 pub mod storage;
 mod user;
@@ -21,10 +23,17 @@ pub use user::*;
 
 use super::auth;
 
+const DEFAULT_COMPILATION_ARTISTS: [&str; 2] = ["Various Artists", "VA"];
+
 #[derive(Debug, Clone, Default)]
 pub struct Config {
 	pub album_art_pattern: Option<Regex>,
+	pub artist_art_pattern: Option<Regex>,
 	pub ddns_update_url: Option<http::Uri>,
+	pub compilation_artists: Option<Vec<String>>,
+	pub duplicate_detection: Option<storage::DuplicateDetection>,
+	pub genres: Vec<storage::Genre>,
+	pub transliterations: HashMap<char, String>,
 	pub mount_dirs: Vec<MountDir>,
 	pub users: Vec<User>,
 }
@@ -43,12 +52,30 @@ impl TryFrom<storage::Config> for Config {
 			None => None,
 		};
 
+		config.artist_art_pattern = match c.artist_art_pattern.as_deref().map(Regex::new) {
+			Some(Ok(u)) => Some(u),
+			Some(Err(_)) => return Err(Error::IndexArtistArtPatternInvalid),
+			None => None,
+		};
+
 		config.ddns_update_url = match c.ddns_update_url.map(http::Uri::try_from) {
 			Some(Ok(u)) => Some(u),
 			Some(Err(_)) => return Err(Error::DDNSUpdateURLInvalid),
 			None => None,
 		};
 
+		config.compilation_artists = c.compilation_artists;
+		config.duplicate_detection = c.duplicate_detection;
+		config.set_genres(c.genres)?;
+
+		for (from, to) in c.transliterations {
+			let mut chars = from.chars();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) => config.transliterations.insert(c, to),
+				_ => return Err(Error::TransliterationInvalid(from)),
+			};
+		}
+
 		Ok(config)
 	}
 }
@@ -57,8 +84,17 @@ impl From<Config> for storage::Config {
 	fn from(c: Config) -> Self {
 		Self {
 			album_art_pattern: c.album_art_pattern.map(|p| p.as_str().to_owned()),
+			artist_art_pattern: c.artist_art_pattern.map(|p| p.as_str().to_owned()),
 			mount_dirs: c.mount_dirs.into_iter().map(|d| d.into()).collect(),
 			ddns_update_url: c.ddns_update_url.map(|u| u.to_string()),
+			compilation_artists: c.compilation_artists,
+			duplicate_detection: c.duplicate_detection,
+			genres: c.genres,
+			transliterations: c
+				.transliterations
+				.into_iter()
+				.map(|(from, to)| (from.to_string(), to))
+				.collect(),
 			users: c.users.into_iter().map(|u| u.into()).collect(),
 		}
 	}
@@ -198,6 +234,19 @@ impl Manager {
 		.await
 	}
 
+	pub async fn get_index_artist_art_pattern(&self) -> Regex {
+		let config = self.config.read().await;
+		let pattern = config.artist_art_pattern.clone();
+		pattern.unwrap_or_else(|| Regex::new("artist.(jpeg|jpg|png)").unwrap())
+	}
+
+	pub async fn set_index_artist_art_pattern(&self, regex: Regex) -> Result<(), Error> {
+		self.mutate(|c| {
+			c.artist_art_pattern = Some(regex);
+		})
+		.await
+	}
+
 	pub async fn get_ddns_update_url(&self) -> Option<http::Uri> {
 		self.config.read().await.ddns_update_url.clone()
 	}
@@ -209,6 +258,29 @@ impl Manager {
 		.await
 	}
 
+	pub async fn get_compilation_artists(&self) -> Vec<String> {
+		let config = self.config.read().await;
+		config.compilation_artists.clone().unwrap_or_else(|| {
+			DEFAULT_COMPILATION_ARTISTS
+				.iter()
+				.map(|s| s.to_string())
+				.collect()
+		})
+	}
+
+	pub async fn get_duplicate_detection(&self) -> storage::DuplicateDetection {
+		let config = self.config.read().await;
+		config.duplicate_detection.unwrap_or_default()
+	}
+
+	pub async fn get_genres(&self) -> Vec<storage::Genre> {
+		self.config.read().await.genres.clone()
+	}
+
+	pub async fn get_transliterations(&self) -> HashMap<char, String> {
+		self.config.read().await.transliterations.clone()
+	}
+
 	pub async fn get_users(&self) -> Vec<User> {
 		self.config.read().await.users.to_vec()
 	}
@@ -246,6 +318,15 @@ impl Manager {
 			.await
 	}
 
+	pub async fn set_collapse_duplicates(
+		&self,
+		username: &str,
+		collapse: bool,
+	) -> Result<(), Error> {
+		self.mutate_fallible(|c| c.set_collapse_duplicates(username, collapse))
+			.await
+	}
+
 	pub async fn authenticate(
 		&self,
 		auth_token: &auth::Token,
@@ -272,6 +353,11 @@ impl Manager {
 		config.resolve_virtual_path(virtual_path)
 	}
 
+	pub async fn virtualize_path<P: AsRef<Path>>(&self, real_path: P) -> Result<PathBuf, Error> {
+		let config = self.config.read().await;
+		config.virtualize_path(real_path)
+	}
+
 	pub async fn set_mounts(&self, mount_dirs: Vec<storage::MountDir>) -> Result<(), Error> {
 		self.mutate_fallible(|c| c.set_mounts(mount_dirs)).await
 	}
@@ -0,0 +1,68 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::app::Error;
+
+use super::storage;
+use super::Config;
+
+impl Config {
+	pub fn set_genres(&mut self, genres: Vec<storage::Genre>) -> Result<(), Error> {
+		let parents = genres
+			.iter()
+			.filter_map(|g| Some((g.name.to_lowercase(), g.parent.as_ref()?.to_lowercase())))
+			.collect::<HashMap<_, _>>();
+
+		for genre in &genres {
+			let mut ancestors = HashSet::new();
+			let mut current = genre.name.to_lowercase();
+			while let Some(parent) = parents.get(&current) {
+				if !ancestors.insert(parent.clone()) {
+					return Err(Error::GenreHierarchyCycle(genre.name.clone()));
+				}
+				current = parent.clone();
+			}
+		}
+
+		self.genres = genres;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn genre(name: &str, parent: Option<&str>) -> storage::Genre {
+		storage::Genre {
+			name: name.to_owned(),
+			aliases: vec![],
+			parent: parent.map(|p| p.to_owned()),
+		}
+	}
+
+	#[test]
+	fn accepts_genre_hierarchy() {
+		let mut config = Config::default();
+		let genres = vec![
+			genre("Metal", None),
+			genre("Death Metal", Some("Metal")),
+			genre("Melodic Death Metal", Some("Death Metal")),
+		];
+		config.set_genres(genres.clone()).unwrap();
+		assert_eq!(config.genres, genres);
+	}
+
+	#[test]
+	fn rejects_genre_cycles() {
+		let mut config = Config::default();
+		let genres = vec![
+			genre("Metal", Some("Melodic Death Metal")),
+			genre("Death Metal", Some("Metal")),
+			genre("Melodic Death Metal", Some("death metal")),
+		];
+		assert!(matches!(
+			config.set_genres(genres),
+			Err(Error::GenreHierarchyCycle(_))
+		));
+	}
+}
@@ -16,6 +16,14 @@ pub struct MountDir {
 	pub name: String,
 }
 
+impl MountDir {
+	pub fn virtualize_path(&self, real_path: &Path) -> Option<PathBuf> {
+		let sanitized = sanitize_path(real_path);
+		let tail = sanitized.strip_prefix(&self.source).ok()?;
+		Some(Path::new(&self.name).join(tail))
+	}
+}
+
 impl TryFrom<storage::MountDir> for MountDir {
 	type Error = Error;
 
@@ -61,6 +69,13 @@ impl Config {
 		}
 		Err(Error::CouldNotMapToRealPath(virtual_path.as_ref().into()))
 	}
+
+	pub fn virtualize_path<P: AsRef<Path>>(&self, real_path: P) -> Result<PathBuf, Error> {
+		self.mount_dirs
+			.iter()
+			.find_map(|m| m.virtualize_path(real_path.as_ref()))
+			.ok_or_else(|| Error::CouldNotMapToVirtualPath(real_path.as_ref().into()))
+	}
 }
 
 fn sanitize_path(source: &Path) -> PathBuf {
@@ -104,6 +119,38 @@ mod test {
 		}
 	}
 
+	#[test]
+	fn can_virtualize_real_paths() {
+		let raw_config = storage::Config {
+			mount_dirs: vec![storage::MountDir {
+				name: "root".to_owned(),
+				source: PathBuf::from("test_dir"),
+			}],
+			..Default::default()
+		};
+
+		let config: Config = raw_config.try_into().unwrap();
+
+		let test_cases = vec![
+			(vec!["test_dir"], vec!["root"]),
+			(
+				vec!["test_dir", "somewhere", "something.png"],
+				vec!["root", "somewhere", "something.png"],
+			),
+		];
+
+		for (real, r#virtual) in test_cases {
+			let real_path: PathBuf = real.iter().collect();
+			let virtual_path: PathBuf = r#virtual.iter().collect();
+			let converted_path = config.virtualize_path(&real_path).unwrap();
+			assert_eq!(converted_path, virtual_path);
+		}
+
+		assert!(config
+			.virtualize_path(PathBuf::from_iter(["elsewhere", "something.png"]))
+			.is_err());
+	}
+
 	#[test]
 	fn sanitizes_paths() {
 		let mut correct_path = PathBuf::new();
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{collections::BTreeMap, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,8 @@ pub struct User {
 	pub initial_password: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub hashed_password: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub collapse_duplicates: Option<bool>,
 }
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -19,14 +21,41 @@ pub struct MountDir {
 	pub name: String,
 }
 
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateDetection {
+	#[default]
+	Metadata,
+	Fingerprint,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Genre {
+	pub name: String,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub aliases: Vec<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub parent: Option<String>,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Config {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub album_art_pattern: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub artist_art_pattern: Option<String>,
 	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub mount_dirs: Vec<MountDir>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub ddns_update_url: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub compilation_artists: Option<Vec<String>>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub duplicate_detection: Option<DuplicateDetection>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub genres: Vec<Genre>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	pub transliterations: BTreeMap<String, String>,
 	#[serde(default, skip_serializing_if = "Vec::is_empty")]
 	pub users: Vec<User>,
 }
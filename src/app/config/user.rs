@@ -9,12 +9,17 @@ pub struct User {
 	pub admin: Option<bool>,
 	pub initial_password: Option<String>,
 	pub hashed_password: String,
+	pub collapse_duplicates: Option<bool>,
 }
 
 impl User {
 	pub fn is_admin(&self) -> bool {
 		self.admin == Some(true)
 	}
+
+	pub fn collapses_duplicates(&self) -> bool {
+		self.collapse_duplicates == Some(true)
+	}
 }
 
 impl TryFrom<storage::User> for User {
@@ -32,6 +37,7 @@ impl TryFrom<storage::User> for User {
 			admin: user.admin,
 			initial_password: user.initial_password,
 			hashed_password,
+			collapse_duplicates: user.collapse_duplicates,
 		})
 	}
 }
@@ -43,6 +49,7 @@ impl From<User> for storage::User {
 			admin: user.admin,
 			initial_password: user.initial_password,
 			hashed_password: Some(user.hashed_password),
+			collapse_duplicates: user.collapse_duplicates,
 		}
 	}
 }
@@ -80,6 +87,7 @@ impl Config {
 			admin: Some(admin),
 			initial_password: None,
 			hashed_password: password_hash,
+			collapse_duplicates: None,
 		});
 
 		Ok(())
@@ -141,6 +149,12 @@ impl Config {
 		Ok(())
 	}
 
+	pub fn set_collapse_duplicates(&mut self, username: &str, collapse: bool) -> Result<(), Error> {
+		let user = self.get_user_mut(username).ok_or(Error::UserNotFound)?;
+		user.collapse_duplicates = Some(collapse);
+		Ok(())
+	}
+
 	pub fn delete_user(&mut self, username: &str) {
 		self.users.retain(|u| u.name != username);
 	}
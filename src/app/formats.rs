@@ -5,6 +5,8 @@ use std::fs;
 use std::io::{Seek, SeekFrom};
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::app::Error;
 use crate::utils;
 use crate::utils::AudioFormat;
@@ -19,11 +21,49 @@ pub struct SongMetadata {
 	pub album_artists: Vec<String>,
 	pub album: Option<String>,
 	pub year: Option<i32>,
-	pub has_artwork: bool,
+	pub pictures: Vec<PictureType>,
 	pub lyricists: Vec<String>,
 	pub composers: Vec<String>,
 	pub genres: Vec<String>,
 	pub labels: Vec<String>,
+	pub compilation: bool,
+}
+
+/// Role of an image within an album's artwork, as defined by ID3 and FLAC picture types
+#[derive(
+	Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum PictureType {
+	Front,
+	Back,
+	Booklet,
+	Media,
+	#[default]
+	Other,
+}
+
+impl From<id3::frame::PictureType> for PictureType {
+	fn from(picture_type: id3::frame::PictureType) -> Self {
+		match picture_type {
+			id3::frame::PictureType::CoverFront => Self::Front,
+			id3::frame::PictureType::CoverBack => Self::Back,
+			id3::frame::PictureType::Leaflet => Self::Booklet,
+			id3::frame::PictureType::Media => Self::Media,
+			_ => Self::Other,
+		}
+	}
+}
+
+impl From<metaflac::block::PictureType> for PictureType {
+	fn from(picture_type: metaflac::block::PictureType) -> Self {
+		match picture_type {
+			metaflac::block::PictureType::CoverFront => Self::Front,
+			metaflac::block::PictureType::CoverBack => Self::Back,
+			metaflac::block::PictureType::Leaflet => Self::Booklet,
+			metaflac::block::PictureType::Media => Self::Media,
+			_ => Self::Other,
+		}
+	}
 }
 
 pub fn read_metadata<P: AsRef<Path>>(path: P) -> Option<SongMetadata> {
@@ -92,11 +132,15 @@ fn read_id3_from_file<P: AsRef<Path>>(file: &fs::File, path: P) -> Result<SongMe
 		.or_else(|| tag.date_released().map(|d| d.year))
 		.or_else(|| tag.original_date_released().map(|d| d.year))
 		.or_else(|| tag.date_recorded().map(|d| d.year));
-	let has_artwork = tag.pictures().count() > 0;
+	let pictures = tag.pictures().map(|p| p.picture_type.into()).collect();
 	let lyricists = tag.get_text_values("TEXT");
 	let composers = tag.get_text_values("TCOM");
 	let genres = tag.get_text_values("TCON");
 	let labels = tag.get_text_values("TPUB");
+	let compilation = tag
+		.get("TCMP")
+		.and_then(|f| f.content().text())
+		.is_some_and(is_truthy);
 
 	Ok(SongMetadata {
 		disc_number,
@@ -107,11 +151,12 @@ fn read_id3_from_file<P: AsRef<Path>>(file: &fs::File, path: P) -> Result<SongMe
 		album_artists,
 		album,
 		year,
-		has_artwork,
+		pictures,
 		lyricists,
 		composers,
 		genres,
 		labels,
+		compilation,
 	})
 }
 
@@ -177,6 +222,10 @@ fn read_ape<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 	let composers = ape_ext::read_strings(tag.item("COMPOSER"));
 	let genres = ape_ext::read_strings(tag.item("GENRE"));
 	let labels = ape_ext::read_strings(tag.item("PUBLISHER"));
+	let compilation = tag
+		.item("COMPILATION")
+		.and_then(ape_ext::read_string)
+		.is_some_and(|s| is_truthy(&s));
 	Ok(SongMetadata {
 		artists,
 		album_artists,
@@ -186,11 +235,12 @@ fn read_ape<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 		disc_number,
 		track_number,
 		year,
-		has_artwork: false,
+		pictures: vec![],
 		lyricists,
 		composers,
 		genres,
 		labels,
+		compilation,
 	})
 }
 
@@ -213,6 +263,7 @@ fn read_vorbis<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 				"COMPOSER" => metadata.composers.push(value),
 				"GENRE" => metadata.genres.push(value),
 				"PUBLISHER" => metadata.labels.push(value),
+				"COMPILATION" => metadata.compilation = is_truthy(&value),
 				_ => (),
 			}
 		}
@@ -239,6 +290,7 @@ fn read_opus<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 				"COMPOSER" => metadata.composers.push(value),
 				"GENRE" => metadata.genres.push(value),
 				"PUBLISHER" => metadata.labels.push(value),
+				"COMPILATION" => metadata.compilation = is_truthy(&value),
 				_ => (),
 			}
 		}
@@ -262,7 +314,7 @@ fn read_flac<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 		Some(metaflac::Block::StreamInfo(s)) => Some(s.total_samples as u32 / s.sample_rate),
 		_ => None,
 	};
-	let has_artwork = tag.pictures().count() > 0;
+	let pictures = tag.pictures().map(|p| p.picture_type.into()).collect();
 
 	let multivalue = |o: Option<&Vec<String>>| o.cloned().unwrap_or_default();
 
@@ -275,11 +327,14 @@ fn read_flac<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 		disc_number,
 		track_number: vorbis.track(),
 		year,
-		has_artwork,
+		pictures,
 		lyricists: multivalue(vorbis.get("LYRICIST")),
 		composers: multivalue(vorbis.get("COMPOSER")),
 		genres: multivalue(vorbis.get("GENRE")),
 		labels: multivalue(vorbis.get("PUBLISHER")),
+		compilation: vorbis
+			.get("COMPILATION")
+			.is_some_and(|v| v.iter().any(|s| is_truthy(s))),
 	})
 }
 
@@ -297,14 +352,26 @@ fn read_mp4<P: AsRef<Path>>(path: P) -> Result<SongMetadata, Error> {
 		disc_number: tag.disc_number().map(|d| d as u32),
 		track_number: tag.track_number().map(|d| d as u32),
 		year: tag.year().and_then(|v| v.parse::<i32>().ok()),
-		has_artwork: tag.artwork().is_some(),
+		pictures: tag
+			.artworks()
+			.enumerate()
+			.map(|(i, _)| match i {
+				0 => PictureType::Front,
+				_ => PictureType::Other,
+			})
+			.collect(),
 		lyricists: tag.take_lyricists().collect(),
 		composers: tag.take_composers().collect(),
 		genres: tag.take_genres().collect(),
 		labels: tag.take_strings_of(&label_ident).collect(),
+		compilation: tag.compilation(),
 	})
 }
 
+fn is_truthy(value: &str) -> bool {
+	matches!(value.trim(), "1" | "true" | "TRUE" | "True")
+}
+
 #[test]
 fn reads_file_metadata() {
 	let expected_without_duration = SongMetadata {
@@ -316,11 +383,12 @@ fn reads_file_metadata() {
 		album: Some("TEST ALBUM".into()),
 		duration: None,
 		year: Some(2016),
-		has_artwork: false,
+		pictures: vec![],
 		lyricists: vec!["TEST LYRICIST".into()],
 		composers: vec!["TEST COMPOSER".into()],
 		genres: vec!["TEST GENRE".into()],
 		labels: vec!["TEST LABEL".into()],
+		compilation: false,
 	};
 	let expected_with_duration = SongMetadata {
 		duration: Some(0),
@@ -362,31 +430,12 @@ fn reads_file_metadata() {
 
 #[test]
 fn reads_embedded_artwork() {
-	assert!(
-		read_metadata(Path::new("test-data/artwork/sample.aif"))
-			.unwrap()
-			.has_artwork
-	);
-	assert!(
-		read_metadata(Path::new("test-data/artwork/sample.mp3"))
-			.unwrap()
-			.has_artwork
-	);
-	assert!(
-		read_metadata(Path::new("test-data/artwork/sample.flac"))
-			.unwrap()
-			.has_artwork
-	);
-	assert!(
-		read_metadata(Path::new("test-data/artwork/sample.m4a"))
-			.unwrap()
-			.has_artwork
-	);
-	assert!(
-		read_metadata(Path::new("test-data/artwork/sample.wav"))
-			.unwrap()
-			.has_artwork
-	);
+	let has_pictures = |path: &str| !read_metadata(Path::new(path)).unwrap().pictures.is_empty();
+	assert!(has_pictures("test-data/artwork/sample.aif"));
+	assert!(has_pictures("test-data/artwork/sample.mp3"));
+	assert!(has_pictures("test-data/artwork/sample.flac"));
+	assert!(has_pictures("test-data/artwork/sample.m4a"));
+	assert!(has_pictures("test-data/artwork/sample.wav"));
 }
 
 #[test]
@@ -400,11 +449,12 @@ fn reads_multivalue_fields() {
 		album: Some("TEST ALBUM".into()),
 		duration: None,
 		year: Some(2016),
-		has_artwork: false,
+		pictures: vec![],
 		lyricists: vec!["TEST LYRICIST".into(), "OTHER LYRICIST".into()],
 		composers: vec!["TEST COMPOSER".into(), "OTHER COMPOSER".into()],
 		genres: vec!["TEST GENRE".into(), "OTHER GENRE".into()],
 		labels: vec!["TEST LABEL".into(), "OTHER LABEL".into()],
+		compilation: false,
 	};
 	let expected_with_duration = SongMetadata {
 		duration: Some(0),
@@ -440,3 +490,11 @@ fn reads_multivalue_fields() {
 		expected_without_duration
 	);
 }
+
+#[test]
+fn parses_compilation_flags() {
+	assert!(is_truthy("1"));
+	assert!(is_truthy(" true "));
+	assert!(!is_truthy("0"));
+	assert!(!is_truthy(""));
+}
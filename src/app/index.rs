@@ -1,24 +1,44 @@
 use std::{
+	collections::{BTreeMap, HashMap, HashSet},
+	hash::{DefaultHasher, Hash, Hasher},
 	path::{Path, PathBuf},
 	sync::{Arc, RwLock},
+	time::Duration,
 };
 
 use log::{error, info};
 use serde::{Deserialize, Serialize};
 use tokio::task::spawn_blocking;
 
-use crate::app::{scanner, Error};
+use crate::app::{config, scanner, Error};
 
 mod browser;
 mod collection;
 mod dictionary;
+mod duplicates;
+mod genres;
+mod health;
 mod query;
+mod relevance;
+mod relink;
 mod search;
+mod stats;
 mod storage;
+mod suggest;
 
 pub use browser::File;
-pub use collection::{Album, AlbumHeader, Artist, ArtistHeader, Genre, GenreHeader, Song};
-use storage::{store_song, AlbumKey, ArtistKey, GenreKey, InternPath, SongKey};
+pub use collection::{
+	Album, AlbumHeader, AlbumHealth, Artist, ArtistHeader, Decade, DecadeHeader, Genre,
+	GenreHeader, Image, Label, LabelHeader, ListOptions, Song, SortKey, SortOrder,
+};
+pub use health::Rule as HealthRule;
+pub use query::QueryError;
+pub use search::{SearchGroups, SearchLimits, SearchOrder, SearchResult};
+pub use stats::Stats;
+use storage::{
+	store_image, store_song, AlbumKey, ArtistKey, GenreKey, InternPath, LabelKey, SongKey,
+};
+pub use suggest::{Suggestion, SuggestionKind};
 
 #[derive(Clone)]
 pub struct Manager {
@@ -58,12 +78,13 @@ impl Manager {
 		.unwrap()
 	}
 
-	pub async fn replace_index(&self, new_index: Index) {
+	/// Returns the index being replaced
+	pub async fn replace_index(&self, new_index: Index) -> Index {
 		spawn_blocking({
 			let index_manager = self.clone();
 			move || {
 				let mut lock = index_manager.index.write().unwrap();
-				*lock = new_index;
+				std::mem::replace(&mut *lock, new_index)
 			}
 		})
 		.await
@@ -126,6 +147,53 @@ impl Manager {
 		.unwrap()
 	}
 
+	pub async fn get_stats(&self) -> Stats {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || index_manager.index.read().unwrap().collection.get_stats()
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_health(&self, rule: Option<HealthRule>) -> Vec<AlbumHealth> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_health(&index.dictionary, rule)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_duplicates(&self) -> Vec<Vec<Song>> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_duplicates(&index.dictionary)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn collapse_album_duplicates(&self, songs: Vec<Song>) -> Vec<Song> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index
+					.collection
+					.collapse_album_duplicates(&index.dictionary, songs)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
 	pub async fn get_genres(&self) -> Vec<GenreHeader> {
 		spawn_blocking({
 			let index_manager = self.clone();
@@ -138,7 +206,57 @@ impl Manager {
 		.unwrap()
 	}
 
-	pub async fn get_genre(&self, name: String) -> Result<Genre, Error> {
+	pub async fn get_genre(&self, name: String, include_subgenres: bool) -> Result<Genre, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				let name = index
+					.dictionary
+					.get(&name)
+					.ok_or_else(|| Error::GenreNotFound)?;
+				let genre_key = GenreKey(name);
+				index
+					.collection
+					.get_genre(&index.dictionary, genre_key, include_subgenres)
+					.ok_or_else(|| Error::GenreNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_genre_albums(
+		&self,
+		name: String,
+		include_subgenres: bool,
+		options: ListOptions,
+	) -> Result<Vec<AlbumHeader>, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				let name = index
+					.dictionary
+					.get(&name)
+					.ok_or_else(|| Error::GenreNotFound)?;
+				let genre_key = GenreKey(name);
+				index
+					.collection
+					.get_genre_albums(&index.dictionary, genre_key, include_subgenres, &options)
+					.ok_or_else(|| Error::GenreNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_genre_artists(
+		&self,
+		name: String,
+		include_subgenres: bool,
+		options: ListOptions,
+	) -> Result<Vec<ArtistHeader>, Error> {
 		spawn_blocking({
 			let index_manager = self.clone();
 			move || {
@@ -150,7 +268,7 @@ impl Manager {
 				let genre_key = GenreKey(name);
 				index
 					.collection
-					.get_genre(&index.dictionary, genre_key)
+					.get_genre_artists(&index.dictionary, genre_key, include_subgenres, &options)
 					.ok_or_else(|| Error::GenreNotFound)
 			}
 		})
@@ -158,24 +276,121 @@ impl Manager {
 		.unwrap()
 	}
 
-	pub async fn get_albums(&self) -> Vec<AlbumHeader> {
+	pub async fn get_labels(&self) -> Vec<LabelHeader> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_labels(&index.dictionary)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_label(&self, name: String) -> Result<Label, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				let name = index
+					.dictionary
+					.get(&name)
+					.ok_or_else(|| Error::LabelNotFound)?;
+				let label_key = LabelKey(name);
+				index
+					.collection
+					.get_label(&index.dictionary, label_key)
+					.ok_or_else(|| Error::LabelNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_decades(&self) -> Vec<DecadeHeader> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_decades()
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_decade(&self, decade: i64) -> Result<Decade, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index
+					.collection
+					.get_decade(&index.dictionary, decade)
+					.ok_or_else(|| Error::DecadeNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_compilations(&self, options: ListOptions) -> Vec<AlbumHeader> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index
+					.collection
+					.get_compilations(&index.dictionary, &options)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_composers(&self, options: ListOptions) -> Vec<ArtistHeader> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_composers(&index.dictionary, &options)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_lyricists(&self, options: ListOptions) -> Vec<ArtistHeader> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.collection.get_lyricists(&index.dictionary, &options)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_albums(&self, options: ListOptions) -> Vec<AlbumHeader> {
 		spawn_blocking({
 			let index_manager = self.clone();
 			move || {
 				let index = index_manager.index.read().unwrap();
-				index.collection.get_albums(&index.dictionary)
+				index.collection.get_albums(&index.dictionary, &options)
 			}
 		})
 		.await
 		.unwrap()
 	}
 
-	pub async fn get_artists(&self) -> Vec<ArtistHeader> {
+	pub async fn get_artists(&self, options: ListOptions) -> Vec<ArtistHeader> {
 		spawn_blocking({
 			let index_manager = self.clone();
 			move || {
 				let index = index_manager.index.read().unwrap();
-				index.collection.get_artists(&index.dictionary)
+				index.collection.get_artists(&index.dictionary, &options)
 			}
 		})
 		.await
@@ -229,6 +444,37 @@ impl Manager {
 		.unwrap()
 	}
 
+	pub async fn get_album_images(
+		&self,
+		artists: Vec<String>,
+		name: String,
+	) -> Result<Vec<Image>, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				let name = index
+					.dictionary
+					.get(&name)
+					.ok_or_else(|| Error::AlbumNotFound)?;
+				let album_key = AlbumKey {
+					artists: artists
+						.into_iter()
+						.filter_map(|a| index.dictionary.get(a))
+						.map(ArtistKey)
+						.collect(),
+					name,
+				};
+				index
+					.collection
+					.get_album_images(&index.dictionary, album_key)
+					.ok_or_else(|| Error::AlbumNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
 	pub async fn get_random_albums(
 		&self,
 		seed: Option<u64>,
@@ -266,6 +512,37 @@ impl Manager {
 		.unwrap()
 	}
 
+	pub async fn get_folder_playlists(&self) -> Vec<FolderPlaylist> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.folder_playlists.clone()
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn get_folder_playlist(
+		&self,
+		virtual_path: PathBuf,
+	) -> Result<FolderPlaylist, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index
+					.folder_playlists
+					.binary_search_by(|p| p.virtual_path.cmp(&virtual_path))
+					.map(|i| index.folder_playlists[i].clone())
+					.map_err(|_| Error::PlaylistNotFound)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
 	pub async fn get_songs(&self, virtual_paths: Vec<PathBuf>) -> Vec<Result<Song, Error>> {
 		spawn_blocking({
 			let index_manager = self.clone();
@@ -288,14 +565,138 @@ impl Manager {
 		.unwrap()
 	}
 
-	pub async fn search(&self, query: String) -> Result<Vec<Song>, Error> {
+	/// Finds where songs which are missing from the collection were moved since
+	/// `previous_index`. Songs which went missing before that are described by
+	/// `dangling_songs`. Only paths from `virtual_paths` which can be confidently relinked
+	/// are given a new location.
+	pub async fn relink_songs(
+		&self,
+		previous_index: Index,
+		virtual_paths: Vec<PathBuf>,
+		dangling_songs: Vec<Song>,
+	) -> Relinking {
 		spawn_blocking({
 			let index_manager = self.clone();
 			move || {
 				let index = index_manager.index.read().unwrap();
-				index
-					.search
-					.find_songs(&index.collection, &index.dictionary, &query)
+				let mut dangling_songs = dangling_songs
+					.into_iter()
+					.map(|s| (s.virtual_path.clone(), s))
+					.collect::<HashMap<_, _>>();
+				let missing_songs = virtual_paths
+					.into_iter()
+					.filter(|p| index.get_song(p).is_none())
+					.filter_map(|p| {
+						previous_index
+							.get_song(&p)
+							.or_else(|| dangling_songs.remove(&p))
+					})
+					.collect::<Vec<_>>();
+				if missing_songs.is_empty() {
+					return Relinking::default();
+				}
+
+				let added_songs = index
+					.collection
+					.get_song_keys()
+					.filter_map(|k| index.collection.get_song(&index.dictionary, k))
+					.filter(|s| previous_index.get_song(&s.virtual_path).is_none())
+					.collect::<Vec<_>>();
+
+				let mut relinking = Relinking::default();
+				for song in missing_songs {
+					match relink::find_new_location(&song, &added_songs) {
+						Some(new_song) => {
+							relinking
+								.new_locations
+								.insert(song.virtual_path, new_song.virtual_path.clone());
+						}
+						None => relinking.missing_songs.push(song),
+					}
+				}
+				relinking
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn search(
+		&self,
+		query: String,
+		order: SearchOrder,
+		collapse_duplicates: bool,
+		offset: usize,
+		limit: Option<usize>,
+	) -> Result<SearchPage, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				let mut results =
+					index
+						.search
+						.find_songs(&index.collection, &index.dictionary, &query, order)?;
+				if collapse_duplicates {
+					results = index.collapse_duplicate_results(results);
+				}
+				let total = results.len();
+				Ok(SearchPage {
+					songs: results
+						.into_iter()
+						.skip(offset)
+						.take(limit.unwrap_or(usize::MAX))
+						.collect(),
+					total,
+					generation: index.generation,
+				})
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn search_all(
+		&self,
+		query: String,
+		limits: SearchLimits,
+		collapse_duplicates: bool,
+	) -> Result<SearchGroups, Error> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.search.find_all(
+					&index.collection,
+					&index.dictionary,
+					&query,
+					&limits,
+					collapse_duplicates,
+				)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn suggest(&self, prefix: String, count: usize) -> Vec<Suggestion> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				index.suggestions.suggest(&index.dictionary, &prefix, count)
+			}
+		})
+		.await
+		.unwrap()
+	}
+
+	pub async fn rank_names(&self, query: String, names: Vec<String>) -> Vec<usize> {
+		spawn_blocking({
+			let index_manager = self.clone();
+			move || {
+				let index = index_manager.index.read().unwrap();
+				relevance::rank_names(&index.dictionary, &query, &names)
 			}
 		})
 		.await
@@ -303,12 +704,67 @@ impl Manager {
 	}
 }
 
+/// Playlist file found in a mount directory
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FolderPlaylist {
+	pub virtual_path: PathBuf,
+	/// Songs listed in the playlist file, which may not all exist in the collection
+	pub songs: Vec<PathBuf>,
+	/// Total duration of the listed songs which exist in the collection
+	pub duration: Duration,
+	pub num_songs_by_genre: BTreeMap<String, u32>,
+}
+
+/// Outcome of looking for songs which were moved or renamed during a scan
+#[derive(Debug, Default)]
+pub struct Relinking {
+	pub new_locations: HashMap<PathBuf, PathBuf>,
+	/// Songs which could not be found, to look for again after later scans
+	pub missing_songs: Vec<Song>,
+}
+
+/// One page of search results, computed against a specific version of the index
+#[derive(Debug, PartialEq, Eq)]
+pub struct SearchPage {
+	pub songs: Vec<SearchResult>,
+	pub total: usize,
+	pub generation: u64,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Index {
 	pub dictionary: dictionary::Dictionary,
 	pub browser: browser::Browser,
 	pub collection: collection::Collection,
 	pub search: search::Search,
+	pub suggestions: suggest::Suggestions,
+	// Sorted by virtual path
+	pub folder_playlists: Vec<FolderPlaylist>,
+	// Derived from the indexed songs, so that it only changes when their content does
+	pub generation: u64,
+}
+
+impl Index {
+	fn get_song(&self, virtual_path: &Path) -> Option<Song> {
+		let key = SongKey {
+			virtual_path: virtual_path.get(&self.dictionary)?,
+		};
+		self.collection.get_song(&self.dictionary, key)
+	}
+
+	fn collapse_duplicate_results(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+		let mut seen = HashSet::new();
+		results
+			.into_iter()
+			.filter_map(|mut result| {
+				result.song = self
+					.collection
+					.get_preferred_song(&self.dictionary, result.song);
+				seen.insert(result.song.virtual_path.clone())
+					.then_some(result)
+			})
+			.collect()
+	}
 }
 
 impl Default for Index {
@@ -318,6 +774,9 @@ impl Default for Index {
 			browser: Default::default(),
 			collection: Default::default(),
 			search: Default::default(),
+			suggestions: Default::default(),
+			folder_playlists: Default::default(),
+			generation: 0,
 		}
 	}
 }
@@ -328,6 +787,10 @@ pub struct Builder {
 	browser_builder: browser::Builder,
 	collection_builder: collection::Builder,
 	search_builder: search::Builder,
+	suggest_builder: suggest::Builder,
+	genre_aliases: HashMap<String, String>,
+	folder_playlists: Vec<FolderPlaylist>,
+	generation: u64,
 }
 
 impl Builder {
@@ -337,29 +800,130 @@ impl Builder {
 			browser_builder: browser::Builder::default(),
 			collection_builder: collection::Builder::default(),
 			search_builder: search::Builder::default(),
+			suggest_builder: suggest::Builder::default(),
+			genre_aliases: HashMap::new(),
+			folder_playlists: Vec::new(),
+			generation: 0,
+		}
+	}
+
+	pub fn set_compilation_artists(&mut self, names: Vec<String>) {
+		self.collection_builder.set_compilation_artists(names);
+	}
+
+	pub fn set_transliterations(&mut self, transliterations: HashMap<char, String>) {
+		self.dictionary_builder
+			.set_transliterations(transliterations);
+	}
+
+	pub fn set_genres(&mut self, genres: Vec<config::storage::Genre>) {
+		let mut parents = Vec::new();
+		for genre in genres {
+			// Interning configured names first makes them the canonical spelling
+			self.dictionary_builder.get_or_intern_canon(&genre.name);
+			for alias in &genre.aliases {
+				self.genre_aliases
+					.insert(self.dictionary_builder.sanitize(alias), genre.name.clone());
+			}
+			if let Some(parent) = genre.parent {
+				self.dictionary_builder.get_or_intern_canon(&parent);
+				parents.push((genre.name, parent));
+			}
 		}
+		self.collection_builder.set_genre_parents(parents);
 	}
 
-	pub fn add_directory(&mut self, directory: scanner::Directory) {
+	pub fn add_directory(&mut self, mut directory: scanner::Directory) {
+		self.folder_playlists
+			.extend(directory.playlists.drain(..).map(|p| FolderPlaylist {
+				virtual_path: p.virtual_path,
+				songs: p.songs,
+				..Default::default()
+			}));
+		if !directory.images.is_empty() {
+			if let Some(virtual_path) =
+				(&directory.virtual_path).get_or_intern(&mut self.dictionary_builder)
+			{
+				let images = directory
+					.images
+					.drain(..)
+					.filter_map(|i| store_image(&mut self.dictionary_builder, &i))
+					.collect();
+				self.collection_builder
+					.add_directory_images(virtual_path, images);
+			}
+		}
 		self.browser_builder
 			.add_directory(&mut self.dictionary_builder, directory);
 	}
 
-	pub fn add_song(&mut self, scanner_song: scanner::Song) {
+	pub fn add_song(&mut self, mut scanner_song: scanner::Song) {
+		for genre in &mut scanner_song.genres {
+			if let Some(name) = self
+				.genre_aliases
+				.get(&self.dictionary_builder.sanitize(genre.as_str()))
+			{
+				genre.clone_from(name);
+			}
+		}
+		let mut seen_genres = HashSet::new();
+		scanner_song
+			.genres
+			.retain(|g| seen_genres.insert(self.dictionary_builder.sanitize(g)));
+
+		// Songs arrive in no particular order, so their hashes are combined commutatively
+		let mut hasher = DefaultHasher::new();
+		scanner_song.hash(&mut hasher);
+		self.generation = self.generation.wrapping_add(hasher.finish());
+
 		if let Some(storage_song) = store_song(&mut self.dictionary_builder, &scanner_song) {
 			self.browser_builder
 				.add_song(&mut self.dictionary_builder, &scanner_song);
 			self.collection_builder.add_song(&storage_song);
-			self.search_builder.add_song(&scanner_song, &storage_song);
+			if let Some(fingerprint) = scanner_song.fingerprint.clone() {
+				self.collection_builder
+					.add_fingerprint(&storage_song, fingerprint);
+			}
+			self.search_builder
+				.add_song(&self.dictionary_builder, &scanner_song, &storage_song);
+			self.suggest_builder
+				.add_song(&self.dictionary_builder, &scanner_song, &storage_song);
 		}
 	}
 
-	pub fn build(self) -> Index {
+	pub fn build(mut self) -> Index {
+		let dictionary = self.dictionary_builder.build();
+		let collection = self.collection_builder.build(&dictionary);
+
+		self.folder_playlists
+			.sort_by(|a, b| a.virtual_path.cmp(&b.virtual_path));
+		for playlist in &mut self.folder_playlists {
+			let songs = playlist
+				.songs
+				.iter()
+				.filter_map(|p| p.get(&dictionary))
+				.filter_map(|virtual_path| collection.get_stored_song(SongKey { virtual_path }));
+			let mut duration = 0;
+			for song in songs {
+				duration += song.duration.map(|d| d as u64).unwrap_or_default();
+				for genre in &song.genres {
+					*playlist
+						.num_songs_by_genre
+						.entry(dictionary.resolve(genre).to_owned())
+						.or_default() += 1;
+				}
+			}
+			playlist.duration = Duration::from_secs(duration);
+		}
+
 		Index {
-			dictionary: self.dictionary_builder.build(),
 			browser: self.browser_builder.build(),
-			collection: self.collection_builder.build(),
+			collection,
 			search: self.search_builder.build(),
+			suggestions: self.suggest_builder.build(),
+			folder_playlists: self.folder_playlists,
+			generation: self.generation,
+			dictionary,
 		}
 	}
 }
@@ -372,11 +936,15 @@ impl Default for Builder {
 
 #[cfg(test)]
 mod test {
+	use std::collections::HashMap;
+
 	use crate::{
-		app::{index, test},
+		app::{config, index, scanner, test},
 		test_name,
 	};
 
+	use super::GenreKey;
+
 	#[tokio::test]
 	async fn can_persist_index() {
 		let ctx = test::ContextBuilder::new(test_name!()).build().await;
@@ -385,4 +953,104 @@ mod test {
 		ctx.index_manager.persist_index(&index).await.unwrap();
 		assert_eq!(ctx.index_manager.try_restore_index().await.unwrap(), true);
 	}
+
+	#[tokio::test]
+	async fn search_pages_track_index_generation() {
+		let ctx = test::ContextBuilder::new(test_name!()).build().await;
+		let make_index = || {
+			let mut builder = index::Builder::new();
+			for i in 1..=3 {
+				builder.add_song(scanner::Song {
+					virtual_path: format!("{i}.mp3").into(),
+					title: Some(format!("Song {i}")),
+					..Default::default()
+				});
+			}
+			builder.build()
+		};
+		ctx.index_manager.replace_index(make_index()).await;
+
+		let search = |offset, limit| {
+			ctx.index_manager.search(
+				"song".to_owned(),
+				index::SearchOrder::Alphabetical,
+				false,
+				offset,
+				limit,
+			)
+		};
+
+		let page = search(1, Some(1)).await.unwrap();
+		assert_eq!(page.total, 3);
+		assert_eq!(page.songs.len(), 1);
+		assert_eq!(page.songs[0].song.title, Some("Song 2".to_owned()));
+
+		let page = search(2, None).await.unwrap();
+		assert_eq!(page.songs.len(), 1);
+
+		// Rebuilding identical content keeps the generation
+		let generation = page.generation;
+		ctx.index_manager.replace_index(make_index()).await;
+		let page = search(0, None).await.unwrap();
+		assert_eq!(page.generation, generation);
+
+		ctx.index_manager
+			.replace_index(index::Builder::new().build())
+			.await;
+		let page = search(0, None).await.unwrap();
+		assert_ne!(page.generation, generation);
+		assert_eq!(page.total, 0);
+	}
+
+	#[test]
+	fn genres_follow_taxonomy() {
+		let mut builder = index::Builder::new();
+		builder.set_genres(vec![
+			config::storage::Genre {
+				name: "Metal".to_owned(),
+				..Default::default()
+			},
+			config::storage::Genre {
+				name: "Melodic Death Metal".to_owned(),
+				aliases: vec!["Melodeath".to_owned()],
+				parent: Some("Metal".to_owned()),
+			},
+		]);
+		builder.add_song(scanner::Song {
+			virtual_path: ["Insomnium", "Ephemeral.mp3"].iter().collect(),
+			artists: vec!["Insomnium".to_owned()],
+			genres: vec!["melodeath".to_owned(), "Folk".to_owned()],
+			..Default::default()
+		});
+		builder.add_song(scanner::Song {
+			virtual_path: ["Stratovarius", "Fantasy.mp3"].iter().collect(),
+			artists: vec!["Stratovarius".to_owned()],
+			genres: vec!["metal".to_owned()],
+			..Default::default()
+		});
+		let index = builder.build();
+
+		let key = |name: &str| GenreKey(index.dictionary.get(name).unwrap());
+		let collection = &index.collection;
+
+		let melodeath = collection
+			.get_genre(&index.dictionary, key("Melodic Death Metal"), false)
+			.unwrap();
+		assert_eq!(melodeath.header.parent, Some("Metal".to_owned()));
+		assert_eq!(melodeath.songs.len(), 1);
+
+		let metal = collection
+			.get_genre(&index.dictionary, key("Metal"), false)
+			.unwrap();
+		assert_eq!(metal.songs.len(), 1);
+		assert_eq!(
+			metal.related_genres,
+			HashMap::from_iter([("Folk".to_owned(), 1)])
+		);
+
+		let metal = collection
+			.get_genre(&index.dictionary, key("Metal"), true)
+			.unwrap();
+		assert_eq!(metal.songs.len(), 2);
+	}
 }
@@ -225,6 +225,7 @@ mod test {
 				&mut dictionary_builder,
 				scanner::Directory {
 					virtual_path: directory.to_owned(),
+					..Default::default()
 				},
 			);
 		}
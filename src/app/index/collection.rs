@@ -1,24 +1,42 @@
 use std::{
-	borrow::BorrowMut,
+	borrow::{BorrowMut, Cow},
 	cmp::Ordering,
 	collections::{HashMap, HashSet},
-	path::PathBuf,
+	hash::Hash,
+	path::{Path, PathBuf},
+	time::{SystemTime, UNIX_EPOCH},
 };
 
+use enum_map::{Enum, EnumMap};
+use lasso2::Spur;
 use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use rayon::slice::ParallelSliceMut;
 use serde::{Deserialize, Serialize};
 use tinyvec::TinyVec;
 use unicase::UniCase;
 
+use crate::app::formats::PictureType;
 use crate::app::index::dictionary::Dictionary;
-use crate::app::index::storage::{self, AlbumKey, ArtistKey, GenreKey, SongKey};
+use crate::app::index::storage::{
+	self, AlbumKey, ArtistKey, GenreKey, InternPath, LabelKey, PathKey, SongKey,
+};
+use crate::app::peaks::Fingerprint;
+
+use super::{
+	dictionary, duplicates,
+	genres::Taxonomy,
+	health::{self, Rule},
+	query::{Direction, Field, NumberField, TextField},
+	stats::{self, Stats},
+	storage::fetch_song,
+};
 
-use super::{dictionary, storage::fetch_song};
+const MOSAIC_SIZE: usize = 4;
 
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct GenreHeader {
 	pub name: String,
+	pub parent: Option<String>,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -30,15 +48,43 @@ pub struct Genre {
 	pub songs: Vec<Song>,
 }
 
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LabelHeader {
+	pub name: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Label {
+	pub header: LabelHeader,
+	pub albums: Vec<AlbumHeader>,
+	pub artists: Vec<ArtistHeader>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DecadeHeader {
+	pub decade: i64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Decade {
+	pub header: DecadeHeader,
+	pub albums: Vec<AlbumHeader>,
+	pub artists: Vec<ArtistHeader>,
+}
+
 #[derive(Debug, Default, PartialEq, Eq)]
 pub struct ArtistHeader {
 	pub name: UniCase<String>,
+	pub artwork: Option<PathBuf>,
+	pub mosaic: Vec<PathBuf>,
 	pub num_albums_as_performer: u32,
 	pub num_albums_as_additional_performer: u32,
 	pub num_albums_as_composer: u32,
 	pub num_albums_as_lyricist: u32,
 	pub num_songs_by_genre: HashMap<String, u32>,
 	pub num_songs: u32,
+	pub num_discs: u32,
+	pub duration: i64,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -54,6 +100,16 @@ pub struct AlbumHeader {
 	pub artists: Vec<String>,
 	pub year: Option<i64>,
 	pub date_added: i64,
+	pub num_songs_by_genre: HashMap<String, u32>,
+	pub num_songs: u32,
+	pub num_discs: u32,
+	pub duration: i64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AlbumHealth {
+	pub album: AlbumHeader,
+	pub issues: Vec<Rule>,
 }
 
 #[derive(Debug, Default, PartialEq, Eq)]
@@ -62,6 +118,13 @@ pub struct Album {
 	pub songs: Vec<Song>,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct Image {
+	pub path: PathBuf,
+	pub picture_type: PictureType,
+	pub picture_index: Option<u32>,
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Song {
 	pub real_path: PathBuf,
@@ -82,38 +145,157 @@ pub struct Song {
 	pub date_added: i64,
 }
 
+#[derive(Clone, Copy, Debug, Default, Deserialize, Enum, Eq, Hash, PartialEq, Serialize)]
+pub enum SortKey {
+	#[default]
+	Name,
+	Year,
+	DateAdded,
+	Artist,
+	NumSongs,
+	Duration,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SortOrder {
+	#[default]
+	Ascending,
+	Descending,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ListOptions {
+	pub sort: SortKey,
+	pub order: SortOrder,
+	pub offset: usize,
+	pub count: Option<usize>,
+}
+
+impl ListOptions {
+	fn paginate<'a, T>(&self, items: &'a [T]) -> Vec<&'a T> {
+		let len = items.len();
+		let start = self.offset.min(len);
+		let end = start.saturating_add(self.count.unwrap_or(len)).min(len);
+		match self.order {
+			SortOrder::Ascending => items[start..end].iter().collect(),
+			SortOrder::Descending => items[(len - end)..(len - start)].iter().rev().collect(),
+		}
+	}
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct Collection {
 	artists: HashMap<ArtistKey, storage::Artist>,
 	albums: HashMap<AlbumKey, storage::Album>,
 	genres: HashMap<GenreKey, storage::Genre>,
+	labels: HashMap<LabelKey, storage::Label>,
+	decades: HashMap<i64, storage::Decade>,
 	songs: HashMap<SongKey, storage::Song>,
 	recent_albums: Vec<AlbumKey>,
+	album_orderings: EnumMap<SortKey, Vec<AlbumKey>>,
+	artist_orderings: EnumMap<SortKey, Vec<ArtistKey>>,
+	genre_album_orderings: HashMap<GenreKey, EnumMap<SortKey, Vec<AlbumKey>>>,
+	genre_artist_orderings: HashMap<GenreKey, EnumMap<SortKey, Vec<ArtistKey>>>,
+	stats: Stats,
+	health: HashMap<AlbumKey, Vec<Rule>>,
+	duplicates: Vec<Vec<SongKey>>,
+	// Position of each duplicated song's group within `duplicates`
+	duplicate_groups: HashMap<SongKey, usize>,
+	genre_taxonomy: Taxonomy,
 }
 
 impl Collection {
-	pub fn get_albums(&self, dictionary: &Dictionary) -> Vec<AlbumHeader> {
-		let mut albums = self
-			.albums
-			.values()
+	pub fn get_stats(&self) -> Stats {
+		self.stats.clone()
+	}
+
+	pub fn get_health(&self, dictionary: &Dictionary, rule: Option<Rule>) -> Vec<AlbumHealth> {
+		self.album_orderings[SortKey::Name]
+			.iter()
+			.filter_map(|k| Some((self.albums.get(k)?, self.health.get(k)?)))
+			.filter(|(_, issues)| rule.is_none_or(|r| issues.contains(&r)))
+			.map(|(album, issues)| AlbumHealth {
+				album: make_album_header(album, dictionary),
+				issues: issues.clone(),
+			})
+			.collect()
+	}
+
+	pub fn get_duplicates(&self, dictionary: &Dictionary) -> Vec<Vec<Song>> {
+		self.duplicates
+			.iter()
+			.map(|group| {
+				group
+					.iter()
+					.filter_map(|k| self.get_song(dictionary, *k))
+					.collect()
+			})
+			.collect()
+	}
+
+	// Replaces songs of an album by their best available copy within the same album, omitting
+	// copies listed more than once
+	pub fn collapse_album_duplicates(
+		&self,
+		dictionary: &Dictionary,
+		songs: Vec<Song>,
+	) -> Vec<Song> {
+		let mut seen = HashSet::new();
+		songs
+			.into_iter()
+			.map(|song| self.find_preferred_song(dictionary, song, true))
+			.filter(|song| seen.insert(song.virtual_path.clone()))
+			.collect()
+	}
+
+	// Best copy of a song, or the song itself if it has no duplicates
+	pub fn get_preferred_song(&self, dictionary: &Dictionary, song: Song) -> Song {
+		self.find_preferred_song(dictionary, song, false)
+	}
+
+	pub fn get_preferred_song_key(&self, song_key: SongKey) -> SongKey {
+		self.duplicate_groups
+			.get(&song_key)
+			.and_then(|group| self.duplicates[*group].first())
+			.copied()
+			.unwrap_or(song_key)
+	}
+
+	fn find_preferred_song(&self, dictionary: &Dictionary, song: Song, same_album: bool) -> Song {
+		let song_key = (&song.virtual_path)
+			.get(dictionary)
+			.map(|virtual_path| SongKey { virtual_path });
+		let album_key = song_key
+			.and_then(|k| self.songs.get(&k))
+			.and_then(|s| s.album_key());
+		song_key
+			.and_then(|k| self.duplicate_groups.get(&k))
+			.and_then(|group| {
+				// Groups are sorted from best to worst copy
+				self.duplicates[*group].iter().find(|k| {
+					!same_album || self.songs.get(k).and_then(|s| s.album_key()) == album_key
+				})
+			})
+			.and_then(|k| self.get_song(dictionary, *k))
+			.unwrap_or(song)
+	}
+
+	pub fn get_albums(&self, dictionary: &Dictionary, options: &ListOptions) -> Vec<AlbumHeader> {
+		options
+			.paginate(&self.album_orderings[options.sort])
+			.into_iter()
+			.filter_map(|k| self.albums.get(k))
 			.map(|a| make_album_header(a, dictionary))
-			.collect::<Vec<_>>();
-		let collator = dictionary::make_collator();
-		albums.sort_by(|a, b| collator.compare(&a.name, &b.name));
-		albums
+			.collect()
 	}
 
-	pub fn get_artists(&self, dictionary: &Dictionary) -> Vec<ArtistHeader> {
-		let exceptions = [dictionary.get("Various Artists"), dictionary.get("VA")];
-		let mut artists = self
-			.artists
-			.values()
-			.filter(|a| !exceptions.contains(&Some(a.name)))
+	pub fn get_artists(&self, dictionary: &Dictionary, options: &ListOptions) -> Vec<ArtistHeader> {
+		options
+			.paginate(&self.artist_orderings[options.sort])
+			.into_iter()
+			.filter_map(|k| self.artists.get(k))
 			.map(|a| make_artist_header(a, dictionary))
-			.collect::<Vec<_>>();
-		let collator = dictionary::make_collator();
-		artists.sort_by(|a, b| collator.compare(&a.name, &b.name));
-		artists
+			.collect()
 	}
 
 	pub fn get_artist(&self, dictionary: &Dictionary, artist_key: ArtistKey) -> Option<Artist> {
@@ -160,6 +342,23 @@ impl Collection {
 		})
 	}
 
+	pub fn get_album_images(
+		&self,
+		dictionary: &Dictionary,
+		album_key: AlbumKey,
+	) -> Option<Vec<Image>> {
+		self.albums.get(&album_key).map(|a| {
+			a.images
+				.iter()
+				.map(|i| Image {
+					path: PathBuf::from(dictionary.resolve(&i.path.0)),
+					picture_type: i.picture_type,
+					picture_index: i.picture_index,
+				})
+				.collect()
+		})
+	}
+
 	pub fn get_random_albums(
 		&self,
 		dictionary: &Dictionary,
@@ -203,46 +402,45 @@ impl Collection {
 		let mut genres = self
 			.genres
 			.values()
-			.filter(|g| !g.albums.is_empty())
-			.map(|g| make_genre_header(g, dictionary))
+			.filter(|g| {
+				let genre_key = GenreKey(g.name);
+				std::iter::once(genre_key)
+					.chain(self.genre_taxonomy.descendants(genre_key))
+					.filter_map(|k| self.genres.get(&k))
+					.any(|g| !g.albums.is_empty())
+			})
+			.map(|g| make_genre_header(g, &self.genre_taxonomy, dictionary))
 			.collect::<Vec<_>>();
 		let collator = dictionary::make_collator();
 		genres.sort_by(|a, b| collator.compare(&a.name, &b.name));
 		genres
 	}
 
-	pub fn get_genre(&self, dictionary: &Dictionary, genre_key: GenreKey) -> Option<Genre> {
+	pub fn get_genre(
+		&self,
+		dictionary: &Dictionary,
+		genre_key: GenreKey,
+		include_subgenres: bool,
+	) -> Option<Genre> {
 		self.genres.get(&genre_key).map(|genre| {
-			let collator = dictionary::make_collator();
-
-			let mut albums = genre
-				.albums
-				.iter()
-				.filter_map(|album_key| {
-					self.albums
-						.get(album_key)
-						.map(|a| make_album_header(a, dictionary))
-				})
-				.collect::<Vec<_>>();
-			albums.sort_by(|a, b| collator.compare(&a.name, &b.name));
-
-			let mut artists = genre
-				.artists
-				.iter()
-				.filter_map(|artist_key| {
-					self.artists
-						.get(artist_key)
-						.map(|a| make_artist_header(a, dictionary))
-				})
-				.collect::<Vec<_>>();
-			artists.sort_by(|a, b| collator.compare(&a.name, &b.name));
+			let mut album_keys = genre.albums.clone();
+			let mut artist_keys = genre.artists.clone();
+			let mut song_keys = genre.songs.iter().copied().collect::<HashSet<_>>();
+			if include_subgenres {
+				for subgenre in self.get_genre_descendants(genre_key) {
+					let Some(subgenre) = self.genres.get(&subgenre) else {
+						continue;
+					};
+					album_keys.extend(subgenre.albums.iter().cloned());
+					artist_keys.extend(subgenre.artists.iter().copied());
+					song_keys.extend(subgenre.songs.iter().copied());
+				}
+			}
 
-			let mut songs = genre.songs.to_vec();
-			self.sort_songs(&mut songs, dictionary);
-			let songs = songs
-				.into_iter()
-				.filter_map(|k| self.get_song(dictionary, k))
-				.collect::<Vec<_>>();
+			let albums = self.get_album_headers(dictionary, &album_keys);
+			let artists = self.get_artist_headers(dictionary, &artist_keys);
+			let songs =
+				self.get_sorted_songs(dictionary, &song_keys.into_iter().collect::<Vec<_>>());
 
 			let related_genres = genre
 				.related_genres
@@ -253,7 +451,7 @@ impl Collection {
 				.collect();
 
 			Genre {
-				header: make_genre_header(genre, dictionary),
+				header: make_genre_header(genre, &self.genre_taxonomy, dictionary),
 				albums,
 				artists,
 				related_genres,
@@ -262,6 +460,256 @@ impl Collection {
 		})
 	}
 
+	pub fn get_genre_descendants(&self, genre_key: GenreKey) -> Vec<GenreKey> {
+		self.genre_taxonomy.descendants(genre_key)
+	}
+
+	fn get_genre_subtree(&self, genre_key: GenreKey) -> impl Iterator<Item = GenreKey> {
+		std::iter::once(genre_key).chain(self.get_genre_descendants(genre_key))
+	}
+
+	pub fn get_labels(&self, dictionary: &Dictionary) -> Vec<LabelHeader> {
+		let mut labels = self
+			.labels
+			.values()
+			.map(|l| LabelHeader {
+				name: dictionary.resolve(&l.name).to_string(),
+			})
+			.collect::<Vec<_>>();
+		let collator = dictionary::make_collator();
+		labels.sort_by(|a, b| collator.compare(&a.name, &b.name));
+		labels
+	}
+
+	pub fn get_label(&self, dictionary: &Dictionary, label_key: LabelKey) -> Option<Label> {
+		self.labels.get(&label_key).map(|label| Label {
+			header: LabelHeader {
+				name: dictionary.resolve(&label.name).to_string(),
+			},
+			albums: self.get_album_headers(dictionary, &label.albums),
+			artists: self.get_artist_headers(dictionary, &label.artists),
+		})
+	}
+
+	pub fn get_decades(&self) -> Vec<DecadeHeader> {
+		let mut decades = self
+			.decades
+			.keys()
+			.map(|decade| DecadeHeader { decade: *decade })
+			.collect::<Vec<_>>();
+		decades.sort_by_key(|d| d.decade);
+		decades
+	}
+
+	pub fn get_decade(&self, dictionary: &Dictionary, decade: i64) -> Option<Decade> {
+		self.decades.get(&decade).map(|d| Decade {
+			header: DecadeHeader { decade },
+			albums: self.get_album_headers(dictionary, &d.albums),
+			artists: self.get_artist_headers(dictionary, &d.artists),
+		})
+	}
+
+	pub fn get_compilations(
+		&self,
+		dictionary: &Dictionary,
+		options: &ListOptions,
+	) -> Vec<AlbumHeader> {
+		let albums = self.album_orderings[options.sort]
+			.iter()
+			.filter_map(|k| self.albums.get(k))
+			.filter(|a| a.compilation)
+			.collect::<Vec<_>>();
+		options
+			.paginate(&albums)
+			.into_iter()
+			.map(|a| make_album_header(a, dictionary))
+			.collect()
+	}
+
+	pub fn get_composers(
+		&self,
+		dictionary: &Dictionary,
+		options: &ListOptions,
+	) -> Vec<ArtistHeader> {
+		self.get_artists_where(dictionary, options, |a| !a.albums_as_composer.is_empty())
+	}
+
+	pub fn get_lyricists(
+		&self,
+		dictionary: &Dictionary,
+		options: &ListOptions,
+	) -> Vec<ArtistHeader> {
+		self.get_artists_where(dictionary, options, |a| !a.albums_as_lyricist.is_empty())
+	}
+
+	fn get_artists_where<F>(
+		&self,
+		dictionary: &Dictionary,
+		options: &ListOptions,
+		predicate: F,
+	) -> Vec<ArtistHeader>
+	where
+		F: Fn(&storage::Artist) -> bool,
+	{
+		let artists = self.artist_orderings[options.sort]
+			.iter()
+			.filter_map(|k| self.artists.get(k))
+			.filter(|a| predicate(a))
+			.collect::<Vec<_>>();
+		options
+			.paginate(&artists)
+			.into_iter()
+			.map(|a| make_artist_header(a, dictionary))
+			.collect()
+	}
+
+	pub fn get_artist_header(
+		&self,
+		dictionary: &Dictionary,
+		artist_key: ArtistKey,
+	) -> Option<ArtistHeader> {
+		self.artists
+			.get(&artist_key)
+			.map(|a| make_artist_header(a, dictionary))
+	}
+
+	pub fn get_genre_header(
+		&self,
+		dictionary: &Dictionary,
+		genre_key: GenreKey,
+	) -> Option<GenreHeader> {
+		self.genres
+			.get(&genre_key)
+			.map(|g| make_genre_header(g, &self.genre_taxonomy, dictionary))
+	}
+
+	// Albums containing any of the given songs
+	pub fn get_song_albums<'a>(
+		&self,
+		dictionary: &Dictionary,
+		song_keys: impl IntoIterator<Item = &'a SongKey>,
+	) -> Vec<AlbumHeader> {
+		let album_keys = song_keys
+			.into_iter()
+			.filter_map(|k| self.songs.get(k))
+			.filter_map(|s| s.album_key())
+			.collect::<HashSet<_>>();
+		self.get_album_headers(dictionary, &album_keys)
+	}
+
+	fn get_album_headers(
+		&self,
+		dictionary: &Dictionary,
+		keys: &HashSet<AlbumKey>,
+	) -> Vec<AlbumHeader> {
+		let collator = dictionary::make_collator();
+		let mut albums = keys
+			.iter()
+			.filter_map(|k| self.albums.get(k))
+			.map(|a| make_album_header(a, dictionary))
+			.collect::<Vec<_>>();
+		albums.sort_by(|a, b| collator.compare(&a.name, &b.name));
+		albums
+	}
+
+	fn get_artist_headers(
+		&self,
+		dictionary: &Dictionary,
+		keys: &HashSet<ArtistKey>,
+	) -> Vec<ArtistHeader> {
+		let collator = dictionary::make_collator();
+		let mut artists = keys
+			.iter()
+			.filter_map(|k| self.artists.get(k))
+			.map(|a| make_artist_header(a, dictionary))
+			.collect::<Vec<_>>();
+		artists.sort_by(|a, b| collator.compare(&a.name, &b.name));
+		artists
+	}
+
+	fn get_sorted_songs(&self, dictionary: &Dictionary, keys: &[SongKey]) -> Vec<Song> {
+		let mut songs = keys.to_vec();
+		self.sort_songs(&mut songs, dictionary);
+		songs
+			.into_iter()
+			.filter_map(|k| self.get_song(dictionary, k))
+			.collect()
+	}
+
+	pub fn get_genre_albums(
+		&self,
+		dictionary: &Dictionary,
+		genre_key: GenreKey,
+		include_subgenres: bool,
+		options: &ListOptions,
+	) -> Option<Vec<AlbumHeader>> {
+		self.genres.get(&genre_key).map(|_| {
+			let keys: Cow<[AlbumKey]> = match include_subgenres {
+				false => self
+					.genre_album_orderings
+					.get(&genre_key)
+					.map(|o| Cow::Borrowed(o[options.sort].as_slice()))
+					.unwrap_or_default(),
+				// Subgenres overlap, so the subtree is carved out of the global ordering
+				true => {
+					let members = self
+						.get_genre_subtree(genre_key)
+						.filter_map(|k| self.genres.get(&k))
+						.flat_map(|g| g.albums.iter())
+						.collect::<HashSet<_>>();
+					self.album_orderings[options.sort]
+						.iter()
+						.filter(|k| members.contains(k))
+						.cloned()
+						.collect()
+				}
+			};
+			options
+				.paginate(&keys)
+				.into_iter()
+				.filter_map(|k| self.albums.get(k))
+				.map(|a| make_album_header(a, dictionary))
+				.collect()
+		})
+	}
+
+	pub fn get_genre_artists(
+		&self,
+		dictionary: &Dictionary,
+		genre_key: GenreKey,
+		include_subgenres: bool,
+		options: &ListOptions,
+	) -> Option<Vec<ArtistHeader>> {
+		self.genres.get(&genre_key).map(|_| {
+			let keys: Cow<[ArtistKey]> = match include_subgenres {
+				false => self
+					.genre_artist_orderings
+					.get(&genre_key)
+					.map(|o| Cow::Borrowed(o[options.sort].as_slice()))
+					.unwrap_or_default(),
+				// Subgenres overlap, so the subtree is carved out of the global ordering
+				true => {
+					let members = self
+						.get_genre_subtree(genre_key)
+						.filter_map(|k| self.genres.get(&k))
+						.flat_map(|g| g.artists.iter())
+						.collect::<HashSet<_>>();
+					self.artist_orderings[options.sort]
+						.iter()
+						.filter(|k| members.contains(k))
+						.cloned()
+						.collect()
+				}
+			};
+			options
+				.paginate(&keys)
+				.into_iter()
+				.filter_map(|k| self.artists.get(k))
+				.map(|a| make_artist_header(a, dictionary))
+				.collect()
+		})
+	}
+
 	pub fn num_songs(&self) -> usize {
 		self.songs.len()
 	}
@@ -270,11 +718,48 @@ impl Collection {
 		self.songs.get(&song_key).map(|s| fetch_song(dictionary, s))
 	}
 
+	pub fn get_stored_song(&self, song_key: SongKey) -> Option<&storage::Song> {
+		self.songs.get(&song_key)
+	}
+
 	pub fn sort_songs(&self, songs: &mut [SongKey], dictionary: &Dictionary) {
 		songs.par_sort_unstable_by(|a, b| self.compare_songs(*a, *b, dictionary));
 	}
 
-	fn compare_songs(&self, a: SongKey, b: SongKey, dictionary: &Dictionary) -> Ordering {
+	pub fn get_song_keys(&self) -> impl Iterator<Item = SongKey> + '_ {
+		self.songs.keys().copied()
+	}
+
+	/// Stable sort, so that songs with equal values keep their previous order
+	pub fn sort_songs_by_fields(
+		&self,
+		songs: &mut [SongKey],
+		dictionary: &Dictionary,
+		fields: &[(Field, Direction)],
+	) {
+		if fields.is_empty() {
+			return;
+		}
+		songs.par_sort_by(|a, b| {
+			let (a, b) = match (self.songs.get(a), self.songs.get(b)) {
+				(Some(a), Some(b)) => (a, b),
+				_ => return Ordering::Equal,
+			};
+			for (field, direction) in fields {
+				let ordering = compare_song_fields(a, b, *field, dictionary);
+				let ordering = match direction {
+					Direction::Ascending => ordering,
+					Direction::Descending => ordering.reverse(),
+				};
+				if ordering != Ordering::Equal {
+					return ordering;
+				}
+			}
+			Ordering::Equal
+		});
+	}
+
+	pub fn compare_songs(&self, a: SongKey, b: SongKey, dictionary: &Dictionary) -> Ordering {
 		let (a, b) = match (self.songs.get(&a), self.songs.get(&b)) {
 			(None, None) => return Ordering::Equal,
 			(None, Some(_)) => return Ordering::Less,
@@ -294,14 +779,7 @@ impl Collection {
 			&b.album_artists
 		};
 
-		for (a_artist, b_artist) in a_artists.iter().zip(b_artists) {
-			match dictionary.cmp(&a_artist.0, &b_artist.0) {
-				Ordering::Equal => (),
-				o => return o,
-			}
-		}
-
-		match a_artists.len().cmp(&b_artists.len()) {
+		match compare_artists(a_artists, b_artists, dictionary) {
 			Ordering::Equal => (),
 			o => return o,
 		}
@@ -322,8 +800,57 @@ impl Collection {
 		let a_key = (a.disc_number, a.track_number);
 		let b_key = (b.disc_number, b.track_number);
 
-		a_key.cmp(&b_key)
+		// Paths break ties so that song lists are sorted identically every time
+		a_key
+			.cmp(&b_key)
+			.then_with(|| dictionary.cmp(&a.virtual_path.0, &b.virtual_path.0))
+	}
+}
+
+fn compare_song_fields(
+	a: &storage::Song,
+	b: &storage::Song,
+	field: Field,
+	dictionary: &Dictionary,
+) -> Ordering {
+	let compare_strings = |a: &[Spur], b: &[Spur]| {
+		for (a, b) in a.iter().zip(b) {
+			match dictionary.cmp(a, b) {
+				Ordering::Equal => (),
+				o => return o,
+			}
+		}
+		a.len().cmp(&b.len())
+	};
+	match field {
+		Field::Text(TextField::Album) => compare_strings(a.album.as_slice(), b.album.as_slice()),
+		Field::Text(TextField::AlbumArtist) => {
+			compare_artists(&a.album_artists, &b.album_artists, dictionary)
+		}
+		Field::Text(TextField::Artist) => compare_artists(&a.artists, &b.artists, dictionary),
+		Field::Text(TextField::Composer) => compare_artists(&a.composers, &b.composers, dictionary),
+		Field::Text(TextField::Genre) => compare_strings(&a.genres, &b.genres),
+		Field::Text(TextField::Label) => compare_strings(&a.labels, &b.labels),
+		Field::Text(TextField::Lyricist) => compare_artists(&a.lyricists, &b.lyricists, dictionary),
+		Field::Text(TextField::Path) => dictionary.cmp(&a.virtual_path.0, &b.virtual_path.0),
+		Field::Text(TextField::Title) => compare_strings(a.title.as_slice(), b.title.as_slice()),
+		Field::Number(NumberField::Bitrate) => a.bitrate().cmp(&b.bitrate()),
+		Field::Number(NumberField::DateAdded) => a.date_added.cmp(&b.date_added),
+		Field::Number(NumberField::DiscNumber) => a.disc_number.cmp(&b.disc_number),
+		Field::Number(NumberField::Duration) => a.duration.cmp(&b.duration),
+		Field::Number(NumberField::TrackNumber) => a.track_number.cmp(&b.track_number),
+		Field::Number(NumberField::Year) => a.year.cmp(&b.year),
+	}
+}
+
+fn compare_artists(a: &[ArtistKey], b: &[ArtistKey], dictionary: &Dictionary) -> Ordering {
+	for (a_artist, b_artist) in a.iter().zip(b) {
+		match dictionary.cmp(&a_artist.0, &b_artist.0) {
+			Ordering::Equal => (),
+			o => return o,
+		}
 	}
+	a.len().cmp(&b.len())
 }
 
 fn make_album_header(album: &storage::Album, dictionary: &Dictionary) -> AlbumHeader {
@@ -341,12 +868,30 @@ fn make_album_header(album: &storage::Album, dictionary: &Dictionary) -> AlbumHe
 			.collect(),
 		year: album.year,
 		date_added: album.date_added,
+		num_songs_by_genre: album
+			.num_songs_by_genre
+			.iter()
+			.map(|(genre, num)| (dictionary.resolve(genre).to_string(), *num))
+			.collect(),
+		num_songs: album.songs.len() as u32,
+		num_discs: album.num_discs,
+		duration: album.duration,
 	}
 }
 
 fn make_artist_header(artist: &storage::Artist, dictionary: &Dictionary) -> ArtistHeader {
 	ArtistHeader {
 		name: UniCase::new(dictionary.resolve(&artist.name).to_owned()),
+		artwork: artist
+			.artwork
+			.as_ref()
+			.map(|a| dictionary.resolve(&a.0))
+			.map(PathBuf::from),
+		mosaic: artist
+			.mosaic
+			.iter()
+			.map(|a| PathBuf::from(dictionary.resolve(&a.0)))
+			.collect(),
 		num_albums_as_performer: artist.albums_as_performer.len() as u32,
 		num_albums_as_additional_performer: artist.albums_as_additional_performer.len() as u32,
 		num_albums_as_composer: artist.albums_as_composer.len() as u32,
@@ -357,13 +902,39 @@ fn make_artist_header(artist: &storage::Artist, dictionary: &Dictionary) -> Arti
 			.map(|(genre, num)| (dictionary.resolve(genre).to_string(), *num))
 			.collect(),
 		num_songs: artist.num_songs,
+		num_discs: artist.num_discs,
+		duration: artist.duration,
 	}
 }
 
-fn make_genre_header(genre: &storage::Genre, dictionary: &Dictionary) -> GenreHeader {
+fn make_genre_header(
+	genre: &storage::Genre,
+	taxonomy: &Taxonomy,
+	dictionary: &Dictionary,
+) -> GenreHeader {
 	GenreHeader {
 		name: dictionary.resolve(&genre.name).to_string(),
+		parent: taxonomy
+			.parent(GenreKey(genre.name))
+			.map(|p| dictionary.resolve(&p.0).to_string()),
+	}
+}
+
+// Splits collection-wide orderings into per-genre orderings, so that listing the albums or
+// artists of a genre does not require going through the whole collection.
+fn make_genre_orderings<K: Clone + Eq + Hash>(
+	orderings: &EnumMap<SortKey, Vec<K>>,
+	genres: &HashMap<K, Vec<GenreKey>>,
+) -> HashMap<GenreKey, EnumMap<SortKey, Vec<K>>> {
+	let mut genre_orderings = HashMap::<GenreKey, EnumMap<SortKey, Vec<K>>>::new();
+	for (sort, keys) in orderings {
+		for key in keys {
+			for genre_key in genres.get(key).into_iter().flatten() {
+				genre_orderings.entry(*genre_key).or_default()[sort].push(key.clone());
+			}
+		}
 	}
+	genre_orderings
 }
 
 #[derive(Clone, Default)]
@@ -371,14 +942,41 @@ pub struct Builder {
 	artists: HashMap<ArtistKey, storage::Artist>,
 	albums: HashMap<AlbumKey, storage::Album>,
 	genres: HashMap<GenreKey, storage::Genre>,
+	labels: HashMap<LabelKey, storage::Label>,
+	decades: HashMap<i64, storage::Decade>,
 	songs: HashMap<SongKey, storage::Song>,
+	fingerprints: HashMap<SongKey, Fingerprint>,
+	directory_images: HashMap<PathKey, Vec<storage::Image>>,
+	compilation_artists: Vec<String>,
+	genre_parents: Vec<(String, String)>,
 }
 
 impl Builder {
-	pub fn add_song(&mut self, song: &storage::Song) {
-		self.add_song_to_album(song);
-		self.add_song_to_artists(song);
-		self.add_song_to_genres(song);
+	pub fn set_compilation_artists(&mut self, names: Vec<String>) {
+		self.compilation_artists = names;
+	}
+
+	pub fn set_genre_parents(&mut self, parents: Vec<(String, String)>) {
+		self.genre_parents = parents;
+	}
+
+	pub fn add_fingerprint(&mut self, song: &storage::Song, fingerprint: Fingerprint) {
+		let song_key = SongKey {
+			virtual_path: song.virtual_path,
+		};
+		self.fingerprints.insert(song_key, fingerprint);
+	}
+
+	pub fn add_directory_images(&mut self, directory: PathKey, images: Vec<storage::Image>) {
+		self.directory_images.insert(directory, images);
+	}
+
+	pub fn add_song(&mut self, song: &storage::Song) {
+		self.add_song_to_album(song);
+		self.add_song_to_artists(song);
+		self.add_song_to_genres(song);
+		self.add_song_to_labels(song);
+		self.add_song_to_decades(song);
 
 		self.songs.insert(
 			SongKey {
@@ -388,7 +986,40 @@ impl Builder {
 		);
 	}
 
-	pub fn build(self) -> Collection {
+	pub fn build(mut self, dictionary: &Dictionary) -> Collection {
+		let compilation_artists = self
+			.compilation_artists
+			.iter()
+			.filter_map(|name| dictionary.get_canon(name))
+			.map(ArtistKey)
+			.collect::<HashSet<_>>();
+
+		for album in self.albums.values_mut() {
+			if !album.artists.is_empty()
+				&& album
+					.artists
+					.iter()
+					.all(|a| compilation_artists.contains(a))
+			{
+				album.compilation = true;
+			}
+		}
+
+		self.compute_album_aggregates(dictionary);
+		self.compute_artist_aggregates(dictionary);
+
+		let genre_taxonomy = Taxonomy::new(
+			self.genre_parents
+				.iter()
+				.filter_map(|(child, parent)| {
+					let child = GenreKey(dictionary.get_canon(child)?);
+					let parent = GenreKey(dictionary.get_canon(parent)?);
+					(child != parent).then_some((child, parent))
+				})
+				.collect(),
+		);
+		self.compute_genre_aggregates(&genre_taxonomy);
+
 		let mut recent_albums = self.albums.keys().cloned().collect::<Vec<_>>();
 		recent_albums.sort_by_key(|a| {
 			self.albums
@@ -397,18 +1028,318 @@ impl Builder {
 				.unwrap_or_default()
 		});
 
+		let album_orderings = EnumMap::from_fn(|sort| self.make_album_ordering(dictionary, sort));
+		let artist_orderings = EnumMap::from_fn(|sort| {
+			self.make_artist_ordering(dictionary, sort, &compilation_artists)
+		});
+
+		let mut album_genres = HashMap::<AlbumKey, Vec<GenreKey>>::new();
+		let mut artist_genres = HashMap::<ArtistKey, Vec<GenreKey>>::new();
+		for (genre_key, genre) in &self.genres {
+			for album_key in &genre.albums {
+				album_genres
+					.entry(album_key.clone())
+					.or_default()
+					.push(*genre_key);
+			}
+			for artist_key in &genre.artists {
+				artist_genres
+					.entry(*artist_key)
+					.or_default()
+					.push(*genre_key);
+			}
+		}
+		let genre_album_orderings = make_genre_orderings(&album_orderings, &album_genres);
+		let genre_artist_orderings = make_genre_orderings(&artist_orderings, &artist_genres);
+
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_secs() as i64)
+			.unwrap_or_default();
+		let mut stats_builder = stats::Builder::new(now);
+		for song in self.songs.values() {
+			stats_builder.add_song(song, dictionary);
+		}
+		let stats = stats_builder.build(
+			self.albums.len(),
+			artist_orderings[SortKey::Name].len(),
+			self.genres.len(),
+		);
+
+		let health = health::analyze(&self.albums, &self.songs, dictionary);
+
+		let duplicates = duplicates::find_duplicates(&self.songs, &self.fingerprints, dictionary);
+		let duplicate_groups = duplicates
+			.iter()
+			.enumerate()
+			.flat_map(|(i, group)| group.iter().map(move |k| (*k, i)))
+			.collect();
+
 		Collection {
 			artists: self.artists,
 			albums: self.albums,
 			genres: self.genres,
+			labels: self.labels,
+			decades: self.decades,
 			songs: self.songs,
 			recent_albums,
+			album_orderings,
+			artist_orderings,
+			genre_album_orderings,
+			genre_artist_orderings,
+			stats,
+			health,
+			duplicates,
+			duplicate_groups,
+			genre_taxonomy,
+		}
+	}
+
+	fn compute_genre_aggregates(&mut self, taxonomy: &Taxonomy) {
+		// Genres which only appear in the taxonomy can still be browsed for their subgenres
+		for genre_key in taxonomy.genres() {
+			self.get_or_create_genre(*genre_key);
+		}
+
+		for genre in self.genres.values_mut() {
+			genre.related_genres.clear();
+		}
+
+		// Songs count towards the related genres of their genres' ancestors too
+		for song in self.songs.values() {
+			let mut genres = Vec::new();
+			for genre_key in song.genres.iter().map(|g| GenreKey(*g)) {
+				for genre_key in std::iter::once(genre_key).chain(taxonomy.ancestors(genre_key)) {
+					if !genres.contains(&genre_key) {
+						genres.push(genre_key);
+					}
+				}
+			}
+			for genre_key in &genres {
+				let Some(genre) = self.genres.get_mut(genre_key) else {
+					continue;
+				};
+				for other_genre in &genres {
+					if genre_key == other_genre || taxonomy.is_lineage(*genre_key, *other_genre) {
+						continue;
+					}
+					*genre.related_genres.entry(*other_genre).or_default() += 1;
+				}
+			}
+		}
+	}
+
+	fn compute_album_aggregates(&mut self, dictionary: &Dictionary) {
+		for album in self.albums.values_mut() {
+			let mut songs = album
+				.songs
+				.iter()
+				.filter_map(|k| self.songs.get(k))
+				.collect::<Vec<_>>();
+			songs.sort_by(|a, b| {
+				(a.disc_number, a.track_number)
+					.cmp(&(b.disc_number, b.track_number))
+					.then_with(|| dictionary.cmp(&a.virtual_path.0, &b.virtual_path.0))
+			});
+
+			// Image files belong to the directories holding the album's songs
+			let mut images = Vec::<storage::Image>::new();
+			let mut directories = Vec::new();
+			for song in &songs {
+				let directory = Path::new(dictionary.resolve(&song.virtual_path.0))
+					.parent()
+					.and_then(|p| p.to_str())
+					.and_then(|p| dictionary.get(p))
+					.map(PathKey);
+				if let Some(directory) = directory {
+					if !directories.contains(&directory) {
+						directories.push(directory);
+					}
+				}
+			}
+			for directory in &directories {
+				for image in self.directory_images.get(directory).into_iter().flatten() {
+					if !images.contains(image) {
+						images.push(*image);
+					}
+				}
+			}
+
+			// Tracks often embed identical pictures, so embedded pictures are only
+			// listed for the first track carrying each picture type.
+			let mut embedded_types = HashSet::new();
+			for song in &songs {
+				let mut song_types = HashSet::new();
+				for image in &song.images {
+					let is_new = match image.picture_index {
+						None => !images.contains(image),
+						Some(_) => !embedded_types.contains(&image.picture_type),
+					};
+					if is_new {
+						images.push(*image);
+						if image.picture_index.is_some() {
+							song_types.insert(image.picture_type);
+						}
+					}
+				}
+				embedded_types.extend(song_types);
+			}
+			images.sort_by_key(|i| (i.picture_type, i.picture_index.is_some()));
+
+			// Songs carry the art picked while scanning (embedded pictures, then files
+			// matching the album art pattern), other front covers are only a fallback.
+			album.artwork = songs.iter().find_map(|s| s.artwork).or_else(|| {
+				images
+					.iter()
+					.find(|i| i.picture_type == PictureType::Front)
+					.map(|i| i.path)
+			});
+			album.images = images;
+
+			album.duration = songs.iter().filter_map(|s| s.duration).sum();
+			album.num_discs = songs
+				.iter()
+				.map(|s| s.disc_number.unwrap_or(1))
+				.collect::<HashSet<_>>()
+				.len() as u32;
+			album.num_songs_by_genre.clear();
+			for genre in songs.iter().flat_map(|s| s.genres.iter()) {
+				*album.num_songs_by_genre.entry(*genre).or_default() += 1;
+			}
+		}
+	}
+
+	fn compute_artist_aggregates(&mut self, dictionary: &Dictionary) {
+		let mut durations = HashMap::<ArtistKey, i64>::new();
+		for song in self.songs.values() {
+			let artists = song
+				.album_artists
+				.iter()
+				.chain(song.artists.iter())
+				.chain(song.composers.iter())
+				.chain(song.lyricists.iter())
+				.collect::<HashSet<_>>();
+			for artist_key in artists {
+				*durations.entry(*artist_key).or_default() += song.duration.unwrap_or_default();
+			}
+		}
+
+		for (artist_key, artist) in self.artists.iter_mut() {
+			artist.duration = durations.get(artist_key).copied().unwrap_or_default();
+			artist.num_discs = artist
+				.all_albums
+				.iter()
+				.filter_map(|k| self.albums.get(k))
+				.map(|a| a.num_discs)
+				.sum();
+
+			// Artists without an image of their own are represented by their album covers
+			artist.mosaic.clear();
+			if artist.artwork.is_none() {
+				let albums = match artist.albums_as_performer.is_empty() {
+					true => &artist.all_albums,
+					false => &artist.albums_as_performer,
+				};
+				let mut albums = albums
+					.iter()
+					.filter_map(|k| self.albums.get(k))
+					.collect::<Vec<_>>();
+				albums.sort_by(|a, b| {
+					a.year
+						.cmp(&b.year)
+						.then_with(|| dictionary.cmp(&a.name, &b.name))
+				});
+				for artwork in albums.iter().filter_map(|a| a.artwork) {
+					if artist.mosaic.len() >= MOSAIC_SIZE {
+						break;
+					}
+					if !artist.mosaic.contains(&artwork) {
+						artist.mosaic.push(artwork);
+					}
+				}
+			}
+		}
+	}
+
+	fn make_album_ordering(&self, dictionary: &Dictionary, sort: SortKey) -> Vec<AlbumKey> {
+		let mut keys = self.albums.keys().cloned().collect::<Vec<_>>();
+		keys.par_sort_unstable_by(|a_key, b_key| {
+			let (a, b) = (&self.albums[a_key], &self.albums[b_key]);
+			let ordering = match sort {
+				SortKey::Name => Ordering::Equal,
+				SortKey::Year => a.year.cmp(&b.year),
+				SortKey::DateAdded => a.date_added.cmp(&b.date_added),
+				SortKey::Artist => compare_artists(&a.artists, &b.artists, dictionary)
+					.then_with(|| a.year.cmp(&b.year)),
+				SortKey::NumSongs => a.songs.len().cmp(&b.songs.len()),
+				SortKey::Duration => a.duration.cmp(&b.duration),
+			};
+			ordering
+				.then_with(|| dictionary.cmp(&a.name, &b.name))
+				.then_with(|| compare_artists(&a_key.artists, &b_key.artists, dictionary))
+		});
+		keys
+	}
+
+	fn make_artist_ordering(
+		&self,
+		dictionary: &Dictionary,
+		sort: SortKey,
+		compilation_artists: &HashSet<ArtistKey>,
+	) -> Vec<ArtistKey> {
+		let mut years = HashMap::<ArtistKey, Option<i64>>::new();
+		let mut dates_added = HashMap::<ArtistKey, i64>::new();
+		for (artist_key, artist) in &self.artists {
+			let albums = artist
+				.all_albums
+				.iter()
+				.filter_map(|k| self.albums.get(k))
+				.collect::<Vec<_>>();
+			years.insert(*artist_key, albums.iter().filter_map(|a| a.year).min());
+			dates_added.insert(
+				*artist_key,
+				albums
+					.iter()
+					.map(|a| a.date_added)
+					.max()
+					.unwrap_or_default(),
+			);
 		}
+
+		let mut keys = self
+			.artists
+			.keys()
+			.filter(|k| !compilation_artists.contains(*k))
+			.copied()
+			.collect::<Vec<_>>();
+		keys.par_sort_unstable_by(|a_key, b_key| {
+			let (a, b) = (&self.artists[a_key], &self.artists[b_key]);
+			let ordering = match sort {
+				SortKey::Name | SortKey::Artist => Ordering::Equal,
+				SortKey::Year => years[a_key].cmp(&years[b_key]),
+				SortKey::DateAdded => dates_added[a_key].cmp(&dates_added[b_key]),
+				SortKey::NumSongs => a.num_songs.cmp(&b.num_songs),
+				SortKey::Duration => a.duration.cmp(&b.duration),
+			};
+			ordering.then_with(|| dictionary.cmp(&a.name, &b.name))
+		});
+		keys
 	}
 
 	fn add_song_to_artists(&mut self, song: &storage::Song) {
 		let album_key = song.album_key();
 
+		let main_artists = match song.album_artists.is_empty() {
+			true => &song.artists,
+			false => &song.album_artists,
+		};
+		if let Some(artwork) = song.artist_artwork {
+			for artist_key in main_artists {
+				let artist = self.get_or_create_artist(*artist_key);
+				artist.artwork = artist.artwork.or(Some(artwork));
+			}
+		}
+
 		let mut all_artists = TinyVec::<[ArtistKey; 8]>::new();
 
 		for artist_key in &song.album_artists {
@@ -470,6 +1401,8 @@ impl Builder {
 			.entry(artist_key)
 			.or_insert_with(|| storage::Artist {
 				name: artist_key.0,
+				artwork: None,
+				mosaic: Vec::new(),
 				all_albums: HashSet::new(),
 				albums_as_performer: HashSet::new(),
 				albums_as_additional_performer: HashSet::new(),
@@ -477,6 +1410,8 @@ impl Builder {
 				albums_as_lyricist: HashSet::new(),
 				num_songs_by_genre: HashMap::new(),
 				num_songs: 0,
+				num_discs: 0,
+				duration: 0,
 			})
 			.borrow_mut()
 	}
@@ -490,15 +1425,12 @@ impl Builder {
 		let album = self.albums.entry(album_key).or_default().borrow_mut();
 		album.name = name;
 
-		if album.artwork.is_none() {
-			album.artwork = song.artwork;
-		}
-
 		if album.year.is_none() {
 			album.year = song.year;
 		}
 
 		album.date_added = album.date_added.max(song.date_added);
+		album.compilation |= song.compilation;
 
 		if !song.album_artists.is_empty() {
 			album.artists = song.album_artists.clone();
@@ -511,16 +1443,19 @@ impl Builder {
 		});
 	}
 
+	fn get_or_create_genre(&mut self, genre_key: GenreKey) -> &mut storage::Genre {
+		self.genres.entry(genre_key).or_insert(storage::Genre {
+			name: genre_key.0,
+			albums: HashSet::new(),
+			artists: HashSet::new(),
+			related_genres: HashMap::new(),
+			songs: Vec::new(),
+		})
+	}
+
 	fn add_song_to_genres(&mut self, song: &storage::Song) {
 		for name in &song.genres {
-			let genre_key = GenreKey(*name);
-			let genre = self.genres.entry(genre_key).or_insert(storage::Genre {
-				name: *name,
-				albums: HashSet::new(),
-				artists: HashSet::new(),
-				related_genres: HashMap::new(),
-				songs: Vec::new(),
-			});
+			let genre = self.get_or_create_genre(GenreKey(*name));
 
 			if let Some(album_key) = song.album_key() {
 				genre.albums.insert(album_key);
@@ -546,22 +1481,42 @@ impl Builder {
 				virtual_path: song.virtual_path,
 			});
 		}
+	}
 
-		let genres = song.genres.clone();
-		for genre in &genres {
-			for other_genre in &genres {
-				if genre == other_genre {
-					continue;
-				}
-				let Some(genre) = self.genres.get_mut(&GenreKey(*genre)) else {
-					continue;
-				};
-				genre
-					.related_genres
-					.entry(GenreKey(*other_genre))
-					.and_modify(|n| *n += 1)
-					.or_insert(1);
+	fn add_song_to_labels(&mut self, song: &storage::Song) {
+		for name in &song.labels {
+			let label = self
+				.labels
+				.entry(LabelKey(*name))
+				.or_insert_with(|| storage::Label {
+					name: *name,
+					albums: HashSet::new(),
+					artists: HashSet::new(),
+				});
+
+			if let Some(album_key) = song.album_key() {
+				label.albums.insert(album_key);
 			}
+
+			for artist_key in song.album_artists.iter().chain(song.artists.iter()) {
+				label.artists.insert(*artist_key);
+			}
+		}
+	}
+
+	fn add_song_to_decades(&mut self, song: &storage::Song) {
+		let Some(decade) = song.decade() else {
+			return;
+		};
+
+		let decade = self.decades.entry(decade).or_default();
+
+		if let Some(album_key) = song.album_key() {
+			decade.albums.insert(album_key);
+		}
+
+		for artist_key in song.album_artists.iter().chain(song.artists.iter()) {
+			decade.artists.insert(*artist_key);
 		}
 	}
 }
@@ -572,23 +1527,43 @@ mod test {
 	use tinyvec::tiny_vec;
 
 	use crate::app::{index::dictionary, scanner};
-	use storage::{store_song, InternPath};
+	use storage::{store_image, store_song, InternPath};
 
 	use super::*;
 
 	fn setup_test(songs: Vec<scanner::Song>) -> (Collection, Dictionary) {
+		setup_test_with_directories(vec![], songs)
+	}
+
+	fn setup_test_with_directories(
+		directories: Vec<scanner::Directory>,
+		songs: Vec<scanner::Song>,
+	) -> (Collection, Dictionary) {
 		let mut dictionary_builder = dictionary::Builder::default();
 		let mut builder = Builder::default();
+		builder.set_compilation_artists(vec!["Various Artists".to_owned(), "VA".to_owned()]);
+
+		for directory in directories {
+			let virtual_path = (&directory.virtual_path)
+				.get_or_intern(&mut dictionary_builder)
+				.unwrap();
+			let images = directory
+				.images
+				.iter()
+				.map(|i| store_image(&mut dictionary_builder, i).unwrap())
+				.collect();
+			builder.add_directory_images(virtual_path, images);
+		}
 
 		for song in songs {
 			let song = store_song(&mut dictionary_builder, &song).unwrap();
 			builder.add_song(&song);
 		}
 
-		let browser = builder.build();
 		let dictionary = dictionary_builder.build();
+		let collection = builder.build(&dictionary);
 
-		(browser, dictionary)
+		(collection, dictionary)
 	}
 
 	#[test]
@@ -609,7 +1584,7 @@ mod test {
 		]));
 
 		let artists = collection
-			.get_artists(&strings)
+			.get_artists(&strings, &ListOptions::default())
 			.into_iter()
 			.map(|a| a.name)
 			.collect::<Vec<_>>();
@@ -641,7 +1616,7 @@ mod test {
 		]));
 
 		let artists = collection
-			.get_artists(&strings)
+			.get_artists(&strings, &ListOptions::default())
 			.into_iter()
 			.map(|a| a.name)
 			.collect::<Vec<_>>();
@@ -673,7 +1648,7 @@ mod test {
 		]));
 
 		let artists = collection
-			.get_artists(&strings)
+			.get_artists(&strings, &ListOptions::default())
 			.into_iter()
 			.map(|a| a.name)
 			.collect::<Vec<_>>();
@@ -682,68 +1657,360 @@ mod test {
 	}
 
 	#[test]
-	fn artists_list_excludes_various_artists() {
+	fn artists_list_excludes_various_artists() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Rain of Fury.mp3"),
+				title: Some("Rain of Fury".to_owned()),
+				artists: vec!["Rhapsody Of Fire".to_owned()],
+				album_artists: vec!["Various Artists".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Paradise.mp3"),
+				title: Some("Paradise".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				album_artists: vec!["Various Artists".to_owned()],
+				..Default::default()
+			},
+		]));
+
+		let artists = collection
+			.get_artists(&strings, &ListOptions::default())
+			.into_iter()
+			.map(|a| a.name)
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			artists,
+			vec![
+				UniCase::new("Rhapsody of Fire".to_owned()),
+				UniCase::new("Stratovarius".to_owned()),
+			]
+		);
+	}
+
+	#[test]
+	fn can_list_compilations() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Rain of Fury.mp3"),
+				album: Some("Metal Hits".to_owned()),
+				artists: vec!["Rhapsody Of Fire".to_owned()],
+				album_artists: vec!["Various Artists".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("Ambient Classics".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album_artists: vec!["Chillout Records".to_owned()],
+				compilation: true,
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				..Default::default()
+			},
+		]));
+
+		let compilations = collection
+			.get_compilations(&strings, &ListOptions::default())
+			.into_iter()
+			.map(|a| a.name)
+			.collect::<Vec<_>>();
+		assert_eq!(compilations, vec!["Ambient Classics", "Metal Hits"]);
+
+		let artist = collection
+			.get_artist(
+				&strings,
+				ArtistKey(strings.get("Rhapsody Of Fire").unwrap()),
+			)
+			.unwrap();
+		assert_eq!(artist.header.num_albums_as_additional_performer, 1);
+	}
+
+	#[test]
+	fn computes_album_and_artist_aggregates() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				disc_number: Some(1),
+				duration: Some(300),
+				genres: vec!["Ambient".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Cascade.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				disc_number: Some(2),
+				duration: Some(200),
+				genres: vec!["Ambient".to_owned(), "Electronic".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Papua New Guinea.mp3"),
+				album: Some("Accelerator".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				duration: Some(100),
+				..Default::default()
+			},
+		]));
+
+		let album = collection
+			.get_album(
+				&strings,
+				AlbumKey {
+					artists: tiny_vec![ArtistKey(strings.get("FSOL").unwrap())],
+					name: strings.get("ISDN").unwrap(),
+				},
+			)
+			.unwrap();
+		assert_eq!(album.header.num_songs, 2);
+		assert_eq!(album.header.num_discs, 2);
+		assert_eq!(album.header.duration, 500);
+		assert_eq!(
+			album.header.num_songs_by_genre,
+			HashMap::from_iter([("Ambient".to_owned(), 2), ("Electronic".to_owned(), 1)])
+		);
+
+		let artist = collection
+			.get_artist(&strings, ArtistKey(strings.get("FSOL").unwrap()))
+			.unwrap();
+		assert_eq!(artist.header.num_songs, 3);
+		assert_eq!(artist.header.num_discs, 3);
+		assert_eq!(artist.header.duration, 600);
+	}
+
+	#[test]
+	fn can_get_all_albums() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				album: Some("Lifeforms".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				..Default::default()
+			},
+		]));
+
+		let albums = collection.get_albums(&strings, &ListOptions::default());
+		assert_eq!(albums.len(), 3);
+
+		assert_eq!(
+			albums.into_iter().map(|a| a.name).collect::<Vec<_>>(),
+			vec![
+				"Elysium".to_owned(),
+				"ISDN".to_owned(),
+				"Lifeforms".to_owned()
+			]
+		);
+	}
+
+	#[test]
+	fn can_sort_albums() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				year: Some(1994),
+				duration: Some(400),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				year: Some(2011),
+				duration: Some(300),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Cascade.mp3"),
+				album: Some("Lifeforms".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				year: Some(1994),
+				duration: Some(100),
+				..Default::default()
+			},
+		]));
+
+		let list = |sort, order| {
+			collection
+				.get_albums(
+					&strings,
+					&ListOptions {
+						sort,
+						order,
+						..Default::default()
+					},
+				)
+				.into_iter()
+				.map(|a| a.name)
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			list(SortKey::Year, SortOrder::Ascending),
+			vec!["ISDN", "Lifeforms", "Elysium"]
+		);
+		assert_eq!(
+			list(SortKey::Year, SortOrder::Descending),
+			vec!["Elysium", "Lifeforms", "ISDN"]
+		);
+		assert_eq!(
+			list(SortKey::Artist, SortOrder::Ascending),
+			vec!["ISDN", "Lifeforms", "Elysium"]
+		);
+		assert_eq!(
+			list(SortKey::Duration, SortOrder::Ascending),
+			vec!["Lifeforms", "Elysium", "ISDN"]
+		);
+	}
+
+	#[test]
+	fn can_sort_genre_albums() {
 		let (collection, strings) = setup_test(Vec::from([
 			scanner::Song {
-				virtual_path: PathBuf::from("Rain of Fury.mp3"),
-				title: Some("Rain of Fury".to_owned()),
-				artists: vec!["Rhapsody Of Fire".to_owned()],
-				album_artists: vec!["Various Artists".to_owned()],
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				genres: vec!["Ambient".to_owned()],
+				duration: Some(400),
 				..Default::default()
 			},
 			scanner::Song {
-				virtual_path: PathBuf::from("Paradise.mp3"),
-				title: Some("Paradise".to_owned()),
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				album: Some("Elysium".to_owned()),
 				artists: vec!["Stratovarius".to_owned()],
-				album_artists: vec!["Various Artists".to_owned()],
+				genres: vec!["Metal".to_owned()],
+				duration: Some(300),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Cascade.mp3"),
+				album: Some("Lifeforms".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				genres: vec!["Ambient".to_owned()],
+				duration: Some(100),
 				..Default::default()
 			},
 		]));
 
-		let artists = collection
-			.get_artists(&strings)
-			.into_iter()
-			.map(|a| a.name)
-			.collect::<Vec<_>>();
+		let genre_key = GenreKey(strings.get("Ambient").unwrap());
+		let list = |sort, offset, count| {
+			collection
+				.get_genre_albums(
+					&strings,
+					genre_key,
+					false,
+					&ListOptions {
+						sort,
+						offset,
+						count,
+						..Default::default()
+					},
+				)
+				.unwrap()
+				.into_iter()
+				.map(|a| a.name)
+				.collect::<Vec<_>>()
+		};
 
-		assert_eq!(
-			artists,
-			vec![
-				UniCase::new("Rhapsody of Fire".to_owned()),
-				UniCase::new("Stratovarius".to_owned()),
-			]
+		assert_eq!(list(SortKey::Name, 0, None), vec!["ISDN", "Lifeforms"]);
+		assert_eq!(list(SortKey::Duration, 0, None), vec!["Lifeforms", "ISDN"]);
+		assert_eq!(list(SortKey::Duration, 1, Some(1)), vec!["ISDN"]);
+	}
+
+	#[test]
+	fn can_paginate_albums() {
+		let (collection, strings) = setup_test(
+			["A", "B", "C", "D", "E"]
+				.into_iter()
+				.map(|name| scanner::Song {
+					virtual_path: PathBuf::from(format!("{name}.mp3")),
+					album: Some(name.to_owned()),
+					artists: vec!["FSOL".to_owned()],
+					..Default::default()
+				})
+				.collect(),
 		);
+
+		let page = |offset, count, order| {
+			collection
+				.get_albums(
+					&strings,
+					&ListOptions {
+						order,
+						offset,
+						count: Some(count),
+						..Default::default()
+					},
+				)
+				.into_iter()
+				.map(|a| a.name)
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(page(0, 2, SortOrder::Ascending), vec!["A", "B"]);
+		assert_eq!(page(2, 2, SortOrder::Ascending), vec!["C", "D"]);
+		assert_eq!(page(4, 2, SortOrder::Ascending), vec!["E"]);
+		assert!(page(10, 2, SortOrder::Ascending).is_empty());
+		assert_eq!(page(1, 2, SortOrder::Descending), vec!["D", "C"]);
 	}
 
 	#[test]
-	fn can_get_all_albums() {
+	fn can_sort_artists_by_num_songs() {
 		let (collection, strings) = setup_test(Vec::from([
 			scanner::Song {
-				album: Some("ISDN".to_owned()),
+				virtual_path: PathBuf::from("Kai.mp3"),
 				artists: vec!["FSOL".to_owned()],
 				..Default::default()
 			},
 			scanner::Song {
-				album: Some("Elysium".to_owned()),
-				artists: vec!["Stratovarius".to_owned()],
+				virtual_path: PathBuf::from("Cascade.mp3"),
+				artists: vec!["FSOL".to_owned()],
 				..Default::default()
 			},
 			scanner::Song {
-				album: Some("Lifeforms".to_owned()),
-				artists: vec!["FSOL".to_owned()],
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				artists: vec!["Stratovarius".to_owned()],
 				..Default::default()
 			},
 		]));
 
-		let albums = collection.get_albums(&strings);
-		assert_eq!(albums.len(), 3);
+		let artists = collection
+			.get_artists(
+				&strings,
+				&ListOptions {
+					sort: SortKey::NumSongs,
+					order: SortOrder::Descending,
+					..Default::default()
+				},
+			)
+			.into_iter()
+			.map(|a| a.name)
+			.collect::<Vec<_>>();
 
 		assert_eq!(
-			albums.into_iter().map(|a| a.name).collect::<Vec<_>>(),
+			artists,
 			vec![
-				"Elysium".to_owned(),
-				"ISDN".to_owned(),
-				"Lifeforms".to_owned()
+				UniCase::new("FSOL".to_owned()),
+				UniCase::new("Stratovarius".to_owned())
 			]
 		);
 	}
@@ -882,7 +2149,7 @@ mod test {
 				..Default::default()
 			}]));
 
-			let artists = collection.get_artists(&strings);
+			let artists = collection.get_artists(&strings, &ListOptions::default());
 
 			if test.expect_listed {
 				assert!(artists.iter().any(|a| a.name == UniCase::new(artist_name)));
@@ -1101,7 +2368,7 @@ mod test {
 		]));
 
 		let genre = collection
-			.get_genre(&strings, GenreKey(strings.get("Metal").unwrap()))
+			.get_genre(&strings, GenreKey(strings.get("Metal").unwrap()), false)
 			.unwrap();
 
 		assert_eq!(genre.header.name, "Metal".to_owned());
@@ -1113,4 +2380,398 @@ mod test {
 			HashMap::from_iter([("Power Metal".to_owned(), 1)])
 		);
 	}
+
+	#[test]
+	fn can_get_label() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				labels: vec!["Edel".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Seasons.mp3"),
+				album: Some("Inhuman Rampage".to_owned()),
+				artists: vec!["Dragonforce".to_owned()],
+				labels: vec!["Roadrunner".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Demo.mp3"),
+				labels: vec!["Self-released".to_owned()],
+				..Default::default()
+			},
+		]));
+
+		let labels = collection.get_labels(&strings);
+		assert_eq!(
+			labels,
+			vec![
+				LabelHeader {
+					name: "Edel".to_owned()
+				},
+				LabelHeader {
+					name: "Roadrunner".to_owned()
+				},
+				LabelHeader {
+					name: "Self-released".to_owned()
+				},
+			]
+		);
+
+		let label = collection
+			.get_label(&strings, LabelKey(strings.get("Edel").unwrap()))
+			.unwrap();
+		assert_eq!(label.albums.len(), 1);
+		assert_eq!(label.albums[0].name, "Elysium");
+		assert_eq!(label.artists.len(), 1);
+		assert_eq!(label.artists[0].name, UniCase::new("Stratovarius"));
+
+		let label = collection
+			.get_label(&strings, LabelKey(strings.get("Self-released").unwrap()))
+			.unwrap();
+		assert!(label.albums.is_empty());
+	}
+
+	#[test]
+	fn can_get_decades() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				year: Some(1994),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Cascade.mp3"),
+				album: Some("Lifeforms".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				year: Some(1990),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Fantasy.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				year: Some(2011),
+				..Default::default()
+			},
+		]));
+
+		assert_eq!(
+			collection.get_decades(),
+			vec![DecadeHeader { decade: 1990 }, DecadeHeader { decade: 2010 }]
+		);
+
+		let decade = collection.get_decade(&strings, 1990).unwrap();
+		assert_eq!(decade.albums.len(), 2);
+		assert_eq!(decade.artists.len(), 1);
+
+		assert!(collection.get_decade(&strings, 2000).is_none());
+	}
+
+	#[test]
+	fn can_list_composers_and_lyricists() {
+		let (collection, strings) = setup_test(Vec::from([scanner::Song {
+			virtual_path: PathBuf::from("Nessun Dorma.mp3"),
+			album: Some("Turandot".to_owned()),
+			artists: vec!["Pavarotti".to_owned()],
+			composers: vec!["Puccini".to_owned()],
+			lyricists: vec!["Adami".to_owned(), "Simoni".to_owned()],
+			..Default::default()
+		}]));
+
+		let names = |artists: Vec<ArtistHeader>| {
+			artists
+				.into_iter()
+				.map(|a| a.name.into_inner())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			names(collection.get_composers(&strings, &ListOptions::default())),
+			vec!["Puccini"]
+		);
+		assert_eq!(
+			names(collection.get_lyricists(&strings, &ListOptions::default())),
+			vec!["Adami", "Simoni"]
+		);
+	}
+
+	#[test]
+	fn can_collapse_duplicates() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["mp3", "Kai.mp3"]),
+				title: Some("Kai".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(300),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["flac", "Kai.flac"]),
+				title: Some("Kai".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(300),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["mp3", "Cascade.mp3"]),
+				title: Some("Cascade".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(250),
+				..Default::default()
+			},
+		]));
+
+		let duplicates = collection.get_duplicates(&strings);
+		assert_eq!(duplicates.len(), 1);
+		assert_eq!(
+			duplicates[0]
+				.iter()
+				.map(|s| s.virtual_path.clone())
+				.collect::<Vec<_>>(),
+			vec![
+				PathBuf::from_iter(["flac", "Kai.flac"]),
+				PathBuf::from_iter(["mp3", "Kai.mp3"]),
+			]
+		);
+
+		let album = collection
+			.get_album(
+				&strings,
+				AlbumKey {
+					artists: tiny_vec![ArtistKey(strings.get("FSOL").unwrap())],
+					name: strings.get("ISDN").unwrap(),
+				},
+			)
+			.unwrap();
+		assert_eq!(album.songs.len(), 3);
+
+		let songs = collection.collapse_album_duplicates(&strings, album.songs);
+		let mut paths = songs
+			.into_iter()
+			.map(|s| s.virtual_path)
+			.collect::<Vec<_>>();
+		paths.sort();
+		assert_eq!(
+			paths,
+			vec![
+				PathBuf::from_iter(["flac", "Kai.flac"]),
+				PathBuf::from_iter(["mp3", "Cascade.mp3"]),
+			]
+		);
+	}
+
+	#[test]
+	fn album_duplicates_are_collapsed_within_album() {
+		let mut dictionary_builder = dictionary::Builder::default();
+		let mut builder = Builder::default();
+		for (path, album) in [("Kai.mp3", "ISDN"), ("Kai.flac", "Best Of")] {
+			let song = scanner::Song {
+				virtual_path: PathBuf::from(path),
+				title: Some("Kai".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some(album.to_owned()),
+				duration: Some(300),
+				..Default::default()
+			};
+			let song = store_song(&mut dictionary_builder, &song).unwrap();
+			builder.add_song(&song);
+			builder.add_fingerprint(&song, Fingerprint(vec![0xF0F0_F0F0; 8]));
+		}
+		let strings = dictionary_builder.build();
+		let collection = builder.build(&strings);
+		assert_eq!(collection.get_duplicates(&strings).len(), 1);
+
+		let album = collection
+			.get_album(
+				&strings,
+				AlbumKey {
+					artists: tiny_vec![ArtistKey(strings.get("FSOL").unwrap())],
+					name: strings.get("ISDN").unwrap(),
+				},
+			)
+			.unwrap();
+		let songs = collection.collapse_album_duplicates(&strings, album.songs);
+		assert_eq!(songs.len(), 1);
+		assert_eq!(songs[0].virtual_path, PathBuf::from("Kai.mp3"));
+	}
+
+	#[test]
+	fn artists_have_artwork_or_mosaic() {
+		let (collection, strings) = setup_test(Vec::from([
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["FSOL", "ISDN", "Kai.mp3"]),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				artwork: Some(PathBuf::from_iter(["FSOL", "ISDN", "Folder.jpg"])),
+				artist_artwork: Some(PathBuf::from_iter(["FSOL", "artist.jpg"])),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["Stratovarius", "Elysium", "Elysium.mp3"]),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				year: Some(2011),
+				artwork: Some(PathBuf::from_iter([
+					"Stratovarius",
+					"Elysium",
+					"Folder.jpg",
+				])),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from_iter(["Stratovarius", "Destiny", "SOS.mp3"]),
+				album: Some("Destiny".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				year: Some(1998),
+				artwork: Some(PathBuf::from_iter([
+					"Stratovarius",
+					"Destiny",
+					"Folder.jpg",
+				])),
+				..Default::default()
+			},
+		]));
+
+		let artists = collection.get_artists(&strings, &ListOptions::default());
+
+		assert_eq!(
+			artists[0].artwork,
+			Some(PathBuf::from_iter(["FSOL", "artist.jpg"]))
+		);
+		assert!(artists[0].mosaic.is_empty());
+
+		assert_eq!(artists[1].artwork, None);
+		assert_eq!(
+			artists[1].mosaic,
+			vec![
+				PathBuf::from_iter(["Stratovarius", "Destiny", "Folder.jpg"]),
+				PathBuf::from_iter(["Stratovarius", "Elysium", "Folder.jpg"]),
+			]
+		);
+	}
+
+	#[test]
+	fn album_images_are_listed_and_front_cover_is_deterministic() {
+		let folder_image = |name: &str, picture_type| scanner::Image {
+			path: PathBuf::from_iter(["FSOL", "ISDN", name]),
+			picture_type,
+			picture_index: None,
+		};
+		let embedded_image = |song: &str, picture_type, index| scanner::Image {
+			path: PathBuf::from_iter(["FSOL", "ISDN", song]),
+			picture_type,
+			picture_index: Some(index),
+		};
+		let directory = scanner::Directory {
+			virtual_path: PathBuf::from_iter(["FSOL", "ISDN"]),
+			images: vec![
+				folder_image("back.jpg", PictureType::Back),
+				folder_image("scan.jpg", PictureType::Other),
+			],
+			..Default::default()
+		};
+		let (collection, strings) = setup_test_with_directories(
+			vec![directory],
+			Vec::from([
+				scanner::Song {
+					virtual_path: PathBuf::from_iter(["FSOL", "ISDN", "02.mp3"]),
+					album: Some("ISDN".to_owned()),
+					artists: vec!["FSOL".to_owned()],
+					track_number: Some(2),
+					artwork: Some(PathBuf::from_iter(["FSOL", "ISDN", "02.mp3"])),
+					images: vec![
+						embedded_image("02.mp3", PictureType::Front, 0),
+						embedded_image("02.mp3", PictureType::Media, 1),
+					],
+					..Default::default()
+				},
+				scanner::Song {
+					virtual_path: PathBuf::from_iter(["FSOL", "ISDN", "01.mp3"]),
+					album: Some("ISDN".to_owned()),
+					artists: vec!["FSOL".to_owned()],
+					track_number: Some(1),
+					artwork: Some(PathBuf::from_iter(["FSOL", "ISDN", "01.mp3"])),
+					images: vec![
+						embedded_image("01.mp3", PictureType::Booklet, 0),
+						embedded_image("01.mp3", PictureType::Front, 1),
+					],
+					..Default::default()
+				},
+			]),
+		);
+
+		let album_key = AlbumKey {
+			artists: tiny_vec![ArtistKey(strings.get("FSOL").unwrap())],
+			name: strings.get("ISDN").unwrap(),
+		};
+
+		let images = collection
+			.get_album_images(&strings, album_key.clone())
+			.unwrap();
+		assert_eq!(
+			images
+				.iter()
+				.map(|i| (
+					i.path.file_name().unwrap().to_str().unwrap(),
+					i.picture_type,
+					i.picture_index
+				))
+				.collect::<Vec<_>>(),
+			vec![
+				("01.mp3", PictureType::Front, Some(1)),
+				("back.jpg", PictureType::Back, None),
+				("01.mp3", PictureType::Booklet, Some(0)),
+				("02.mp3", PictureType::Media, Some(1)),
+				("scan.jpg", PictureType::Other, None),
+			]
+		);
+
+		let album = collection.get_album(&strings, album_key).unwrap();
+		assert_eq!(
+			album.header.artwork,
+			Some(PathBuf::from_iter(["FSOL", "ISDN", "01.mp3"]))
+		);
+	}
+
+	#[test]
+	fn album_artwork_prefers_song_artwork_over_other_front_covers() {
+		let directory = scanner::Directory {
+			virtual_path: PathBuf::from_iter(["FSOL", "ISDN"]),
+			images: vec![scanner::Image {
+				path: PathBuf::from_iter(["FSOL", "ISDN", "Folder.jpg"]),
+				picture_type: PictureType::Front,
+				picture_index: None,
+			}],
+			..Default::default()
+		};
+		let (collection, strings) = setup_test_with_directories(
+			vec![directory],
+			Vec::from([scanner::Song {
+				virtual_path: PathBuf::from_iter(["FSOL", "ISDN", "01.mp3"]),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				artwork: Some(PathBuf::from_iter(["FSOL", "ISDN", "cover.png"])),
+				..Default::default()
+			}]),
+		);
+
+		let album_key = AlbumKey {
+			artists: tiny_vec![ArtistKey(strings.get("FSOL").unwrap())],
+			name: strings.get("ISDN").unwrap(),
+		};
+		let album = collection.get_album(&strings, album_key).unwrap();
+		assert_eq!(
+			album.header.artwork,
+			Some(PathBuf::from_iter(["FSOL", "ISDN", "cover.png"]))
+		);
+	}
 }
@@ -4,12 +4,58 @@ use icu_collator::{Collator, CollatorOptions, Strength};
 use lasso2::{Rodeo, RodeoReader, Spur};
 use rayon::slice::ParallelSliceMut;
 use serde::{Deserialize, Serialize};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
 
-pub fn sanitize(s: &str) -> String {
-	// TODO merge inconsistent diacritic usage
-	let mut cleaned = s.to_owned();
-	cleaned.retain(|c| !matches!(c, ' ' | '_' | '-' | '\''));
-	cleaned.to_lowercase()
+// Letters which Unicode decomposition does not reduce to a base letter
+const DEFAULT_TRANSLITERATIONS: [(char, &str); 9] = [
+	('ß', "ss"),
+	('æ', "ae"),
+	('œ', "oe"),
+	('ø', "o"),
+	('đ', "d"),
+	('ð', "d"),
+	('ł', "l"),
+	('þ', "th"),
+	('ı', "i"),
+];
+
+fn sanitize(s: &str, transliterations: &HashMap<char, String>) -> String {
+	let mut transliterated = String::with_capacity(s.len());
+	for c in s.nfc().flat_map(char::to_lowercase) {
+		match transliterations.get(&c) {
+			Some(t) => transliterated.push_str(t),
+			None => match DEFAULT_TRANSLITERATIONS.iter().find(|(k, _)| *k == c) {
+				Some((_, t)) => transliterated.push_str(t),
+				None => transliterated.push(c),
+			},
+		}
+	}
+
+	// Compatibility decomposition also folds full-width and half-width forms
+	let mut folded = String::with_capacity(transliterated.len());
+	let mut strip_marks = false;
+	for c in transliterated.nfkd() {
+		if is_combining_mark(c) {
+			if !strip_marks {
+				folded.push(c);
+			}
+		} else {
+			strip_marks = has_optional_marks(c);
+			if !matches!(c, ' ' | '_' | '-' | '\'') {
+				folded.push(c);
+			}
+		}
+	}
+
+	// Marks which were kept are recomposed (eg. half-width `ｶﾞ` becomes `ガ`)
+	folded.nfc().flat_map(char::to_lowercase).collect()
+}
+
+// Whether accents on a letter are commonly omitted, which is the case for Latin, Greek and
+// Cyrillic letters. In other scripts, marks tell words apart (eg. Japanese voicing marks or
+// Indic vowel signs).
+fn has_optional_marks(c: char) -> bool {
+	matches!(c, '\u{0000}'..='\u{024F}' | '\u{0370}'..='\u{052F}' | '\u{1E00}'..='\u{1FFF}')
 }
 
 pub fn make_collator() -> Collator {
@@ -26,6 +72,7 @@ pub struct Dictionary {
 	strings: RodeoReader,          // Interned strings
 	canon: HashMap<String, Spur>,  // Canonical representation of similar strings
 	sort_keys: HashMap<Spur, u32>, // All spurs sorted against each other
+	transliterations: HashMap<char, String>,
 }
 
 impl Dictionary {
@@ -34,7 +81,11 @@ impl Dictionary {
 	}
 
 	pub fn get_canon<S: AsRef<str>>(&self, string: S) -> Option<Spur> {
-		self.canon.get(&sanitize(string.as_ref())).copied()
+		self.canon.get(&self.sanitize(string)).copied()
+	}
+
+	pub fn sanitize<S: AsRef<str>>(&self, string: S) -> String {
+		sanitize(string.as_ref(), &self.transliterations)
 	}
 
 	pub fn resolve(&self, spur: &Spur) -> &str {
@@ -56,6 +107,7 @@ impl Default for Dictionary {
 			strings: Rodeo::default().into_reader(),
 			canon: Default::default(),
 			sort_keys: Default::default(),
+			transliterations: Default::default(),
 		}
 	}
 }
@@ -64,9 +116,17 @@ impl Default for Dictionary {
 pub struct Builder {
 	strings: Rodeo,
 	canon: HashMap<String, Spur>,
+	transliterations: HashMap<char, String>,
 }
 
 impl Builder {
+	pub fn set_transliterations(&mut self, transliterations: HashMap<char, String>) {
+		self.transliterations = transliterations
+			.into_iter()
+			.flat_map(|(k, v)| k.to_lowercase().map(move |k| (k, v.to_lowercase())))
+			.collect();
+	}
+
 	pub fn build(self) -> Dictionary {
 		let mut sorted_spurs = self.strings.iter().collect::<Vec<_>>();
 		// TODO this is too slow!
@@ -85,6 +145,7 @@ impl Builder {
 			strings: self.strings.into_reader(),
 			canon: self.canon,
 			sort_keys,
+			transliterations: self.transliterations,
 		}
 	}
 
@@ -93,7 +154,7 @@ impl Builder {
 	}
 
 	pub fn get_or_intern_canon<S: AsRef<str>>(&mut self, string: S) -> Option<Spur> {
-		let cleaned = sanitize(string.as_ref());
+		let cleaned = self.sanitize(&string);
 		match cleaned.is_empty() {
 			true => None,
 			false => Some(
@@ -104,4 +165,73 @@ impl Builder {
 			),
 		}
 	}
+
+	pub fn sanitize<S: AsRef<str>>(&self, string: S) -> String {
+		sanitize(string.as_ref(), &self.transliterations)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn sanitize_ignores_case_and_punctuation() {
+		let builder = Builder::default();
+		assert_eq!(builder.sanitize("Tobokegao"), "tobokegao");
+		assert_eq!(builder.sanitize("Hell-O_ 'World'"), "helloworld");
+	}
+
+	#[test]
+	fn sanitize_ignores_diacritics() {
+		let builder = Builder::default();
+		assert_eq!(builder.sanitize("Beyoncé"), builder.sanitize("Beyonce"));
+		assert_eq!(builder.sanitize("Motörhead"), "motorhead");
+		assert_eq!(builder.sanitize("Mo\u{0308}to\u{0308}rhead"), "motorhead");
+		assert_eq!(builder.sanitize("Sigur Rós"), "sigurros");
+		assert_eq!(builder.sanitize("Straße"), "strasse");
+		assert_eq!(builder.sanitize("Ørjan"), "orjan");
+	}
+
+	#[test]
+	fn sanitize_ignores_character_width() {
+		let builder = Builder::default();
+		assert_eq!(builder.sanitize("ＡＢＣ１２３"), "abc123");
+		assert_eq!(builder.sanitize("ｶﾀｶﾅ"), builder.sanitize("カタカナ"));
+	}
+
+	#[test]
+	fn sanitize_keeps_meaningful_marks() {
+		let builder = Builder::default();
+		assert_ne!(builder.sanitize("ガ"), builder.sanitize("カ"));
+		assert_eq!(builder.sanitize("ｶﾞ"), builder.sanitize("ガ"));
+		assert_ne!(builder.sanitize("バンド"), builder.sanitize("ハント"));
+		assert_ne!(builder.sanitize("कि"), builder.sanitize("क"));
+		assert_eq!(builder.sanitize("Ελλάδα"), builder.sanitize("ελλαδα"));
+	}
+
+	#[test]
+	fn sanitize_uses_transliterations() {
+		let mut builder = Builder::default();
+		builder.set_transliterations(HashMap::from([
+			('Ö', "Oe".to_owned()),
+			('ø', "oe".to_owned()),
+		]));
+		assert_eq!(builder.sanitize("Motörhead"), "motoerhead");
+		assert_eq!(builder.sanitize("Mo\u{0308}to\u{0308}rhead"), "moetoerhead");
+		assert_eq!(builder.sanitize("Ørjan"), "oerjan");
+		assert_eq!(builder.sanitize("Straße"), "strasse");
+	}
+
+	#[test]
+	fn merges_similar_strings() {
+		let mut builder = Builder::default();
+		let a = builder.get_or_intern_canon("Beyoncé");
+		let b = builder.get_or_intern_canon("BEYONCE");
+		assert_eq!(a, b);
+
+		let dictionary = builder.build();
+		assert_eq!(dictionary.resolve(&a.unwrap()), "Beyoncé");
+		assert_eq!(dictionary.get_canon("beyonce"), a);
+	}
 }
@@ -0,0 +1,323 @@
+use std::{cmp::Ordering, collections::HashMap, path::Path};
+
+use lasso2::Spur;
+
+use crate::app::{
+	index::{
+		dictionary::Dictionary,
+		storage::{self, SongKey},
+	},
+	peaks::Fingerprint,
+};
+
+// Maximum difference in duration (in seconds) between two copies of the same song
+const DURATION_TOLERANCE: i64 = 2;
+const FINGERPRINT_SIMILARITY_THRESHOLD: f32 = 0.9;
+// Silent or constant-level audio yields fingerprints with (almost) no bits set, or all of
+// them, which cannot tell songs apart
+const MIN_FINGERPRINT_DENSITY: f32 = 0.1;
+const LOSSLESS_FORMATS: [&str; 6] = ["aif", "aiff", "ape", "flac", "wav", "wv"];
+
+/// Groups songs which are copies of each other, such as a FLAC file and its MP3
+/// transcode. Songs within each group are ordered from best to worst format.
+pub fn find_duplicates(
+	songs: &HashMap<SongKey, storage::Song>,
+	fingerprints: &HashMap<SongKey, Fingerprint>,
+	dictionary: &Dictionary,
+) -> Vec<Vec<SongKey>> {
+	let keys = songs.keys().copied().collect::<Vec<_>>();
+	let indices = keys
+		.iter()
+		.enumerate()
+		.map(|(i, k)| (*k, i))
+		.collect::<HashMap<_, _>>();
+	let mut groups = DisjointSet::new(keys.len());
+
+	// Songs with identical (normalized) artists, album, title, position and similar durations
+	let mut by_metadata = HashMap::<(Vec<Spur>, Option<Spur>, Spur), Vec<usize>>::new();
+	for (i, key) in keys.iter().enumerate() {
+		let song = &songs[key];
+		let Some(title) = song.title else {
+			continue;
+		};
+		let mut artists = song.artists.iter().map(|a| a.0).collect::<Vec<_>>();
+		if artists.is_empty() {
+			continue;
+		}
+		artists.sort();
+		by_metadata
+			.entry((artists, song.album, title))
+			.or_default()
+			.push(i);
+	}
+	for candidates in by_metadata.into_values() {
+		for (n, a) in candidates.iter().enumerate() {
+			for b in &candidates[(n + 1)..] {
+				if are_same_track(&songs[&keys[*a]], &songs[&keys[*b]]) {
+					groups.union(*a, *b);
+				}
+			}
+		}
+	}
+
+	// Songs which sound alike, regardless of how they are tagged
+	let mut fingerprinted = fingerprints
+		.iter()
+		.filter(|(_, f)| is_distinctive(f))
+		.filter_map(|(k, f)| Some((indices.get(k)?, songs[k].duration?, f)))
+		.collect::<Vec<_>>();
+	fingerprinted.sort_by_key(|(_, duration, _)| *duration);
+	for (n, (a, a_duration, a_fingerprint)) in fingerprinted.iter().enumerate() {
+		for (b, b_duration, b_fingerprint) in &fingerprinted[(n + 1)..] {
+			if b_duration - a_duration > DURATION_TOLERANCE {
+				break;
+			}
+			if a_fingerprint.similarity(b_fingerprint) >= FINGERPRINT_SIMILARITY_THRESHOLD {
+				groups.union(**a, **b);
+			}
+		}
+	}
+
+	let mut members = HashMap::<usize, Vec<SongKey>>::new();
+	for (i, key) in keys.iter().enumerate() {
+		members.entry(groups.find(i)).or_default().push(*key);
+	}
+
+	let mut duplicates = members
+		.into_values()
+		.filter(|group| group.len() > 1)
+		.map(|mut group| {
+			group.sort_by(|a, b| compare_quality(&songs[b], &songs[a], dictionary));
+			group
+		})
+		.collect::<Vec<_>>();
+	duplicates.sort_by(|a, b| dictionary.cmp(&a[0].virtual_path.0, &b[0].virtual_path.0));
+	duplicates
+}
+
+// Whether two songs with the same tags are copies of one track, rather than different
+// tracks which happen to share a title (eg. an `Intro` on each disc)
+fn are_same_track(a: &storage::Song, b: &storage::Song) -> bool {
+	let similar_duration = match (a.duration, b.duration) {
+		(Some(a), Some(b)) => (a - b).abs() <= DURATION_TOLERANCE,
+		(None, None) => true,
+		_ => false,
+	};
+	let same_disc = match (a.disc_number, b.disc_number) {
+		(Some(a), Some(b)) => a == b,
+		_ => true,
+	};
+	let same_track = match (a.track_number, b.track_number) {
+		(Some(a), Some(b)) => a == b,
+		_ => true,
+	};
+	similar_duration && same_disc && same_track
+}
+
+fn is_distinctive(fingerprint: &Fingerprint) -> bool {
+	let num_bits = 32 * fingerprint.0.len();
+	if num_bits == 0 {
+		return false;
+	}
+	let num_set_bits = fingerprint
+		.0
+		.iter()
+		.map(|b| b.count_ones() as usize)
+		.sum::<usize>();
+	let density = num_set_bits as f32 / num_bits as f32;
+	(MIN_FINGERPRINT_DENSITY..=1.0 - MIN_FINGERPRINT_DENSITY).contains(&density)
+}
+
+// Lossless formats win over lossy ones, then larger files (higher bitrates) win
+fn compare_quality(a: &storage::Song, b: &storage::Song, dictionary: &Dictionary) -> Ordering {
+	let is_lossless = |song: &storage::Song| {
+		Path::new(dictionary.resolve(&song.virtual_path.0))
+			.extension()
+			.map(|e| e.to_string_lossy().to_lowercase())
+			.is_some_and(|e| LOSSLESS_FORMATS.contains(&e.as_str()))
+	};
+	is_lossless(a)
+		.cmp(&is_lossless(b))
+		.then_with(|| a.size.cmp(&b.size))
+		.then_with(|| dictionary.cmp(&b.virtual_path.0, &a.virtual_path.0))
+}
+
+struct DisjointSet {
+	parents: Vec<usize>,
+}
+
+impl DisjointSet {
+	fn new(size: usize) -> Self {
+		Self {
+			parents: (0..size).collect(),
+		}
+	}
+
+	fn find(&mut self, i: usize) -> usize {
+		let mut root = i;
+		while self.parents[root] != root {
+			root = self.parents[root];
+		}
+		let mut i = i;
+		while self.parents[i] != root {
+			let parent = self.parents[i];
+			self.parents[i] = root;
+			i = parent;
+		}
+		root
+	}
+
+	fn union(&mut self, a: usize, b: usize) {
+		let (a, b) = (self.find(a), self.find(b));
+		self.parents[a] = b;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use crate::app::{
+		index::{dictionary, storage::store_song},
+		scanner,
+	};
+
+	use super::*;
+
+	fn setup_test(songs: Vec<scanner::Song>) -> Vec<Vec<PathBuf>> {
+		let mut dictionary_builder = dictionary::Builder::default();
+		let mut stored_songs = HashMap::new();
+		let mut fingerprints = HashMap::new();
+		for song in &songs {
+			let stored_song = store_song(&mut dictionary_builder, song).unwrap();
+			let key = SongKey {
+				virtual_path: stored_song.virtual_path,
+			};
+			if let Some(fingerprint) = &song.fingerprint {
+				fingerprints.insert(key, fingerprint.clone());
+			}
+			stored_songs.insert(key, stored_song);
+		}
+		let dictionary = dictionary_builder.build();
+		find_duplicates(&stored_songs, &fingerprints, &dictionary)
+			.into_iter()
+			.map(|group| {
+				group
+					.into_iter()
+					.map(|k| PathBuf::from(dictionary.resolve(&k.virtual_path.0)))
+					.collect()
+			})
+			.collect()
+	}
+
+	#[test]
+	fn groups_songs_with_matching_metadata() {
+		let duplicates = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("mp3/Kai.mp3"),
+				title: Some("Kai".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(301),
+				size: 5_000,
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("flac/Kai.flac"),
+				title: Some("kai".to_owned()),
+				artists: vec!["fsol".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(300),
+				size: 20_000,
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("flac/Kai (Live).flac"),
+				title: Some("Kai".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				album: Some("ISDN".to_owned()),
+				duration: Some(420),
+				..Default::default()
+			},
+		]);
+		assert_eq!(
+			duplicates,
+			vec![vec![
+				PathBuf::from("flac/Kai.flac"),
+				PathBuf::from("mp3/Kai.mp3")
+			]]
+		);
+	}
+
+	#[test]
+	fn keeps_tracks_sharing_a_title_apart() {
+		let duplicates = setup_test(
+			[(1, "cd1/01 - Intro.mp3"), (2, "cd2/01 - Intro.mp3")]
+				.into_iter()
+				.map(|(disc_number, path)| scanner::Song {
+					virtual_path: PathBuf::from(path),
+					title: Some("Intro".to_owned()),
+					artists: vec!["FSOL".to_owned()],
+					album: Some("ISDN".to_owned()),
+					disc_number: Some(disc_number),
+					track_number: Some(1),
+					duration: Some(60),
+					..Default::default()
+				})
+				.collect(),
+		);
+		assert!(duplicates.is_empty());
+	}
+
+	#[test]
+	fn groups_songs_with_matching_fingerprints() {
+		let fingerprint = Fingerprint(vec![0b1011_0110, 0xFFFF_0000, 0x1234_5678]);
+		let duplicates = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("a/Track 01.mp3"),
+				duration: Some(300),
+				size: 5_000,
+				fingerprint: Some(fingerprint.clone()),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("b/Kai.ogg"),
+				title: Some("Kai".to_owned()),
+				duration: Some(299),
+				size: 8_000,
+				fingerprint: Some(fingerprint),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("c/Cascade.mp3"),
+				title: Some("Cascade".to_owned()),
+				duration: Some(300),
+				fingerprint: Some(Fingerprint(vec![0x0F0F_0F0F, 0x1111_1111, 0])),
+				..Default::default()
+			},
+		]);
+		assert_eq!(
+			duplicates,
+			vec![vec![
+				PathBuf::from("b/Kai.ogg"),
+				PathBuf::from("a/Track 01.mp3")
+			]]
+		);
+	}
+
+	#[test]
+	fn ignores_fingerprints_of_silent_songs() {
+		let duplicates = setup_test(
+			["a/Silence.mp3", "b/Hush.mp3"]
+				.into_iter()
+				.map(|path| scanner::Song {
+					virtual_path: PathBuf::from(path),
+					duration: Some(30),
+					fingerprint: Some(Fingerprint(vec![0; 8])),
+					..Default::default()
+				})
+				.collect(),
+		);
+		assert!(duplicates.is_empty());
+	}
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::index::storage::GenreKey;
+
+/// Parent/child relationships between genres, as defined in the configuration
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Taxonomy {
+	parents: HashMap<GenreKey, GenreKey>,
+	children: HashMap<GenreKey, Vec<GenreKey>>,
+}
+
+impl Taxonomy {
+	pub fn new(parents: HashMap<GenreKey, GenreKey>) -> Self {
+		let mut children = HashMap::<GenreKey, Vec<GenreKey>>::new();
+		for (child, parent) in &parents {
+			children.entry(*parent).or_default().push(*child);
+		}
+		Self { parents, children }
+	}
+
+	pub fn genres(&self) -> impl Iterator<Item = &GenreKey> {
+		self.parents.keys().chain(self.children.keys())
+	}
+
+	pub fn parent(&self, genre: GenreKey) -> Option<GenreKey> {
+		self.parents.get(&genre).copied()
+	}
+
+	// Closest ancestor first
+	pub fn ancestors(&self, genre: GenreKey) -> Vec<GenreKey> {
+		let mut ancestors = Vec::new();
+		let mut current = genre;
+		while let Some(parent) = self.parents.get(&current) {
+			// Merged spellings can still produce loops which config validation did not see
+			if *parent == genre || ancestors.contains(parent) {
+				break;
+			}
+			ancestors.push(*parent);
+			current = *parent;
+		}
+		ancestors
+	}
+
+	pub fn descendants(&self, genre: GenreKey) -> Vec<GenreKey> {
+		let mut descendants = Vec::new();
+		let mut pending = vec![genre];
+		while let Some(current) = pending.pop() {
+			for child in self.children.get(&current).into_iter().flatten() {
+				if *child != genre && !descendants.contains(child) {
+					descendants.push(*child);
+					pending.push(*child);
+				}
+			}
+		}
+		descendants
+	}
+
+	// Whether one genre is an ancestor of the other
+	pub fn is_lineage(&self, a: GenreKey, b: GenreKey) -> bool {
+		self.ancestors(a).contains(&b) || self.ancestors(b).contains(&a)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashSet;
+
+	use lasso2::Rodeo;
+
+	use super::*;
+
+	#[test]
+	fn can_walk_hierarchy() {
+		let mut strings = Rodeo::default();
+		let mut key = |s: &str| GenreKey(strings.get_or_intern(s));
+		let (metal, death, melodeath, folk) = (
+			key("Metal"),
+			key("Death Metal"),
+			key("Melodic Death Metal"),
+			key("Folk"),
+		);
+		let taxonomy = Taxonomy::new(HashMap::from([(death, metal), (melodeath, death)]));
+
+		assert_eq!(taxonomy.parent(melodeath), Some(death));
+		assert_eq!(taxonomy.ancestors(melodeath), vec![death, metal]);
+		assert_eq!(
+			taxonomy
+				.descendants(metal)
+				.into_iter()
+				.collect::<HashSet<_>>(),
+			HashSet::from([death, melodeath])
+		);
+		assert!(taxonomy.is_lineage(metal, melodeath));
+		assert!(!taxonomy.is_lineage(folk, melodeath));
+		assert!(taxonomy.descendants(folk).is_empty());
+	}
+}
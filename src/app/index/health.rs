@@ -0,0 +1,271 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::{Path, PathBuf},
+};
+
+use lasso2::Spur;
+use serde::{Deserialize, Serialize};
+
+use crate::app::index::{
+	dictionary::Dictionary,
+	storage::{self, AlbumKey, SongKey},
+};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum Rule {
+	/// Tracks of the album disagree on who the album artists are
+	MismatchedAlbumArtists,
+	/// Some tracks have no track number
+	MissingTrackNumbers,
+	/// Several tracks share the same disc and track number
+	DuplicateTrackNumbers,
+	/// Tracks of the album do not agree on a release year
+	YearDrift,
+	/// The album has no artwork
+	MissingArtwork,
+}
+
+pub fn analyze(
+	albums: &HashMap<AlbumKey, storage::Album>,
+	songs: &HashMap<SongKey, storage::Song>,
+	dictionary: &Dictionary,
+) -> HashMap<AlbumKey, Vec<Rule>> {
+	let split_albums = find_split_albums(albums, songs, dictionary);
+
+	albums
+		.iter()
+		.filter_map(|(album_key, album)| {
+			let album_songs = album
+				.songs
+				.iter()
+				.filter_map(|k| songs.get(k))
+				.collect::<Vec<_>>();
+
+			let mut issues = vec![];
+
+			let num_with_album_artists = album_songs
+				.iter()
+				.filter(|s| !s.album_artists.is_empty())
+				.count();
+			let inconsistent_album_artists =
+				num_with_album_artists > 0 && num_with_album_artists < album_songs.len();
+			if inconsistent_album_artists || split_albums.contains(album_key) {
+				issues.push(Rule::MismatchedAlbumArtists);
+			}
+
+			if album_songs.iter().any(|s| s.track_number.is_none()) {
+				issues.push(Rule::MissingTrackNumbers);
+			}
+
+			let mut positions = HashSet::new();
+			let has_duplicates = album_songs
+				.iter()
+				.filter_map(|s| s.track_number.map(|t| (s.disc_number, t)))
+				.any(|position| !positions.insert(position));
+			if has_duplicates {
+				issues.push(Rule::DuplicateTrackNumbers);
+			}
+
+			let years = album_songs
+				.iter()
+				.filter_map(|s| s.year)
+				.collect::<HashSet<_>>();
+			if years.len() > 1 {
+				issues.push(Rule::YearDrift);
+			}
+
+			if album.artwork.is_none() {
+				issues.push(Rule::MissingArtwork);
+			}
+
+			(!issues.is_empty()).then(|| (album_key.clone(), issues))
+		})
+		.collect()
+}
+
+// Albums sharing a name and a directory but keyed under different artists
+fn find_split_albums(
+	albums: &HashMap<AlbumKey, storage::Album>,
+	songs: &HashMap<SongKey, storage::Song>,
+	dictionary: &Dictionary,
+) -> HashSet<AlbumKey> {
+	let mut locations = HashMap::<(Spur, PathBuf), HashSet<&AlbumKey>>::new();
+	for (album_key, album) in albums {
+		for song in album.songs.iter().filter_map(|k| songs.get(k)) {
+			let virtual_path = Path::new(dictionary.resolve(&song.virtual_path.0));
+			let directory = virtual_path.parent().unwrap_or(virtual_path).to_owned();
+			locations
+				.entry((album.name, directory))
+				.or_default()
+				.insert(album_key);
+		}
+	}
+
+	locations
+		.into_values()
+		.filter(|keys| keys.len() > 1)
+		.flatten()
+		.cloned()
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use crate::app::{
+		index::{dictionary, storage::store_song},
+		scanner,
+	};
+
+	use super::*;
+
+	// Album artists and name of each album with issues
+	type Report = HashMap<(Vec<String>, String), Vec<Rule>>;
+
+	fn album(artists: &[&str], name: &str) -> (Vec<String>, String) {
+		(
+			artists.iter().map(|a| (*a).to_owned()).collect(),
+			name.to_owned(),
+		)
+	}
+
+	fn setup_test(songs: Vec<scanner::Song>) -> Report {
+		let mut dictionary_builder = dictionary::Builder::default();
+		let mut albums = HashMap::<AlbumKey, storage::Album>::new();
+		let mut stored_songs = HashMap::new();
+		for song in &songs {
+			let song = store_song(&mut dictionary_builder, song).unwrap();
+			if let Some(album_key) = song.album_key() {
+				let album = albums.entry(album_key.clone()).or_default();
+				album.name = album_key.name;
+				album.artwork = album.artwork.or(song.artwork);
+				album.songs.insert(SongKey {
+					virtual_path: song.virtual_path,
+				});
+			}
+			stored_songs.insert(
+				SongKey {
+					virtual_path: song.virtual_path,
+				},
+				song,
+			);
+		}
+		let dictionary = dictionary_builder.build();
+		analyze(&albums, &stored_songs, &dictionary)
+			.into_iter()
+			.map(|(k, v)| {
+				let artists = k
+					.artists
+					.iter()
+					.map(|a| dictionary.resolve(&a.0).to_owned())
+					.collect();
+				((artists, dictionary.resolve(&k.name).to_owned()), v)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn healthy_album_has_no_issues() {
+		let report = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("ISDN/Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				track_number: Some(1),
+				year: Some(1994),
+				artwork: Some(PathBuf::from("ISDN/Folder.jpg")),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("ISDN/Cascade.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				track_number: Some(2),
+				year: Some(1994),
+				artwork: Some(PathBuf::from("ISDN/Folder.jpg")),
+				..Default::default()
+			},
+		]);
+		assert!(report.is_empty());
+	}
+
+	#[test]
+	fn detects_track_number_and_year_issues() {
+		let report = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("ISDN/Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				track_number: Some(1),
+				year: Some(1994),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("ISDN/Cascade.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				track_number: Some(1),
+				year: Some(1995),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("ISDN/Smakatak.mp3"),
+				album: Some("ISDN".to_owned()),
+				artists: vec!["FSOL".to_owned()],
+				..Default::default()
+			},
+		]);
+		assert_eq!(
+			report[&album(&["FSOL"], "ISDN")],
+			vec![
+				Rule::MissingTrackNumbers,
+				Rule::DuplicateTrackNumbers,
+				Rule::YearDrift,
+				Rule::MissingArtwork,
+			]
+		);
+	}
+
+	#[test]
+	fn detects_mismatched_album_artists() {
+		let report = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("Elysium/Darkest Hours.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				album_artists: vec!["Stratovarius".to_owned()],
+				track_number: Some(1),
+				artwork: Some(PathBuf::from("Elysium/Folder.jpg")),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Elysium/Under Flaming Skies.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				track_number: Some(2),
+				artwork: Some(PathBuf::from("Elysium/Folder.jpg")),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Elysium/Infernal Maze.mp3"),
+				album: Some("Elysium".to_owned()),
+				artists: vec!["Stratovarius".to_owned()],
+				album_artists: vec!["Strato".to_owned()],
+				track_number: Some(3),
+				artwork: Some(PathBuf::from("Elysium/Folder.jpg")),
+				..Default::default()
+			},
+		]);
+		// Both halves of the split album are reported
+		assert_eq!(report.len(), 2);
+		assert_eq!(
+			report[&album(&["Stratovarius"], "Elysium")],
+			vec![Rule::MismatchedAlbumArtists]
+		);
+		assert_eq!(
+			report[&album(&["Strato"], "Elysium")],
+			vec![Rule::MismatchedAlbumArtists]
+		);
+	}
+}
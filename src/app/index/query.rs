@@ -1,12 +1,17 @@
-use std::collections::HashSet;
+use std::{
+	collections::{BTreeSet, HashSet},
+	ops::Range,
+	str::FromStr,
+};
 
 use chumsky::{
-	error::Simple,
+	error::{Simple, SimpleReason},
 	prelude::{choice, end, filter, just, none_of, recursive},
 	text::{int, keyword, whitespace, TextParser},
 	Parser,
 };
 use enum_map::Enum;
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, Hash, PartialEq, Serialize)]
@@ -26,11 +31,17 @@ pub enum TextField {
 pub enum TextOp {
 	Eq,
 	Like,
+	// Genre or any of its subgenres
+	Within,
+	Regex,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, Hash, PartialEq, Serialize)]
 pub enum NumberField {
+	Bitrate,
+	DateAdded,
 	DiscNumber,
+	Duration,
 	TrackNumber,
 	Year,
 }
@@ -61,54 +72,200 @@ pub enum BoolOp {
 pub enum Expr {
 	Fuzzy(Literal),
 	TextCmp(TextField, TextOp, String),
-	NumberCmp(NumberField, NumberOp, i32),
+	NumberCmp(NumberField, NumberOp, i64),
+	// Compares against a point in time this many seconds before the query is evaluated
+	RelativeCmp(NumberField, NumberOp, i64),
+	Not(Box<Expr>),
 	Combined(Box<Expr>, BoolOp, Box<Expr>),
 }
 
-pub fn make_parser() -> impl Parser<char, Expr, Error = Simple<char>> {
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Field {
+	Text(TextField),
+	Number(NumberField),
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Direction {
+	#[default]
+	Ascending,
+	Descending,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Query {
+	pub filter: Expr,
+	pub order_by: Vec<(Field, Direction)>,
+	pub limit: Option<usize>,
+}
+
+/// Describes why a query could not be parsed. Spans are measured in characters, not bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryError {
+	pub span: Range<usize>,
+	pub expected: Vec<String>,
+	pub hint: String,
+}
+
+// Field names accepted by the parser, in alphabetical order
+pub const FIELD_KEYWORDS: [&str; 15] = [
+	"album",
+	"albumartist",
+	"artist",
+	"bitrate",
+	"composer",
+	"dateadded",
+	"discnumber",
+	"duration",
+	"genre",
+	"label",
+	"lyricist",
+	"path",
+	"title",
+	"tracknumber",
+	"year",
+];
+
+// Misspelled field names within this edit distance are suggested in error messages
+const MAX_FIELD_TYPOS: usize = 2;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+const YEAR: i64 = 365 * DAY;
+
+pub fn make_regex(pattern: &str) -> Result<Regex, regex::Error> {
+	RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
+fn integer<T: FromStr>() -> impl Parser<char, T, Error = Simple<char>> + Clone {
+	int(10)
+		.from_str::<T>()
+		.try_map(|n, span| n.map_err(|_| Simple::custom(span, "number is too large")))
+}
+
+fn text_field() -> impl Parser<char, TextField, Error = Simple<char>> + Clone {
+	choice((
+		keyword("album").to(TextField::Album),
+		keyword("albumartist").to(TextField::AlbumArtist),
+		keyword("artist").to(TextField::Artist),
+		keyword("composer").to(TextField::Composer),
+		keyword("genre").to(TextField::Genre),
+		keyword("label").to(TextField::Label),
+		keyword("lyricist").to(TextField::Lyricist),
+		keyword("path").to(TextField::Path),
+		keyword("title").to(TextField::Title),
+	))
+	.padded()
+}
+
+fn number_field() -> impl Parser<char, NumberField, Error = Simple<char>> + Clone {
+	choice((
+		keyword("bitrate").to(NumberField::Bitrate),
+		keyword("dateadded").to(NumberField::DateAdded),
+		keyword("discnumber").to(NumberField::DiscNumber),
+		keyword("duration").to(NumberField::Duration),
+		keyword("tracknumber").to(NumberField::TrackNumber),
+		keyword("year").to(NumberField::Year),
+	))
+	.padded()
+}
+
+fn make_expr_parser() -> impl Parser<char, Expr, Error = Simple<char>> + Clone {
 	recursive(|expr| {
 		let quoted_str = just('"')
 			.ignore_then(none_of('"').repeated().collect::<String>())
 			.then_ignore(just('"'));
 
 		let symbols = r#"()<>"|&=!"#.chars().collect::<HashSet<_>>();
+		let list_symbols = symbols.clone();
+
+		// `ORDER` and `LIMIT` only end the filter when the rest of the query is a valid
+		// clause, so that they can still be searched for (eg. `New ORDER`)
+		let clauses = choice((keyword("ORDER"), keyword("LIMIT")))
+			.rewind()
+			.ignore_then(make_clauses_parser())
+			.then_ignore(end())
+			.rewind();
+		let raw_str = choice((
+			clauses.to(None),
+			filter(move |c: &char| !c.is_whitespace() && !symbols.contains(c))
+				.repeated()
+				.at_least(1)
+				.collect::<String>()
+				.map(Some),
+		))
+		.try_map(|s, span| {
+			s.ok_or_else(|| Simple::custom(span, "expected a search term before this clause"))
+		});
+
+		let str_ = choice((quoted_str.clone(), raw_str)).padded();
+
+		let number = integer::<i32>().padded();
+
+		let integer = integer::<i64>().padded();
+
+		let unit = choice((
+			just('s').to(1),
+			just('m').to(MINUTE),
+			just('h').to(HOUR),
+			just('d').to(DAY),
+			just('w').to(WEEK),
+			just('y').to(YEAR),
+		));
+
+		let quantity = integer.clone().then(unit.or_not()).padded();
 
-		let raw_str = filter(move |c: &char| !c.is_whitespace() && !symbols.contains(c))
-			.repeated()
-			.at_least(1)
-			.collect::<String>();
-
-		let str_ = choice((quoted_str, raw_str)).padded();
-
-		let number = int(10).from_str().unwrapped().padded();
-
-		let text_field = choice((
-			keyword("album").to(TextField::Album),
-			keyword("albumartist").to(TextField::AlbumArtist),
-			keyword("artist").to(TextField::Artist),
-			keyword("composer").to(TextField::Composer),
-			keyword("genre").to(TextField::Genre),
-			keyword("label").to(TextField::Label),
-			keyword("lyricist").to(TextField::Lyricist),
-			keyword("path").to(TextField::Path),
-			keyword("title").to(TextField::Title),
+		let text_op = choice((
+			just("=").to(TextOp::Eq),
+			just("%").to(TextOp::Like),
+			just("~").to(TextOp::Regex),
 		))
 		.padded();
 
-		let text_op = choice((just("=").to(TextOp::Eq), just("%").to(TextOp::Like))).padded();
+		let text_cmp =
+			text_field()
+				.then(text_op)
+				.then(str_.clone())
+				.try_map(|((a, b), c), span| {
+					if b == TextOp::Regex {
+						make_regex(&c).map_err(|e| Simple::custom(span, e.to_string()))?;
+					}
+					Ok(Expr::TextCmp(a, b, c))
+				});
 
-		let text_cmp = text_field
-			.then(text_op)
-			.then(str_.clone())
-			.map(|((a, b), c)| Expr::TextCmp(a, b, c));
+		let subgenre_cmp = keyword("genre")
+			.padded()
+			.ignore_then(just("<=").padded())
+			.ignore_then(str_.clone())
+			.map(|s| Expr::TextCmp(TextField::Genre, TextOp::Within, s));
 
-		let number_field = choice((
-			keyword("discnumber").to(NumberField::DiscNumber),
-			keyword("tracknumber").to(NumberField::TrackNumber),
-			keyword("year").to(NumberField::Year),
+		let list_str = choice((
+			quoted_str,
+			filter(move |c: &char| !c.is_whitespace() && !list_symbols.contains(c) && *c != ',')
+				.repeated()
+				.at_least(1)
+				.collect::<String>(),
 		))
 		.padded();
 
+		let text_in = text_field()
+			.then_ignore(keyword("IN").padded())
+			.then(
+				list_str
+					.clone()
+					.then(just(',').ignore_then(list_str).repeated())
+					.delimited_by(just('('), just(')'))
+					.padded(),
+			)
+			.map(|(field, (first, rest))| {
+				let cmp = |s| Expr::TextCmp(field, TextOp::Eq, s);
+				rest.into_iter().fold(cmp(first), |a, s| {
+					Expr::Combined(Box::new(a), BoolOp::Or, Box::new(cmp(s)))
+				})
+			});
+
 		let number_op = choice((
 			just("=").to(NumberOp::Eq),
 			just(">=").to(NumberOp::GreaterOrEq),
@@ -118,16 +275,61 @@ pub fn make_parser() -> impl Parser<char, Expr, Error = Simple<char>> {
 		))
 		.padded();
 
-		let number_cmp = number_field
-			.then(number_op)
-			.then(number)
-			.map(|((a, b), c)| Expr::NumberCmp(a, b, c));
+		let number_cmp = number_field().then(number_op).then(quantity).try_map(
+			|((field, op), (n, unit)), span| match (field, unit) {
+				(_, None) => Ok(Expr::NumberCmp(field, op, n)),
+				(NumberField::Duration, Some(seconds)) => n
+					.checked_mul(seconds)
+					.map(|n| Expr::NumberCmp(field, op, n))
+					.ok_or_else(|| Simple::custom(span, "duration is too large")),
+				(NumberField::DateAdded, Some(seconds)) => n
+					.checked_mul(seconds)
+					.map(|n| Expr::RelativeCmp(field, op, n))
+					.ok_or_else(|| Simple::custom(span, "duration is too large")),
+				(_, Some(_)) => Err(Simple::custom(
+					span,
+					"units are only supported for `duration` and `dateadded`",
+				)),
+			},
+		);
+
+		let number_in = number_field()
+			.then_ignore(keyword("IN").padded())
+			.then(
+				integer
+					.clone()
+					.then(just(',').ignore_then(integer).repeated())
+					.delimited_by(just('('), just(')'))
+					.padded(),
+			)
+			.map(|(field, (first, rest))| {
+				let cmp = |n| Expr::NumberCmp(field, NumberOp::Eq, n);
+				rest.into_iter().fold(cmp(first), |a, n| {
+					Expr::Combined(Box::new(a), BoolOp::Or, Box::new(cmp(n)))
+				})
+			});
 
 		let literal = choice((number.map(Literal::Number), str_.map(Literal::Text)));
 		let fuzzy = literal.map(Expr::Fuzzy);
 
-		let filter = choice((text_cmp, number_cmp, fuzzy));
-		let atom = choice((filter, expr.delimited_by(just('('), just(')'))));
+		let filter = choice((
+			subgenre_cmp,
+			text_in,
+			text_cmp,
+			number_in,
+			number_cmp,
+			fuzzy,
+		));
+		let term = choice((filter, expr.delimited_by(just('('), just(')'))));
+
+		let negation = choice((
+			keyword("NOT").padded().ignored(),
+			just('-').padded().ignored(),
+		))
+		.ignore_then(term.clone())
+		.map(|e| Expr::Not(Box::new(e)));
+
+		let atom = choice((negation, term));
 
 		let bool_op = choice((
 			just("&&").to(BoolOp::And),
@@ -148,7 +350,164 @@ pub fn make_parser() -> impl Parser<char, Expr, Error = Simple<char>> {
 
 		implicit_and
 	})
-	.then_ignore(end())
+}
+
+#[cfg(test)]
+pub fn make_parser() -> impl Parser<char, Expr, Error = Simple<char>> {
+	make_expr_parser().then_ignore(end())
+}
+
+pub fn parse_query(query: &str) -> Result<Query, QueryError> {
+	make_query_parser().parse(query).map_err(|errors| {
+		// Errors furthest into the query are the most specific
+		match errors.into_iter().max_by_key(|e| e.span().start) {
+			Some(error) => describe_error(query, error),
+			None => QueryError {
+				span: 0..query.chars().count(),
+				expected: Vec::new(),
+				hint: "invalid query".to_owned(),
+			},
+		}
+	})
+}
+
+fn describe_error(query: &str, error: Simple<char>) -> QueryError {
+	match error.reason() {
+		SimpleReason::Custom(message) => QueryError {
+			span: error.span(),
+			expected: Vec::new(),
+			hint: message.clone(),
+		},
+		SimpleReason::Unclosed { span, delimiter } => QueryError {
+			span: span.clone(),
+			expected: Vec::new(),
+			hint: format!("unclosed `{delimiter}`"),
+		},
+		SimpleReason::Unexpected => {
+			if let Some(unknown_field) = find_unknown_field(query, error.span().start) {
+				return unknown_field;
+			}
+
+			let expected = error
+				.expected()
+				.map(|e| match e {
+					Some(c) => format!("`{c}`"),
+					None => "end of query".to_owned(),
+				})
+				.collect::<BTreeSet<_>>()
+				.into_iter()
+				.collect::<Vec<_>>();
+
+			let mut hint = match error.found() {
+				Some(c) => format!("unexpected `{c}`"),
+				None => "unexpected end of query".to_owned(),
+			};
+			if !expected.is_empty() {
+				hint = format!("{hint}, expected {}", expected.join(" or "));
+			}
+
+			QueryError {
+				span: error.span(),
+				expected,
+				hint,
+			}
+		}
+	}
+}
+
+// Detects comparisons against a misspelled field name, such as `yaer > 2000`
+fn find_unknown_field(query: &str, position: usize) -> Option<QueryError> {
+	let chars = query.chars().collect::<Vec<_>>();
+	if !matches!(chars.get(position), Some('=' | '%' | '~' | '<' | '>')) {
+		return None;
+	}
+
+	let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+	let end = position
+		- chars[..position]
+			.iter()
+			.rev()
+			.take_while(|c| c.is_whitespace())
+			.count();
+	let start = end - chars[..end].iter().rev().take_while(|c| is_word(c)).count();
+	let word = chars[start..end].iter().collect::<String>();
+	if word.is_empty() || FIELD_KEYWORDS.contains(&word.to_lowercase().as_str()) {
+		return None;
+	}
+
+	let closest = FIELD_KEYWORDS
+		.iter()
+		.map(|k| (edit_distance(&word.to_lowercase(), k), k))
+		.filter(|(distance, _)| *distance <= MAX_FIELD_TYPOS)
+		.min();
+	let hint = match closest {
+		Some((_, keyword)) => format!("unknown field `{word}`, did you mean `{keyword}`?"),
+		None => format!("unknown field `{word}`"),
+	};
+
+	Some(QueryError {
+		span: start..end,
+		expected: FIELD_KEYWORDS.iter().map(|k| format!("`{k}`")).collect(),
+		hint,
+	})
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+	let b = b.chars().collect::<Vec<_>>();
+	let mut previous = (0..=b.len()).collect::<Vec<_>>();
+	for (i, a) in a.chars().enumerate() {
+		let mut current = vec![i + 1; b.len() + 1];
+		for (j, b) in b.iter().enumerate() {
+			let substitution = previous[j] + usize::from(a != *b);
+			current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+		}
+		previous = current;
+	}
+	previous[b.len()]
+}
+
+// Optional `ORDER BY` and `LIMIT` clauses ending a query
+fn make_clauses_parser(
+) -> impl Parser<char, (Option<Vec<(Field, Direction)>>, Option<usize>), Error = Simple<char>> + Clone
+{
+	let field = choice((
+		text_field().map(Field::Text),
+		number_field().map(Field::Number),
+	));
+
+	let direction = choice((
+		keyword("ASC").to(Direction::Ascending),
+		keyword("DESC").to(Direction::Descending),
+	))
+	.padded();
+
+	let order_by = keyword("ORDER")
+		.padded()
+		.ignore_then(keyword("BY").padded())
+		.ignore_then(
+			field
+				.then(direction.or_not().map(Option::unwrap_or_default))
+				.separated_by(just(',').padded())
+				.at_least(1),
+		);
+
+	let limit = keyword("LIMIT")
+		.padded()
+		.ignore_then(integer::<usize>().padded());
+
+	order_by.or_not().then(limit.or_not())
+}
+
+/// Parses a filter expression, optionally followed by `ORDER BY` and `LIMIT` clauses
+fn make_query_parser() -> impl Parser<char, Query, Error = Simple<char>> {
+	make_expr_parser()
+		.then(make_clauses_parser())
+		.then_ignore(end())
+		.map(|(filter, (order_by, limit))| Query {
+			filter,
+			order_by: order_by.unwrap_or_default(),
+			limit,
+		})
 }
 
 #[test]
@@ -239,6 +598,15 @@ fn can_parse_text_fields() {
 	);
 }
 
+#[test]
+fn field_keywords_are_parsed() {
+	let parser = make_parser();
+	for keyword in FIELD_KEYWORDS {
+		let expr = parser.parse(format!("{keyword} = 1").as_str()).unwrap();
+		assert!(!matches!(expr, Expr::Fuzzy(_)));
+	}
+}
+
 #[test]
 fn can_parse_text_operators() {
 	let parser = make_parser();
@@ -250,6 +618,10 @@ fn can_parse_text_operators() {
 		parser.parse(r#"album % "legendary tales""#).unwrap(),
 		Expr::TextCmp(TextField::Album, TextOp::Like, "legendary tales".to_owned()),
 	);
+	assert_eq!(
+		parser.parse(r#"genre <= metal"#).unwrap(),
+		Expr::TextCmp(TextField::Genre, TextOp::Within, "metal".to_owned()),
+	);
 }
 
 #[test]
@@ -269,6 +641,28 @@ fn can_parse_number_fields() {
 	);
 }
 
+#[test]
+fn can_parse_number_units() {
+	let parser = make_parser();
+	assert_eq!(
+		parser.parse(r#"duration > 5m"#).unwrap(),
+		Expr::NumberCmp(NumberField::Duration, NumberOp::Greater, 300),
+	);
+	assert_eq!(
+		parser.parse(r#"duration < 90s"#).unwrap(),
+		Expr::NumberCmp(NumberField::Duration, NumberOp::Less, 90),
+	);
+	assert_eq!(
+		parser.parse(r#"dateadded >= 2w"#).unwrap(),
+		Expr::RelativeCmp(NumberField::DateAdded, NumberOp::GreaterOrEq, 2 * WEEK),
+	);
+	assert_eq!(
+		parser.parse(r#"dateadded > 1700000000"#).unwrap(),
+		Expr::NumberCmp(NumberField::DateAdded, NumberOp::Greater, 1700000000),
+	);
+	assert!(parser.parse(r#"year > 7d"#).is_err());
+}
+
 #[test]
 fn can_parse_number_operators() {
 	let parser = make_parser();
@@ -294,6 +688,182 @@ fn can_parse_number_operators() {
 	);
 }
 
+#[test]
+fn can_parse_regex_operator() {
+	let parser = make_parser();
+	assert_eq!(
+		parser.parse(r#"title ~ "^the (.*) song$""#).unwrap(),
+		Expr::TextCmp(
+			TextField::Title,
+			TextOp::Regex,
+			"^the (.*) song$".to_owned()
+		),
+	);
+	assert!(parser.parse(r#"title ~ "(""#).is_err());
+}
+
+#[test]
+fn can_parse_lists() {
+	let parser = make_parser();
+	assert_eq!(
+		parser.parse(r#"artist IN (abba, "pink floyd")"#).unwrap(),
+		Expr::Combined(
+			Box::new(Expr::TextCmp(
+				TextField::Artist,
+				TextOp::Eq,
+				"abba".to_owned()
+			)),
+			BoolOp::Or,
+			Box::new(Expr::TextCmp(
+				TextField::Artist,
+				TextOp::Eq,
+				"pink floyd".to_owned()
+			)),
+		),
+	);
+	assert_eq!(
+		parser.parse(r#"year IN (1990,1991)"#).unwrap(),
+		Expr::Combined(
+			Box::new(Expr::NumberCmp(NumberField::Year, NumberOp::Eq, 1990)),
+			BoolOp::Or,
+			Box::new(Expr::NumberCmp(NumberField::Year, NumberOp::Eq, 1991)),
+		),
+	);
+}
+
+#[test]
+fn can_use_unary_not() {
+	let parser = make_parser();
+	assert_eq!(
+		parser.parse(r#"metal NOT live"#).unwrap(),
+		Expr::Combined(
+			Box::new(Expr::Fuzzy(Literal::Text("metal".to_owned()))),
+			BoolOp::And,
+			Box::new(Expr::Not(Box::new(Expr::Fuzzy(Literal::Text(
+				"live".to_owned()
+			))))),
+		),
+	);
+	assert_eq!(
+		parser.parse(r#"-(album % live)"#).unwrap(),
+		Expr::Not(Box::new(Expr::TextCmp(
+			TextField::Album,
+			TextOp::Like,
+			"live".to_owned()
+		))),
+	);
+	assert_eq!(
+		parser.parse(r#"a-ha"#).unwrap(),
+		Expr::Fuzzy(Literal::Text("a-ha".to_owned())),
+	);
+}
+
+#[test]
+fn can_parse_order_by_and_limit() {
+	let parser = make_query_parser();
+	assert_eq!(
+		parser
+			.parse(r#"metal ORDER BY year DESC, title LIMIT 10"#)
+			.unwrap(),
+		Query {
+			filter: Expr::Fuzzy(Literal::Text("metal".to_owned())),
+			order_by: vec![
+				(Field::Number(NumberField::Year), Direction::Descending),
+				(Field::Text(TextField::Title), Direction::Ascending),
+			],
+			limit: Some(10),
+		},
+	);
+	assert_eq!(
+		parser.parse(r#"order limit"#).unwrap(),
+		Query {
+			filter: Expr::Combined(
+				Box::new(Expr::Fuzzy(Literal::Text("order".to_owned()))),
+				BoolOp::And,
+				Box::new(Expr::Fuzzy(Literal::Text("limit".to_owned()))),
+			),
+			order_by: vec![],
+			limit: None,
+		},
+	);
+	assert!(parser.parse(r#"ORDER BY year"#).is_err());
+}
+
+#[test]
+fn reserved_words_are_searched_for_outside_of_clauses() {
+	let parser = make_query_parser();
+	assert_eq!(
+		parser.parse(r#"New ORDER"#).unwrap(),
+		Query {
+			filter: Expr::Combined(
+				Box::new(Expr::Fuzzy(Literal::Text("New".to_owned()))),
+				BoolOp::And,
+				Box::new(Expr::Fuzzy(Literal::Text("ORDER".to_owned()))),
+			),
+			order_by: vec![],
+			limit: None,
+		},
+	);
+	assert_eq!(
+		parser.parse(r#"New ORDER LIMIT 5"#).unwrap(),
+		Query {
+			filter: Expr::Combined(
+				Box::new(Expr::Fuzzy(Literal::Text("New".to_owned()))),
+				BoolOp::And,
+				Box::new(Expr::Fuzzy(Literal::Text("ORDER".to_owned()))),
+			),
+			order_by: vec![],
+			limit: Some(5),
+		},
+	);
+}
+
+#[test]
+fn rejects_numbers_out_of_range() {
+	let parser = make_query_parser();
+	assert!(parser.parse(r#"dateadded > 99999999999999999y"#).is_err());
+	assert!(parser.parse(r#"duration < 99999999999999999w"#).is_err());
+	assert!(parser.parse(r#"year > 99999999999999999999"#).is_err());
+
+	// Large numbers can still be searched for as text
+	assert_eq!(
+		parser
+			.parse(r#"metal LIMIT 99999999999999999999999"#)
+			.unwrap()
+			.limit,
+		None,
+	);
+	assert_eq!(
+		parser.parse(r#"99999999999999999999"#).unwrap().filter,
+		Expr::Fuzzy(Literal::Text("99999999999999999999".to_owned())),
+	);
+}
+
+#[test]
+fn errors_suggest_field_names() {
+	let error = parse_query("yaer > 2000").unwrap_err();
+	assert_eq!(error.span, 0..4);
+	assert_eq!(error.hint, "unknown field `yaer`, did you mean `year`?");
+
+	let error = parse_query("rock && tilte = \"paranoid\"").unwrap_err();
+	assert_eq!(error.span, 8..13);
+	assert_eq!(error.hint, "unknown field `tilte`, did you mean `title`?");
+
+	let error = parse_query("zzzzzz = 1").unwrap_err();
+	assert_eq!(error.hint, "unknown field `zzzzzz`");
+}
+
+#[test]
+fn errors_point_at_offending_characters() {
+	let error = parse_query("(album % lands").unwrap_err();
+	assert!(error.hint.starts_with("unexpected end of query"));
+	assert_eq!(error.span.start, 14);
+
+	let error = parse_query("album = abba )").unwrap_err();
+	assert_eq!(error.span, 13..14);
+	assert!(error.hint.starts_with("unexpected `)`"));
+}
+
 #[test]
 fn can_use_and_operator() {
 	let parser = make_parser();
@@ -0,0 +1,252 @@
+use std::cmp::Reverse;
+
+use crate::app::index::{
+	dictionary::Dictionary,
+	query::{BoolOp, Expr, Literal, TextField, TextOp},
+	storage::Song,
+};
+
+const TEXT_FIELDS: [TextField; 9] = [
+	TextField::Album,
+	TextField::AlbumArtist,
+	TextField::Artist,
+	TextField::Composer,
+	TextField::Genre,
+	TextField::Label,
+	TextField::Lyricist,
+	TextField::Path,
+	TextField::Title,
+];
+
+const EXACT_MATCH: u32 = 10;
+const PREFIX_MATCH: u32 = 6;
+const PARTIAL_MATCH: u32 = 3;
+
+/// Sanitized text a query looks for, optionally restricted to a single field
+pub struct Term {
+	pub field: Option<TextField>,
+	pub value: String,
+}
+
+/// Scores how well songs match the text terms of a search query. Matching more terms, matching
+/// them in more prominent fields (title > artist > album > path) and matching whole values or
+/// prefixes rather than arbitrary substrings all increase the score.
+pub struct Scorer {
+	terms: Vec<Term>,
+}
+
+impl Scorer {
+	pub fn new(dictionary: &Dictionary, query: &Expr) -> Self {
+		let mut terms = Vec::new();
+		collect_terms(dictionary, query, &mut terms);
+		Self { terms }
+	}
+
+	pub fn terms(&self) -> &[Term] {
+		&self.terms
+	}
+
+	pub fn score(&self, dictionary: &Dictionary, song: &Song) -> u32 {
+		if self.terms.is_empty() {
+			return 0;
+		}
+
+		let mut total = 0;
+		let mut num_matched_terms = 0;
+		for term in &self.terms {
+			let fields = match &term.field {
+				Some(field) => std::slice::from_ref(field),
+				None => &TEXT_FIELDS[..],
+			};
+			let best = fields
+				.iter()
+				.map(|field| {
+					let quality = field_values(dictionary, song, *field)
+						.into_iter()
+						.map(|value| match_quality(dictionary, &term.value, value))
+						.max()
+						.unwrap_or(0);
+					field_weight(*field) * quality
+				})
+				.max()
+				.unwrap_or(0);
+			if best > 0 {
+				num_matched_terms += 1;
+			}
+			total += best;
+		}
+
+		// Partial term coverage is penalized on top of the missing term scores
+		total * num_matched_terms / self.terms.len() as u32
+	}
+}
+
+/// Indices of the names matching a search query, from most to least relevant
+pub fn rank_names(dictionary: &Dictionary, query: &str, names: &[String]) -> Vec<usize> {
+	let term = dictionary.sanitize(query);
+	if term.is_empty() {
+		return Vec::new();
+	}
+	let mut matches = names
+		.iter()
+		.enumerate()
+		.map(|(i, name)| (match_quality(dictionary, &term, name), i))
+		.filter(|(quality, _)| *quality > 0)
+		.collect::<Vec<_>>();
+	matches.sort_by_key(|(quality, _)| Reverse(*quality));
+	matches.into_iter().map(|(_, i)| i).collect()
+}
+
+fn collect_terms(dictionary: &Dictionary, expr: &Expr, terms: &mut Vec<Term>) {
+	let (field, value) = match expr {
+		Expr::Fuzzy(Literal::Text(s)) => (None, dictionary.sanitize(s)),
+		Expr::Fuzzy(Literal::Number(n)) => (None, n.to_string()),
+		Expr::TextCmp(_, TextOp::Regex, _) => return,
+		Expr::TextCmp(field, _, s) => (Some(*field), dictionary.sanitize(s)),
+		Expr::NumberCmp(_, _, _) | Expr::RelativeCmp(_, _, _) | Expr::Not(_) => return,
+		Expr::Combined(e, BoolOp::Not, _) => return collect_terms(dictionary, e, terms),
+		Expr::Combined(e, _, f) => {
+			collect_terms(dictionary, e, terms);
+			return collect_terms(dictionary, f, terms);
+		}
+	};
+	if !value.is_empty() {
+		terms.push(Term { field, value });
+	}
+}
+
+fn field_weight(field: TextField) -> u32 {
+	match field {
+		TextField::Title => 8,
+		TextField::Artist | TextField::AlbumArtist => 6,
+		TextField::Album => 4,
+		TextField::Composer | TextField::Genre | TextField::Label | TextField::Lyricist => 2,
+		TextField::Path => 1,
+	}
+}
+
+fn field_values<'a>(dictionary: &'a Dictionary, song: &Song, field: TextField) -> Vec<&'a str> {
+	match field {
+		TextField::Album => song.album.iter().map(|s| dictionary.resolve(s)).collect(),
+		TextField::AlbumArtist => song
+			.album_artists
+			.iter()
+			.map(|a| dictionary.resolve(&a.0))
+			.collect(),
+		TextField::Artist => song
+			.artists
+			.iter()
+			.map(|a| dictionary.resolve(&a.0))
+			.collect(),
+		TextField::Composer => song
+			.composers
+			.iter()
+			.map(|a| dictionary.resolve(&a.0))
+			.collect(),
+		TextField::Genre => song.genres.iter().map(|s| dictionary.resolve(s)).collect(),
+		TextField::Label => song.labels.iter().map(|s| dictionary.resolve(s)).collect(),
+		TextField::Lyricist => song
+			.lyricists
+			.iter()
+			.map(|a| dictionary.resolve(&a.0))
+			.collect(),
+		TextField::Path => vec![dictionary.resolve(&song.virtual_path.0)],
+		TextField::Title => song.title.iter().map(|s| dictionary.resolve(s)).collect(),
+	}
+}
+
+// How closely a value matches an already sanitized search term
+pub fn match_quality(dictionary: &Dictionary, term: &str, value: &str) -> u32 {
+	let sanitized = dictionary.sanitize(value);
+	if sanitized == term {
+		EXACT_MATCH
+	} else if sanitized.starts_with(term)
+		|| value
+			.split(|c: char| c.is_whitespace() || std::path::is_separator(c))
+			.any(|word| dictionary.sanitize(word).starts_with(term))
+	{
+		PREFIX_MATCH
+	} else if sanitized.contains(term) {
+		PARTIAL_MATCH
+	} else {
+		0
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use chumsky::Parser;
+
+	use super::*;
+	use crate::app::index::{dictionary, query::make_parser, storage::store_song};
+	use crate::app::scanner;
+
+	fn score_song(query: &str, song: &scanner::Song) -> u32 {
+		let mut dictionary_builder = dictionary::Builder::default();
+		let song = store_song(&mut dictionary_builder, song).unwrap();
+		let dictionary = dictionary_builder.build();
+		let query = make_parser().parse(query).unwrap();
+		Scorer::new(&dictionary, &query).score(&dictionary, &song)
+	}
+
+	fn song(title: &str, artist: &str, album: &str) -> scanner::Song {
+		scanner::Song {
+			virtual_path: PathBuf::from(format!("{artist}/{album}/{title}.mp3")),
+			title: Some(title.to_owned()),
+			artists: vec![artist.to_owned()],
+			album: Some(album.to_owned()),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn exact_title_beats_partial_title() {
+		let time = song("Time", "Pink Floyd", "The Dark Side of the Moon");
+		let sometimes = song("Sometimes", "Erasure", "The Circus");
+		assert!(score_song("time", &time) > score_song("time", &sometimes));
+	}
+
+	#[test]
+	fn prefix_beats_substring() {
+		let timeless = song("Timeless", "Goldie", "Timeless");
+		let sometimes = song("Sometimes", "Erasure", "The Circus");
+		assert!(score_song("time", &timeless) > score_song("time", &sometimes));
+
+		let no_time = song("No Time to Cry", "Sisters of Mercy", "Floodland");
+		assert!(score_song("time", &no_time) > score_song("time", &sometimes));
+	}
+
+	#[test]
+	fn title_beats_artist_beats_album() {
+		let title = song("Rain", "The Beatles", "Revolver");
+		let artist = song("Glory Box", "Rain", "Dummy");
+		let album = song("Go", "Moby", "Rain");
+		assert!(score_song("rain", &title) > score_song("rain", &artist));
+		assert!(score_song("rain", &artist) > score_song("rain", &album));
+	}
+
+	#[test]
+	fn rewards_term_coverage() {
+		let both = song("Time", "Pink Floyd", "The Dark Side of the Moon");
+		let one = song("Time", "Hans Zimmer", "Inception");
+		assert!(score_song("time floyd", &both) > score_song("time floyd", &one));
+	}
+
+	#[test]
+	fn can_rank_names() {
+		let names = ["Road Trip", "Trip Hop", "Gym", "Triplets"].map(|s| s.to_owned());
+		let ranked = rank_names(&Dictionary::default(), "trip", &names);
+		assert_eq!(ranked, vec![0, 1, 3]);
+		let ranked = rank_names(&Dictionary::default(), "Road Trip", &names);
+		assert_eq!(ranked, vec![0]);
+	}
+
+	#[test]
+	fn field_terms_only_match_their_field() {
+		let time = song("Time", "Pink Floyd", "The Dark Side of the Moon");
+		assert_eq!(score_song("artist % time", &time), 0);
+		assert!(score_song("title % time", &time) > 0);
+	}
+}
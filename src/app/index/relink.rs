@@ -0,0 +1,219 @@
+use std::{collections::HashSet, path::Path};
+
+use crate::app::index::collection::Song;
+
+// Maximum difference in duration (in seconds) between a song and its new location
+const DURATION_TOLERANCE: i64 = 2;
+// Minimum score for a song to be considered the new location of a missing song
+const MIN_SCORE: f32 = 0.6;
+
+/// Finds which of `candidates` is most likely `song` after it was moved or renamed. Nothing is
+/// returned unless a single candidate is a confident match.
+pub fn find_new_location<'a>(song: &Song, candidates: &'a [Song]) -> Option<&'a Song> {
+	let mut best_match = None;
+	let mut best_score = MIN_SCORE;
+	let mut is_ambiguous = false;
+	for candidate in candidates {
+		let score = score(song, candidate);
+		if score > best_score {
+			best_match = Some(candidate);
+			best_score = score;
+			is_ambiguous = false;
+		} else if score == best_score && best_match.is_some() {
+			is_ambiguous = true;
+		}
+	}
+	match is_ambiguous {
+		true => None,
+		false => best_match,
+	}
+}
+
+// Likelihood of `candidate` being `song` at a different location, from 0 to 1
+fn score(song: &Song, candidate: &Song) -> f32 {
+	if let (Some(a), Some(b)) = (song.duration, candidate.duration) {
+		if (a - b).abs() > DURATION_TOLERANCE {
+			return 0.0;
+		}
+	}
+
+	let title = match (&song.title, &candidate.title) {
+		(Some(a), Some(b)) if a.to_lowercase() == b.to_lowercase() => Some(1.0),
+		(Some(_), Some(_)) => return 0.0,
+		(None, None) => None,
+		_ => Some(0.0),
+	};
+
+	let artists = match song.artists.is_empty() {
+		true => 0.0,
+		false => {
+			let a = song.artists.iter().map(|a| a.to_lowercase());
+			let b = candidate.artists.iter().map(|a| a.to_lowercase());
+			match a.collect::<HashSet<_>>() == b.collect::<HashSet<_>>() {
+				true => 1.0,
+				false => 0.0,
+			}
+		}
+	};
+
+	let album = match (&song.album, &candidate.album) {
+		(Some(a), Some(b)) if a.to_lowercase() == b.to_lowercase() => 1.0,
+		_ => 0.0,
+	};
+
+	let file_name = file_name_similarity(&song.virtual_path, &candidate.virtual_path);
+
+	match title {
+		Some(title) => 0.4 * title + 0.2 * artists + 0.2 * album + 0.2 * file_name,
+		// Untitled songs are judged on their other properties alone
+		None => (artists + album + file_name) / 3.0,
+	}
+}
+
+// Proportion of words shared by two file names, ignoring their extension
+fn file_name_similarity(a: &Path, b: &Path) -> f32 {
+	let words = |path: &Path| {
+		path.file_stem()
+			.map(|s| s.to_string_lossy().to_lowercase())
+			.unwrap_or_default()
+			.split(|c: char| !c.is_alphanumeric())
+			.filter(|w| !w.is_empty())
+			.map(str::to_owned)
+			.collect::<HashSet<_>>()
+	};
+	let (a, b) = (words(a), words(b));
+	let num_words = a.union(&b).count();
+	if num_words == 0 {
+		return 0.0;
+	}
+	a.intersection(&b).count() as f32 / num_words as f32
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use super::*;
+
+	fn make_song(virtual_path: &str, title: &str, artist: &str, duration: i64) -> Song {
+		Song {
+			virtual_path: PathBuf::from(virtual_path),
+			title: Some(title.to_owned()),
+			artists: vec![artist.to_owned()],
+			album: Some("Hunted".to_owned()),
+			duration: Some(duration),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn finds_moved_song() {
+		let song = make_song(
+			"Music/Khemmis/01 - Above The Water.mp3",
+			"Above The Water",
+			"Khemmis",
+			421,
+		);
+		let candidates = vec![
+			make_song(
+				"Music/Khemmis/Hunted/02 - Candlelight.mp3",
+				"Candlelight",
+				"Khemmis",
+				350,
+			),
+			make_song(
+				"Music/Khemmis/Hunted/01 - Above The Water.mp3",
+				"Above The Water",
+				"Khemmis",
+				421,
+			),
+		];
+		let new_location = find_new_location(&song, &candidates).unwrap();
+		assert_eq!(new_location.virtual_path, candidates[1].virtual_path);
+	}
+
+	#[test]
+	fn finds_renamed_song() {
+		let song = make_song(
+			"Music/Khemmis/01 - Above The Water.mp3",
+			"Above The Water",
+			"Khemmis",
+			421,
+		);
+		let candidates = vec![make_song(
+			"Music/Khemmis/track01.flac",
+			"above the water",
+			"KHEMMIS",
+			420,
+		)];
+		assert!(find_new_location(&song, &candidates).is_some());
+	}
+
+	#[test]
+	fn finds_moved_untitled_song() {
+		let song = Song {
+			title: None,
+			..make_song("Music/Khemmis/01 - Above The Water.mp3", "", "Khemmis", 421)
+		};
+		let candidates = vec![Song {
+			title: None,
+			..make_song(
+				"Music/Khemmis/Hunted/01 - Above The Water.mp3",
+				"",
+				"Khemmis",
+				421,
+			)
+		}];
+		assert!(find_new_location(&song, &candidates).is_some());
+	}
+
+	#[test]
+	fn rejects_different_songs() {
+		let song = make_song(
+			"Music/Khemmis/01 - Above The Water.mp3",
+			"Above The Water",
+			"Khemmis",
+			421,
+		);
+		let candidates = vec![
+			make_song(
+				"Music/Khemmis/01 - Above The Water.mp3",
+				"Above The Water (Live)",
+				"Khemmis",
+				421,
+			),
+			make_song(
+				"Music/Khemmis/Hunted/01 - Above The Water.mp3",
+				"Above The Water",
+				"Khemmis",
+				480,
+			),
+		];
+		assert!(find_new_location(&song, &candidates).is_none());
+	}
+
+	#[test]
+	fn rejects_ambiguous_matches() {
+		let song = make_song(
+			"Music/Khemmis/01 - Above The Water.mp3",
+			"Above The Water",
+			"Khemmis",
+			421,
+		);
+		let candidates = vec![
+			make_song(
+				"Music/A/01 - Above The Water.mp3",
+				"Above The Water",
+				"Khemmis",
+				421,
+			),
+			make_song(
+				"Music/B/01 - Above The Water.mp3",
+				"Above The Water",
+				"Khemmis",
+				421,
+			),
+		];
+		assert!(find_new_location(&song, &candidates).is_none());
+	}
+}
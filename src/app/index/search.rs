@@ -1,21 +1,70 @@
-use chumsky::Parser;
 use enum_map::EnumMap;
 use lasso2::Spur;
-use nohash_hasher::IntSet;
+use nohash_hasher::{IntMap, IntSet};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::{
+	collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+	time::{SystemTime, UNIX_EPOCH},
+};
 use tinyvec::TinyVec;
 
 use crate::app::{
 	index::{
 		dictionary::Dictionary,
-		query::{BoolOp, Expr, Literal, NumberField, NumberOp, TextField, TextOp},
-		storage::SongKey,
+		query::{make_regex, BoolOp, Expr, Literal, NumberField, NumberOp, TextField, TextOp},
+		storage::{ArtistKey, GenreKey, SongKey},
 	},
 	scanner, Error,
 };
 
-use super::{collection, dictionary::sanitize, query::make_parser, storage};
+use super::{
+	collection, dictionary,
+	query::{parse_query, Query},
+	relevance, storage,
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SearchOrder {
+	#[default]
+	Alphabetical,
+	Relevance,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SearchResult {
+	pub song: collection::Song,
+	pub score: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SearchLimits {
+	pub artists: usize,
+	pub albums: usize,
+	pub genres: usize,
+	pub songs: usize,
+}
+
+impl Default for SearchLimits {
+	fn default() -> Self {
+		Self {
+			artists: 5,
+			albums: 5,
+			genres: 5,
+			songs: 20,
+		}
+	}
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SearchGroups {
+	pub artists: Vec<collection::ArtistHeader>,
+	pub albums: Vec<collection::AlbumHeader>,
+	pub genres: Vec<collection::GenreHeader>,
+	pub songs: Vec<SearchResult>,
+	// Number of matching songs before `songs` was truncated
+	pub total_songs: usize,
+}
 
 #[derive(Serialize, Deserialize)]
 pub struct Search {
@@ -38,50 +87,298 @@ impl Search {
 		collection: &collection::Collection,
 		dictionary: &Dictionary,
 		query: &str,
-	) -> Result<Vec<collection::Song>, Error> {
-		let parser = make_parser();
-		let parsed_query = parser
-			.parse(query)
-			.map_err(|_| Error::SearchQueryParseError)?;
+		order: SearchOrder,
+	) -> Result<Vec<SearchResult>, Error> {
+		let parsed_query = parse_query(query).map_err(Error::SearchQueryParseError)?;
+
+		let mut songs = self.rank_songs(collection, dictionary, &parsed_query, order);
+		if let Some(limit) = parsed_query.limit {
+			songs.truncate(limit);
+		}
+
+		Ok(songs
+			.into_iter()
+			.filter_map(|(song_key, score)| {
+				let song = collection.get_song(dictionary, song_key)?;
+				Some(SearchResult { song, score })
+			})
+			.collect())
+	}
 
+	// Songs matching a query alongside their relevance score, in result order
+	fn rank_songs(
+		&self,
+		collection: &collection::Collection,
+		dictionary: &Dictionary,
+		parsed_query: &Query,
+		order: SearchOrder,
+	) -> Vec<(SongKey, u32)> {
 		let mut songs = self
-			.eval(dictionary, &parsed_query)
+			.eval(collection, dictionary, &parsed_query.filter)
 			.into_iter()
 			.collect::<Vec<_>>();
 		collection.sort_songs(&mut songs, dictionary);
+		collection.sort_songs_by_fields(&mut songs, dictionary, &parsed_query.order_by);
+
+		// An explicit `ORDER BY` clause takes precedence over the requested order
+		let scorer = (order == SearchOrder::Relevance && parsed_query.order_by.is_empty())
+			.then(|| relevance::Scorer::new(dictionary, &parsed_query.filter));
+		let mut results = songs
+			.into_iter()
+			.map(|song_key| {
+				let score = scorer
+					.as_ref()
+					.zip(collection.get_stored_song(song_key))
+					.map(|(scorer, song)| scorer.score(dictionary, song))
+					.unwrap_or_default();
+				(song_key, score)
+			})
+			.collect::<Vec<_>>();
+
+		if scorer.is_some() {
+			// Stable sort keeps equally relevant songs in alphabetical order
+			results.sort_by(|(_, a), (_, b)| b.cmp(a));
+		}
+
+		results
+	}
+
+	/// Finds artists, albums and genres whose name matches the query, alongside the most relevant
+	/// matching songs.
+	pub fn find_all(
+		&self,
+		collection: &collection::Collection,
+		dictionary: &Dictionary,
+		query: &str,
+		limits: &SearchLimits,
+		collapse_duplicates: bool,
+	) -> Result<SearchGroups, Error> {
+		let parsed_query = parse_query(query).map_err(Error::SearchQueryParseError)?;
+		let (songs, total_songs) = match parsed_query.order_by.is_empty() {
+			true => self.find_top_songs(
+				collection,
+				dictionary,
+				&parsed_query,
+				limits.songs,
+				collapse_duplicates,
+			),
+			false => {
+				let mut seen = HashSet::new();
+				let mut songs = self
+					.rank_songs(
+						collection,
+						dictionary,
+						&parsed_query,
+						SearchOrder::Relevance,
+					)
+					.into_iter()
+					.map(|(song_key, score)| match collapse_duplicates {
+						true => (collection.get_preferred_song_key(song_key), score),
+						false => (song_key, score),
+					})
+					.filter(|(song_key, _)| seen.insert(*song_key))
+					.collect::<Vec<_>>();
+				songs.truncate(parsed_query.limit.unwrap_or(usize::MAX));
+				let total_songs = songs.len();
+				songs.truncate(limits.songs);
+				(songs, total_songs)
+			}
+		};
 		let songs = songs
 			.into_iter()
-			.filter_map(|song_key| collection.get_song(dictionary, song_key))
+			.filter_map(|(song_key, score)| {
+				let song = collection.get_song(dictionary, song_key)?;
+				Some(SearchResult { song, score })
+			})
+			.collect();
+
+		let scorer = relevance::Scorer::new(dictionary, &parsed_query.filter);
+		let terms = scorer.terms();
+
+		let artist_fields = [
+			TextField::Artist,
+			TextField::AlbumArtist,
+			TextField::Composer,
+			TextField::Lyricist,
+		];
+		let artists = self
+			.find_values(dictionary, &artist_fields, terms)
+			.into_iter()
+			.filter_map(|v| collection.get_artist_header(dictionary, ArtistKey(v)))
+			.take(limits.artists)
+			.collect();
+
+		let albums = self
+			.find_values(dictionary, &[TextField::Album], terms)
+			.into_iter()
+			.filter_map(|v| self.text_fields[TextField::Album].exact.get(&v))
+			.flat_map(|songs| collection.get_song_albums(dictionary, songs))
+			.take(limits.albums)
+			.collect();
+
+		let genres = self
+			.find_values(dictionary, &[TextField::Genre], terms)
+			.into_iter()
+			.filter_map(|v| collection.get_genre_header(dictionary, GenreKey(v)))
+			.take(limits.genres)
+			.collect();
+
+		Ok(SearchGroups {
+			artists,
+			albums,
+			genres,
+			songs,
+			total_songs,
+		})
+	}
+
+	// Most relevant songs matching a query without sorting every match, alongside the number
+	// of matches
+	fn find_top_songs(
+		&self,
+		collection: &collection::Collection,
+		dictionary: &Dictionary,
+		parsed_query: &Query,
+		count: usize,
+		collapse_duplicates: bool,
+	) -> (Vec<(SongKey, u32)>, usize) {
+		let scorer = relevance::Scorer::new(dictionary, &parsed_query.filter);
+		let mut scores = IntMap::<SongKey, u32>::default();
+		for song_key in self.eval(collection, dictionary, &parsed_query.filter) {
+			let Some(song) = collection.get_stored_song(song_key) else {
+				continue;
+			};
+			let score = scorer.score(dictionary, song);
+			// Duplicates are represented by their preferred copy, ranked as their best match
+			let song_key = match collapse_duplicates {
+				true => collection.get_preferred_song_key(song_key),
+				false => song_key,
+			};
+			let best = scores.entry(song_key).or_default();
+			*best = score.max(*best);
+		}
+
+		let compare = |(a, a_score): &(SongKey, u32), (b, b_score): &(SongKey, u32)| {
+			b_score
+				.cmp(a_score)
+				.then_with(|| collection.compare_songs(*a, *b, dictionary))
+		};
+		let mut songs = scores.into_iter().collect::<Vec<_>>();
+		let total = songs.len().min(parsed_query.limit.unwrap_or(usize::MAX));
+		let count = count.min(total);
+		if count < songs.len() {
+			songs.select_nth_unstable_by(count, compare);
+			songs.truncate(count);
+		}
+		songs.sort_by(compare);
+		(songs, total)
+	}
+
+	// Distinct field values matching the text terms of a query, from most to least relevant
+	fn find_values(
+		&self,
+		dictionary: &Dictionary,
+		fields: &[TextField],
+		terms: &[relevance::Term],
+	) -> Vec<Spur> {
+		let field_terms = fields
+			.iter()
+			.flat_map(|field| {
+				terms
+					.iter()
+					.filter(|t| t.field.is_none_or(|f| f == *field))
+					.map(move |t| (*field, t.value.as_str()))
+			})
 			.collect::<Vec<_>>();
 
-		Ok(songs)
+		let mut values = HashSet::new();
+		for (field, term) in &field_terms {
+			values.extend(self.text_fields[*field].find_like_values(dictionary, term));
+		}
+		if values.len() < MIN_FUZZY_RESULTS {
+			for (field, term) in &field_terms {
+				values.extend(self.text_fields[*field].find_similar_values(dictionary, term));
+			}
+		}
+
+		// Values matching more of the terms rank higher
+		let mut values = values
+			.into_iter()
+			.map(|v| {
+				let resolved = dictionary.resolve(&v);
+				let quality = terms
+					.iter()
+					.map(|t| relevance::match_quality(dictionary, &t.value, resolved))
+					.sum::<u32>();
+				(quality, v)
+			})
+			.collect::<Vec<_>>();
+		values.sort_by(|(quality_a, a), (quality_b, b)| {
+			quality_b.cmp(quality_a).then_with(|| dictionary.cmp(a, b))
+		});
+		values.into_iter().map(|(_, v)| v).collect()
 	}
 
-	fn eval(&self, dictionary: &Dictionary, expr: &Expr) -> IntSet<SongKey> {
+	fn eval(
+		&self,
+		collection: &collection::Collection,
+		dictionary: &Dictionary,
+		expr: &Expr,
+	) -> IntSet<SongKey> {
 		match expr {
 			Expr::Fuzzy(s) => self.eval_fuzzy(dictionary, s),
-			Expr::TextCmp(field, op, s) => self.eval_text_operator(dictionary, *field, *op, s),
+			Expr::TextCmp(field, op, s) => {
+				self.eval_text_operator(collection, dictionary, *field, *op, s)
+			}
 			Expr::NumberCmp(field, op, n) => self.eval_number_operator(*field, *op, *n),
-			Expr::Combined(e, op, f) => self.combine(dictionary, e, *op, f),
+			Expr::RelativeCmp(field, op, n) => {
+				let now = SystemTime::now()
+					.duration_since(UNIX_EPOCH)
+					.map(|d| d.as_secs() as i64)
+					.unwrap_or_default();
+				self.eval_number_operator(*field, *op, now.saturating_sub(*n))
+			}
+			Expr::Not(e) => {
+				let excluded = self.eval(collection, dictionary, e);
+				collection
+					.get_song_keys()
+					.filter(|s| !excluded.contains(s))
+					.collect()
+			}
+			Expr::Combined(e, op, f) => self.combine(collection, dictionary, e, *op, f),
 		}
 	}
 
 	fn combine(
 		&self,
+		collection: &collection::Collection,
 		dictionary: &Dictionary,
 		e: &Expr,
 		op: BoolOp,
 		f: &Expr,
 	) -> IntSet<SongKey> {
-		let is_operable = |expr: &Expr| match expr {
-			Expr::Fuzzy(Literal::Text(s)) if s.chars().count() < BIGRAM_SIZE => false,
-			Expr::Fuzzy(Literal::Number(n)) if *n < 10 => false,
-			Expr::TextCmp(_, _, s) if s.chars().count() < BIGRAM_SIZE => false,
-			_ => true,
-		};
+		fn is_operable(expr: &Expr) -> bool {
+			match expr {
+				Expr::Fuzzy(Literal::Text(s)) if s.chars().count() < BIGRAM_SIZE => false,
+				Expr::Fuzzy(Literal::Number(n)) if *n < 10 => false,
+				Expr::TextCmp(_, TextOp::Regex, _) => true,
+				Expr::TextCmp(_, _, s) if s.chars().count() < BIGRAM_SIZE => false,
+				Expr::Not(e) => is_operable(e),
+				_ => true,
+			}
+		}
 
-		let left = is_operable(e).then(|| self.eval(dictionary, e));
-		let right = is_operable(f).then(|| self.eval(dictionary, f));
+		let left = is_operable(e).then(|| self.eval(collection, dictionary, e));
+
+		// Excluding songs is cheaper than listing every song which does not match
+		if let (Some(l), BoolOp::And, Expr::Not(f)) = (&left, op, f) {
+			if is_operable(f) {
+				let excluded = self.eval(collection, dictionary, f);
+				return l.difference(&excluded).cloned().collect();
+			}
+		}
+
+		let right = is_operable(f).then(|| self.eval(collection, dictionary, f));
 
 		match (left, op, right) {
 			(Some(l), BoolOp::And, Some(r)) => l.intersection(&r).cloned().collect(),
@@ -101,12 +398,17 @@ impl Search {
 				for field in self.text_fields.values() {
 					songs.extend(field.find_like(dictionary, s));
 				}
+				if songs.len() < MIN_FUZZY_RESULTS {
+					for field in self.text_fields.values() {
+						songs.extend(field.find_similar(dictionary, s));
+					}
+				}
 				songs
 			}
 			Literal::Number(n) => {
 				let mut songs = IntSet::default();
-				for field in self.number_fields.values() {
-					songs.extend(field.find(*n as i64, NumberOp::Eq));
+				for field in FUZZY_NUMBER_FIELDS {
+					songs.extend(self.number_fields[field].find(*n as i64, NumberOp::Eq));
 				}
 				songs
 					.union(&self.eval_fuzzy(dictionary, &Literal::Text(n.to_string())))
@@ -118,6 +420,7 @@ impl Search {
 
 	fn eval_text_operator(
 		&self,
+		collection: &collection::Collection,
 		dictionary: &Dictionary,
 		field: TextField,
 		operator: TextOp,
@@ -126,6 +429,22 @@ impl Search {
 		match operator {
 			TextOp::Eq => self.text_fields[field].find_exact(dictionary, value),
 			TextOp::Like => self.text_fields[field].find_like(dictionary, value),
+			TextOp::Regex => match make_regex(value) {
+				Ok(regex) => self.text_fields[field].find_regex(dictionary, &regex),
+				Err(_) => IntSet::default(),
+			},
+			TextOp::Within => {
+				let mut songs = self.text_fields[field].find_exact(dictionary, value);
+				let Some(genre) = dictionary.get_canon(value) else {
+					return songs;
+				};
+				for subgenre in collection.get_genre_descendants(GenreKey(genre)) {
+					if let Some(s) = self.text_fields[field].exact.get(&subgenre.0) {
+						songs.extend(s);
+					}
+				}
+				songs
+			}
 		}
 	}
 
@@ -133,20 +452,31 @@ impl Search {
 		&self,
 		field: NumberField,
 		operator: NumberOp,
-		value: i32,
+		value: i64,
 	) -> IntSet<SongKey> {
-		self.number_fields[field].find(value as i64, operator)
+		self.number_fields[field].find(value, operator)
 	}
 }
 
 const BIGRAM_SIZE: usize = 2;
+// Fuzzy queries with fewer results than this also look for values with typos
+const MIN_FUZZY_RESULTS: usize = 10;
 const ASCII_RANGE: usize = u8::MAX as usize;
+// Durations, bitrates and timestamps are only searched when a query names them
+const FUZZY_NUMBER_FIELDS: [NumberField; 3] = [
+	NumberField::DiscNumber,
+	NumberField::TrackNumber,
+	NumberField::Year,
+];
 
 #[derive(Clone, Deserialize, Serialize)]
 struct TextFieldIndex {
 	exact: HashMap<Spur, IntSet<SongKey>>,
 	ascii_bigrams: Vec<Vec<(SongKey, Spur)>>,
 	other_bigrams: HashMap<[char; BIGRAM_SIZE], Vec<(SongKey, Spur)>>,
+	// Distinct values containing each bigram, for lookups tolerating typos
+	value_bigrams: HashMap<[char; BIGRAM_SIZE], Vec<Spur>>,
+	sanitized_values: HashMap<Spur, String>,
 }
 
 impl Default for TextFieldIndex {
@@ -155,6 +485,8 @@ impl Default for TextFieldIndex {
 			exact: Default::default(),
 			ascii_bigrams: vec![Default::default(); ASCII_RANGE * ASCII_RANGE],
 			other_bigrams: Default::default(),
+			value_bigrams: Default::default(),
+			sanitized_values: Default::default(),
 		}
 	}
 }
@@ -166,8 +498,15 @@ impl TextFieldIndex {
 		(a as usize) * ASCII_RANGE + (b as usize)
 	}
 
-	pub fn insert(&mut self, raw_value: &str, value: Spur, song: SongKey) {
-		let characters = sanitize(raw_value).chars().collect::<TinyVec<[char; 32]>>();
+	pub fn insert(
+		&mut self,
+		dictionary: &dictionary::Builder,
+		raw_value: &str,
+		value: Spur,
+		song: SongKey,
+	) {
+		let sanitized = dictionary.sanitize(raw_value);
+		let characters = sanitized.chars().collect::<TinyVec<[char; 32]>>();
 		for substring in characters[..].windows(BIGRAM_SIZE) {
 			if substring.iter().all(|c| c.is_ascii()) {
 				let index = Self::ascii_bigram_to_index(substring[0], substring[1]);
@@ -180,43 +519,133 @@ impl TextFieldIndex {
 			}
 		}
 
+		if let Entry::Vacant(entry) = self.sanitized_values.entry(value) {
+			let mut bigrams = characters[..].windows(BIGRAM_SIZE).collect::<Vec<_>>();
+			bigrams.sort();
+			bigrams.dedup();
+			for bigram in bigrams {
+				self.value_bigrams
+					.entry(bigram.try_into().unwrap())
+					.or_default()
+					.push(value);
+			}
+			entry.insert(sanitized);
+		}
+
 		self.exact.entry(value).or_default().insert(song);
 	}
 
+	pub fn find_regex(&self, dictionary: &Dictionary, regex: &Regex) -> IntSet<SongKey> {
+		let mut songs = IntSet::default();
+		for (value, value_songs) in &self.exact {
+			if regex.is_match(dictionary.resolve(value)) {
+				songs.extend(value_songs);
+			}
+		}
+		songs
+	}
+
 	pub fn find_like(&self, dictionary: &Dictionary, value: &str) -> IntSet<SongKey> {
-		let sanitized = sanitize(value);
+		self.find_like_pairs(dictionary, value)
+			.map(|(k, _v)| k)
+			.collect()
+	}
+
+	pub fn find_like_values(&self, dictionary: &Dictionary, value: &str) -> HashSet<Spur> {
+		self.find_like_pairs(dictionary, value)
+			.map(|(_k, v)| v)
+			.collect()
+	}
+
+	fn find_like_pairs<'a>(
+		&'a self,
+		dictionary: &'a Dictionary,
+		value: &str,
+	) -> impl Iterator<Item = (SongKey, Spur)> + 'a {
+		let sanitized = dictionary.sanitize(value);
 		let characters = sanitized.chars().collect::<Vec<_>>();
-		let empty = Vec::new();
 
 		let candidates_by_bigram = characters[..]
 			.windows(BIGRAM_SIZE)
-			.map(|s| {
-				if s.iter().all(|c| c.is_ascii()) {
-					let index = Self::ascii_bigram_to_index(s[0], s[1]);
-					&self.ascii_bigrams[index]
-				} else {
-					self.other_bigrams
-						.get::<[char; BIGRAM_SIZE]>(s.try_into().unwrap())
-						.unwrap_or(&empty)
-				}
-			})
+			.map(|s| self.get_bigram_candidates(s))
 			.collect::<Vec<_>>();
 
 		candidates_by_bigram
 			.into_iter()
 			.min_by_key(|h| h.len()) // Only check songs that contain the least common bigram from the search term
-			.unwrap_or(&empty)
+			.unwrap_or(&[])
 			.iter()
-			.filter(|(_song_key, indexed_value)| {
+			.filter(move |(_song_key, indexed_value)| {
 				// Only keep songs that actually contain the search term in full
 				let resolved = dictionary.resolve(indexed_value);
-				sanitize(resolved).contains(&sanitized)
+				dictionary.sanitize(resolved).contains(&sanitized)
 			})
-			.map(|(k, _v)| k)
 			.copied()
+	}
+
+	// Finds songs containing the search term, give or take a few typos
+	pub fn find_similar(&self, dictionary: &Dictionary, value: &str) -> IntSet<SongKey> {
+		let mut songs = IntSet::default();
+		for value in self.find_similar_values(dictionary, value) {
+			if let Some(s) = self.exact.get(&value) {
+				songs.extend(s);
+			}
+		}
+		songs
+	}
+
+	pub fn find_similar_values(&self, dictionary: &Dictionary, value: &str) -> Vec<Spur> {
+		let sanitized = dictionary.sanitize(value);
+		let characters = sanitized.chars().collect::<Vec<_>>();
+		let max_typos = max_typos(characters.len());
+		if max_typos == 0 {
+			return Vec::new();
+		}
+
+		// Values within `max_typos` edits of the search term share a minimum number of its bigrams
+		let mut bigrams = characters[..].windows(BIGRAM_SIZE).collect::<Vec<_>>();
+		bigrams.sort();
+		bigrams.dedup();
+		let min_shared_bigrams = bigrams.len().saturating_sub(max_typos * BIGRAM_SIZE).max(1);
+
+		let mut shared_bigrams = HashMap::<Spur, usize>::new();
+		for bigram in bigrams {
+			let values = self
+				.value_bigrams
+				.get::<[char; BIGRAM_SIZE]>(bigram.try_into().unwrap())
+				.map(|v| &v[..])
+				.unwrap_or(&[]);
+			for value in values {
+				*shared_bigrams.entry(*value).or_default() += 1;
+			}
+		}
+
+		shared_bigrams
+			.into_iter()
+			.filter(|(_value, num_shared_bigrams)| *num_shared_bigrams >= min_shared_bigrams)
+			.map(|(value, _)| value)
+			.filter(|value| {
+				let Some(indexed) = self.sanitized_values.get(value) else {
+					return false;
+				};
+				let indexed = indexed.chars().collect::<Vec<_>>();
+				substring_edit_distance(&characters, &indexed) <= max_typos
+			})
 			.collect()
 	}
 
+	fn get_bigram_candidates(&self, bigram: &[char]) -> &[(SongKey, Spur)] {
+		if bigram.iter().all(|c| c.is_ascii()) {
+			let index = Self::ascii_bigram_to_index(bigram[0], bigram[1]);
+			&self.ascii_bigrams[index]
+		} else {
+			self.other_bigrams
+				.get::<[char; BIGRAM_SIZE]>(bigram.try_into().unwrap())
+				.map(|v| &v[..])
+				.unwrap_or(&[])
+		}
+	}
+
 	pub fn find_exact(&self, dictionary: &Dictionary, value: &str) -> IntSet<SongKey> {
 		dictionary
 			.get_canon(value)
@@ -226,6 +655,30 @@ impl TextFieldIndex {
 	}
 }
 
+fn max_typos(term_length: usize) -> usize {
+	match term_length {
+		0..=3 => 0,
+		4..=7 => 1,
+		_ => 2,
+	}
+}
+
+// Smallest number of edits turning `pattern` into any substring of `text`
+fn substring_edit_distance(pattern: &[char], text: &[char]) -> usize {
+	// Matches can start anywhere in `text` at no cost
+	let mut previous = vec![0; text.len() + 1];
+	for (i, p) in pattern.iter().enumerate() {
+		let mut current = vec![i + 1; text.len() + 1];
+		for (j, t) in text.iter().enumerate() {
+			let substitution = previous[j] + usize::from(p != t);
+			current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+		}
+		previous = current;
+	}
+	// Matches can end anywhere in `text` at no cost
+	previous.into_iter().min().unwrap_or(pattern.len())
+}
+
 #[derive(Clone, Default, Deserialize, Serialize)]
 struct NumberFieldIndex {
 	values: BTreeMap<i64, IntSet<SongKey>>,
@@ -260,13 +713,18 @@ pub struct Builder {
 }
 
 impl Builder {
-	pub fn add_song(&mut self, scanner_song: &scanner::Song, storage_song: &storage::Song) {
+	pub fn add_song(
+		&mut self,
+		dictionary: &dictionary::Builder,
+		scanner_song: &scanner::Song,
+		storage_song: &storage::Song,
+	) {
 		let song_key = SongKey {
 			virtual_path: storage_song.virtual_path,
 		};
 
 		if let (Some(str), Some(spur)) = (&scanner_song.album, storage_song.album) {
-			self.text_fields[TextField::Album].insert(str, spur, song_key);
+			self.text_fields[TextField::Album].insert(dictionary, str, spur, song_key);
 		}
 
 		for (str, artist_key) in scanner_song
@@ -274,11 +732,16 @@ impl Builder {
 			.iter()
 			.zip(storage_song.album_artists.iter())
 		{
-			self.text_fields[TextField::AlbumArtist].insert(str, artist_key.0, song_key);
+			self.text_fields[TextField::AlbumArtist].insert(
+				dictionary,
+				str,
+				artist_key.0,
+				song_key,
+			);
 		}
 
 		for (str, artist_key) in scanner_song.artists.iter().zip(storage_song.artists.iter()) {
-			self.text_fields[TextField::Artist].insert(str, artist_key.0, song_key);
+			self.text_fields[TextField::Artist].insert(dictionary, str, artist_key.0, song_key);
 		}
 
 		for (str, artist_key) in scanner_song
@@ -286,7 +749,7 @@ impl Builder {
 			.iter()
 			.zip(storage_song.composers.iter())
 		{
-			self.text_fields[TextField::Composer].insert(str, artist_key.0, song_key);
+			self.text_fields[TextField::Composer].insert(dictionary, str, artist_key.0, song_key);
 		}
 
 		if let Some(disc_number) = &scanner_song.disc_number {
@@ -294,11 +757,11 @@ impl Builder {
 		}
 
 		for (str, spur) in scanner_song.genres.iter().zip(storage_song.genres.iter()) {
-			self.text_fields[TextField::Genre].insert(str, *spur, song_key);
+			self.text_fields[TextField::Genre].insert(dictionary, str, *spur, song_key);
 		}
 
 		for (str, spur) in scanner_song.labels.iter().zip(storage_song.labels.iter()) {
-			self.text_fields[TextField::Label].insert(str, *spur, song_key);
+			self.text_fields[TextField::Label].insert(dictionary, str, *spur, song_key);
 		}
 
 		for (str, artist_key) in scanner_song
@@ -306,17 +769,18 @@ impl Builder {
 			.iter()
 			.zip(storage_song.lyricists.iter())
 		{
-			self.text_fields[TextField::Lyricist].insert(str, artist_key.0, song_key);
+			self.text_fields[TextField::Lyricist].insert(dictionary, str, artist_key.0, song_key);
 		}
 
 		self.text_fields[TextField::Path].insert(
+			dictionary,
 			scanner_song.virtual_path.to_string_lossy().as_ref(),
 			storage_song.virtual_path.0,
 			song_key,
 		);
 
 		if let (Some(str), Some(spur)) = (&scanner_song.title, storage_song.title) {
-			self.text_fields[TextField::Title].insert(str, spur, song_key);
+			self.text_fields[TextField::Title].insert(dictionary, str, spur, song_key);
 		}
 
 		if let Some(track_number) = &scanner_song.track_number {
@@ -326,6 +790,16 @@ impl Builder {
 		if let Some(year) = &scanner_song.year {
 			self.number_fields[NumberField::Year].insert(*year, song_key);
 		}
+
+		if let Some(duration) = &scanner_song.duration {
+			self.number_fields[NumberField::Duration].insert(*duration, song_key);
+		}
+
+		if let Some(bitrate) = storage_song.bitrate() {
+			self.number_fields[NumberField::Bitrate].insert(bitrate, song_key);
+		}
+
+		self.number_fields[NumberField::DateAdded].insert(scanner_song.date_added, song_key);
 	}
 
 	pub fn build(self) -> Search {
@@ -353,29 +827,42 @@ mod test {
 
 	impl Context {
 		pub fn search(&self, query: &str) -> Vec<PathBuf> {
+			self.search_with_order(query, SearchOrder::Alphabetical)
+		}
+
+		pub fn search_with_order(&self, query: &str, order: SearchOrder) -> Vec<PathBuf> {
 			self.search
-				.find_songs(&self.collection, &self.dictionary, query)
+				.find_songs(&self.collection, &self.dictionary, query, order)
 				.unwrap()
 				.into_iter()
-				.map(|s| s.virtual_path)
+				.map(|r| r.song.virtual_path)
 				.collect()
 		}
 	}
 
 	fn setup_test(songs: Vec<scanner::Song>) -> Context {
+		setup_test_with_genre_parents(songs, vec![])
+	}
+
+	fn setup_test_with_genre_parents(
+		songs: Vec<scanner::Song>,
+		genre_parents: Vec<(String, String)>,
+	) -> Context {
 		let mut dictionary_builder = dictionary::Builder::default();
 		let mut collection_builder = collection::Builder::default();
+		collection_builder.set_genre_parents(genre_parents);
 		let mut search_builder = Builder::default();
 		for song in songs {
 			let storage_song = store_song(&mut dictionary_builder, &song).unwrap();
 			collection_builder.add_song(&storage_song);
-			search_builder.add_song(&song, &storage_song);
+			search_builder.add_song(&dictionary_builder, &song, &storage_song);
 		}
 
+		let dictionary = dictionary_builder.build();
 		Context {
-			collection: collection_builder.build(),
+			collection: collection_builder.build(&dictionary),
 			search: search_builder.build(),
-			dictionary: dictionary_builder.build(),
+			dictionary,
 		}
 	}
 
@@ -447,6 +934,23 @@ mod test {
 		assert!(songs.contains(&PathBuf::from("seasons.mp3")));
 	}
 
+	#[test]
+	fn text_is_diacritic_insensitive() {
+		let ctx = setup_test(vec![scanner::Song {
+			virtual_path: PathBuf::from("ace of spades.mp3"),
+			artists: vec!["Motörhead".to_owned()],
+			..Default::default()
+		}]);
+
+		let songs = ctx.search("motorhead");
+		assert_eq!(songs.len(), 1);
+		assert!(songs.contains(&PathBuf::from("ace of spades.mp3")));
+
+		let songs = ctx.search("artist = ＭＯＴＯＲＨＥＡＤ");
+		assert_eq!(songs.len(), 1);
+		assert!(songs.contains(&PathBuf::from("ace of spades.mp3")));
+	}
+
 	#[test]
 	fn can_find_field_exact() {
 		let ctx = setup_test(vec![
@@ -611,6 +1115,182 @@ mod test {
 		assert!(songs.contains(&PathBuf::from("whale.mp3")));
 	}
 
+	#[test]
+	fn can_use_unary_not() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("whale.mp3"),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("whale (live).mp3"),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("space (live).mp3"),
+				..Default::default()
+			},
+		]);
+
+		let songs = ctx.search("whale NOT live");
+		assert_eq!(songs, vec![PathBuf::from("whale.mp3")]);
+
+		let songs = ctx.search("-whale");
+		assert_eq!(songs, vec![PathBuf::from("space (live).mp3")]);
+	}
+
+	#[test]
+	fn can_query_duration_bitrate_and_date_added() {
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_secs() as i64;
+		let day = 24 * 60 * 60;
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("short.mp3"),
+				duration: Some(90),
+				size: 90 * 128 * 1000 / 8,
+				date_added: now - 30 * day,
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("long.mp3"),
+				duration: Some(600),
+				size: 600 * 320 * 1000 / 8,
+				date_added: now - day,
+				..Default::default()
+			},
+		]);
+
+		assert_eq!(ctx.search("duration > 5m"), vec![PathBuf::from("long.mp3")]);
+		assert_eq!(
+			ctx.search("duration <= 90"),
+			vec![PathBuf::from("short.mp3")]
+		);
+		assert_eq!(
+			ctx.search("bitrate >= 320"),
+			vec![PathBuf::from("long.mp3")]
+		);
+		assert_eq!(
+			ctx.search("dateadded > 7d"),
+			vec![PathBuf::from("long.mp3")]
+		);
+		assert_eq!(
+			ctx.search("dateadded < 1w"),
+			vec![PathBuf::from("short.mp3")]
+		);
+	}
+
+	#[test]
+	fn can_use_regex_and_lists() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("a.mp3"),
+				title: Some("The Trooper".to_owned()),
+				year: Some(1983),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("b.mp3"),
+				title: Some("Aces High".to_owned()),
+				year: Some(1984),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("c.mp3"),
+				title: Some("Wasted Years".to_owned()),
+				year: Some(1986),
+				..Default::default()
+			},
+		]);
+
+		assert_eq!(
+			ctx.search(r#"title ~ "^the""#),
+			vec![PathBuf::from("a.mp3")]
+		);
+		assert_eq!(ctx.search(r#"title ~ "s$""#), vec![PathBuf::from("c.mp3")]);
+
+		let songs = ctx.search(r#"title IN ("aces high", "wasted years")"#);
+		assert_eq!(songs.len(), 2);
+		assert!(songs.contains(&PathBuf::from("b.mp3")));
+		assert!(songs.contains(&PathBuf::from("c.mp3")));
+
+		let songs = ctx.search("year IN (1983, 1986)");
+		assert_eq!(songs.len(), 2);
+		assert!(songs.contains(&PathBuf::from("a.mp3")));
+		assert!(songs.contains(&PathBuf::from("c.mp3")));
+	}
+
+	#[test]
+	fn can_order_and_limit_results() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("a.mp3"),
+				title: Some("Metal Heart".to_owned()),
+				year: Some(1985),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("b.mp3"),
+				title: Some("Metal Church".to_owned()),
+				year: Some(1984),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("c.mp3"),
+				title: Some("Heavy Metal".to_owned()),
+				year: Some(1984),
+				..Default::default()
+			},
+		]);
+
+		assert_eq!(
+			ctx.search("metal ORDER BY year DESC, title"),
+			vec![
+				PathBuf::from("a.mp3"),
+				PathBuf::from("c.mp3"),
+				PathBuf::from("b.mp3")
+			]
+		);
+		assert_eq!(
+			ctx.search_with_order("metal ORDER BY title LIMIT 2", SearchOrder::Relevance),
+			vec![PathBuf::from("c.mp3"), PathBuf::from("b.mp3")]
+		);
+	}
+
+	#[test]
+	fn can_find_subgenres() {
+		let ctx = setup_test_with_genre_parents(
+			vec![
+				scanner::Song {
+					virtual_path: PathBuf::from("power.mp3"),
+					genres: vec!["Power Metal".to_owned()],
+					..Default::default()
+				},
+				scanner::Song {
+					virtual_path: PathBuf::from("metal.mp3"),
+					genres: vec!["Metal".to_owned()],
+					..Default::default()
+				},
+				scanner::Song {
+					virtual_path: PathBuf::from("folk.mp3"),
+					genres: vec!["Folk".to_owned()],
+					..Default::default()
+				},
+			],
+			vec![("Power Metal".to_owned(), "Metal".to_owned())],
+		);
+
+		let songs = ctx.search("genre = metal");
+		assert_eq!(songs, vec![PathBuf::from("metal.mp3")]);
+
+		let songs = ctx.search("genre <= metal");
+		assert_eq!(songs.len(), 2);
+		assert!(songs.contains(&PathBuf::from("power.mp3")));
+		assert!(songs.contains(&PathBuf::from("metal.mp3")));
+	}
+
 	#[test]
 	fn results_are_sorted() {
 		let ctx = setup_test(vec![
@@ -675,6 +1355,265 @@ mod test {
 		);
 	}
 
+	#[test]
+	fn can_sort_by_relevance() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("a.mp3"),
+				track_number: Some(1),
+				title: Some("Sometimes".to_owned()),
+				artists: vec!["Various".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("b.mp3"),
+				track_number: Some(2),
+				title: Some("Time".to_owned()),
+				artists: vec!["Various".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("c.mp3"),
+				track_number: Some(3),
+				title: Some("Timeless".to_owned()),
+				artists: vec!["Various".to_owned()],
+				..Default::default()
+			},
+		]);
+
+		let songs = ctx.search("time");
+		assert_eq!(
+			songs,
+			vec![
+				PathBuf::from("a.mp3"),
+				PathBuf::from("b.mp3"),
+				PathBuf::from("c.mp3"),
+			]
+		);
+
+		let songs = ctx.search_with_order("time", SearchOrder::Relevance);
+		assert_eq!(
+			songs,
+			vec![
+				PathBuf::from("b.mp3"),
+				PathBuf::from("c.mp3"),
+				PathBuf::from("a.mp3"),
+			]
+		);
+
+		// Relevance is not computed when it does not affect ordering
+		let results = ctx
+			.search
+			.find_songs(
+				&ctx.collection,
+				&ctx.dictionary,
+				"time",
+				SearchOrder::Alphabetical,
+			)
+			.unwrap();
+		assert!(results.iter().all(|r| r.score == 0));
+	}
+
+	#[test]
+	fn can_find_with_typos() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("seek and destroy.mp3"),
+				artists: vec!["Metallica".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("moonlight.mp3"),
+				composers: vec!["Ludwig van Beethoven".to_owned()],
+				..Default::default()
+			},
+		]);
+
+		let songs = ctx.search("metalica");
+		assert_eq!(songs, vec![PathBuf::from("seek and destroy.mp3")]);
+
+		let songs = ctx.search("beethovn");
+		assert_eq!(songs, vec![PathBuf::from("moonlight.mp3")]);
+
+		let songs = ctx.search("megadeth");
+		assert!(songs.is_empty());
+
+		let songs = ctx.search("artist % metalica");
+		assert!(songs.is_empty());
+	}
+
+	#[test]
+	fn typo_tolerance_is_bounded() {
+		assert_eq!(
+			substring_edit_distance(&['a', 'b', 'c'], &['x', 'a', 'b', 'c', 'x']),
+			0
+		);
+		assert_eq!(
+			substring_edit_distance(&['a', 'x', 'c'], &['a', 'b', 'c']),
+			1
+		);
+		assert_eq!(substring_edit_distance(&['a', 'c'], &['a', 'b', 'c']), 1);
+		assert_eq!(substring_edit_distance(&['a', 'b', 'c'], &[]), 3);
+		assert_eq!(max_typos(3), 0);
+		assert_eq!(max_typos(5), 1);
+		assert_eq!(max_typos(8), 2);
+	}
+
+	#[test]
+	fn can_find_all() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("time.mp3"),
+				title: Some("Time".to_owned()),
+				artists: vec!["Pink Floyd".to_owned()],
+				album: Some("The Dark Side of the Moon".to_owned()),
+				genres: vec!["Progressive Rock".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("in the flesh.mp3"),
+				title: Some("In the Flesh?".to_owned()),
+				artists: vec!["Pink Floyd".to_owned()],
+				album: Some("The Wall".to_owned()),
+				genres: vec!["Rock".to_owned()],
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("pink.mp3"),
+				title: Some("Pink".to_owned()),
+				artists: vec!["Aerosmith".to_owned()],
+				album: Some("Nine Lives".to_owned()),
+				genres: vec!["Rock".to_owned()],
+				..Default::default()
+			},
+		]);
+
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"floyd",
+				&SearchLimits::default(),
+				false,
+			)
+			.unwrap();
+		let artists = groups
+			.artists
+			.iter()
+			.map(|a| a.name.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(artists, vec!["Pink Floyd"]);
+		assert!(groups.albums.is_empty());
+		assert!(groups.genres.is_empty());
+		assert_eq!(groups.songs.len(), 2);
+
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"rock",
+				&SearchLimits {
+					genres: 1,
+					..Default::default()
+				},
+				false,
+			)
+			.unwrap();
+		let genres = groups.genres.iter().map(|g| &g.name).collect::<Vec<_>>();
+		assert_eq!(genres, vec!["Rock"]);
+
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"wall",
+				&SearchLimits::default(),
+				false,
+			)
+			.unwrap();
+		let albums = groups.albums.iter().map(|a| &a.name).collect::<Vec<_>>();
+		assert_eq!(albums, vec!["The Wall"]);
+
+		// Only the text terms of a query are matched against names
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"floyd ORDER BY title LIMIT 5",
+				&SearchLimits::default(),
+				false,
+			)
+			.unwrap();
+		let artists = groups
+			.artists
+			.iter()
+			.map(|a| a.name.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(artists, vec!["Pink Floyd"]);
+
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"title % pink",
+				&SearchLimits::default(),
+				false,
+			)
+			.unwrap();
+		assert!(groups.artists.is_empty());
+		assert_eq!(groups.songs.len(), 1);
+	}
+
+	#[test]
+	fn find_all_counts_songs_beyond_limit() {
+		let ctx = setup_test(vec![
+			scanner::Song {
+				virtual_path: PathBuf::from("time.mp3"),
+				title: Some("Time".to_owned()),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("timeless.mp3"),
+				title: Some("Timeless".to_owned()),
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("sometimes.mp3"),
+				title: Some("Sometimes".to_owned()),
+				..Default::default()
+			},
+		]);
+
+		let groups = ctx
+			.search
+			.find_all(
+				&ctx.collection,
+				&ctx.dictionary,
+				"time",
+				&SearchLimits {
+					songs: 2,
+					..Default::default()
+				},
+				false,
+			)
+			.unwrap();
+		assert_eq!(groups.total_songs, 3);
+		let songs = groups
+			.songs
+			.iter()
+			.map(|r| r.song.virtual_path.clone())
+			.collect::<Vec<_>>();
+		assert_eq!(
+			songs,
+			vec![PathBuf::from("time.mp3"), PathBuf::from("timeless.mp3")]
+		);
+	}
+
 	#[test]
 	fn avoids_bigram_false_positives() {
 		let ctx = setup_test(vec![scanner::Song {
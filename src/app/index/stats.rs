@@ -0,0 +1,164 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::index::{dictionary::Dictionary, storage};
+
+const DAY: i64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+	pub num_songs: u32,
+	pub num_albums: u32,
+	pub num_artists: u32,
+	pub num_genres: u32,
+	pub duration: i64,
+	pub size: u64,
+	pub num_songs_by_format: HashMap<String, u32>,
+	pub num_songs_by_decade: HashMap<i64, u32>,
+	pub num_songs_by_genre: HashMap<String, u32>,
+	pub num_songs_missing_artwork: u32,
+	pub num_songs_missing_year: u32,
+	pub num_songs_missing_album: u32,
+	pub num_songs_added_last_7_days: u32,
+	pub num_songs_added_last_30_days: u32,
+	pub num_songs_added_last_365_days: u32,
+}
+
+pub struct Builder {
+	stats: Stats,
+	now: i64,
+}
+
+impl Builder {
+	pub fn new(now: i64) -> Self {
+		Self {
+			stats: Stats::default(),
+			now,
+		}
+	}
+
+	pub fn add_song(&mut self, song: &storage::Song, dictionary: &Dictionary) {
+		let stats = &mut self.stats;
+
+		stats.num_songs += 1;
+		stats.duration += song.duration.unwrap_or_default();
+		stats.size += song.size;
+
+		let format = Path::new(dictionary.resolve(&song.virtual_path.0))
+			.extension()
+			.map(|e| e.to_string_lossy().to_lowercase())
+			.unwrap_or_default();
+		*stats.num_songs_by_format.entry(format).or_default() += 1;
+
+		if let Some(decade) = song.decade() {
+			*stats.num_songs_by_decade.entry(decade).or_default() += 1;
+		}
+
+		for genre in &song.genres {
+			*stats
+				.num_songs_by_genre
+				.entry(dictionary.resolve(genre).to_owned())
+				.or_default() += 1;
+		}
+
+		if song.artwork.is_none() {
+			stats.num_songs_missing_artwork += 1;
+		}
+		if song.year.is_none() {
+			stats.num_songs_missing_year += 1;
+		}
+		if song.album.is_none() {
+			stats.num_songs_missing_album += 1;
+		}
+
+		let age = self.now - song.date_added;
+		if age <= 7 * DAY {
+			stats.num_songs_added_last_7_days += 1;
+		}
+		if age <= 30 * DAY {
+			stats.num_songs_added_last_30_days += 1;
+		}
+		if age <= 365 * DAY {
+			stats.num_songs_added_last_365_days += 1;
+		}
+	}
+
+	pub fn build(self, num_albums: usize, num_artists: usize, num_genres: usize) -> Stats {
+		Stats {
+			num_albums: num_albums as u32,
+			num_artists: num_artists as u32,
+			num_genres: num_genres as u32,
+			..self.stats
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use crate::app::{
+		index::{dictionary, storage::store_song},
+		scanner,
+	};
+
+	use super::*;
+
+	#[test]
+	fn can_compute_stats() {
+		let now = 1_000 * DAY;
+		let songs = [
+			scanner::Song {
+				virtual_path: PathBuf::from("Kai.mp3"),
+				album: Some("ISDN".to_owned()),
+				year: Some(1994),
+				duration: Some(300),
+				genres: vec!["Ambient".to_owned()],
+				size: 1000,
+				date_added: now - DAY,
+				..Default::default()
+			},
+			scanner::Song {
+				virtual_path: PathBuf::from("Fantasy.FLAC"),
+				duration: Some(200),
+				size: 3000,
+				date_added: now - 100 * DAY,
+				..Default::default()
+			},
+		];
+
+		let mut dictionary_builder = dictionary::Builder::default();
+		let songs = songs
+			.iter()
+			.filter_map(|s| store_song(&mut dictionary_builder, s))
+			.collect::<Vec<_>>();
+		let dictionary = dictionary_builder.build();
+
+		let mut builder = Builder::new(now);
+		for song in &songs {
+			builder.add_song(song, &dictionary);
+		}
+		let stats = builder.build(1, 0, 1);
+
+		assert_eq!(stats.num_songs, 2);
+		assert_eq!(stats.num_albums, 1);
+		assert_eq!(stats.duration, 500);
+		assert_eq!(stats.size, 4000);
+		assert_eq!(
+			stats.num_songs_by_format,
+			HashMap::from_iter([("mp3".to_owned(), 1), ("flac".to_owned(), 1)])
+		);
+		assert_eq!(stats.num_songs_by_decade, HashMap::from_iter([(1990, 1)]));
+		assert_eq!(
+			stats.num_songs_by_genre,
+			HashMap::from_iter([("Ambient".to_owned(), 1)])
+		);
+		assert_eq!(stats.num_songs_missing_artwork, 2);
+		assert_eq!(stats.num_songs_missing_year, 1);
+		assert_eq!(stats.num_songs_missing_album, 1);
+		assert_eq!(stats.num_songs_added_last_7_days, 1);
+		assert_eq!(stats.num_songs_added_last_30_days, 1);
+		assert_eq!(stats.num_songs_added_last_365_days, 2);
+	}
+}
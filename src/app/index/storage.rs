@@ -8,7 +8,7 @@ use log::error;
 use serde::{Deserialize, Serialize};
 use tinyvec::TinyVec;
 
-use crate::app::scanner;
+use crate::app::{formats::PictureType, scanner};
 
 use crate::app::index::dictionary::{self, Dictionary};
 
@@ -27,9 +27,24 @@ pub struct Genre {
 	pub songs: Vec<SongKey>,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Label {
+	pub name: Spur,
+	pub albums: HashSet<AlbumKey>,
+	pub artists: HashSet<ArtistKey>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Decade {
+	pub albums: HashSet<AlbumKey>,
+	pub artists: HashSet<ArtistKey>,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Artist {
 	pub name: Spur,
+	pub artwork: Option<PathKey>,
+	pub mosaic: Vec<PathKey>,
 	pub all_albums: HashSet<AlbumKey>,
 	pub albums_as_performer: HashSet<AlbumKey>,
 	pub albums_as_additional_performer: HashSet<AlbumKey>,
@@ -37,16 +52,23 @@ pub struct Artist {
 	pub albums_as_lyricist: HashSet<AlbumKey>,
 	pub num_songs_by_genre: HashMap<Spur, u32>,
 	pub num_songs: u32,
+	pub num_discs: u32,
+	pub duration: i64,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Album {
 	pub name: Spur,
 	pub artwork: Option<PathKey>,
+	pub images: Vec<Image>,
 	pub artists: TinyVec<[ArtistKey; 1]>,
 	pub year: Option<i64>,
 	pub date_added: i64,
 	pub songs: HashSet<SongKey>,
+	pub compilation: bool,
+	pub num_songs_by_genre: HashMap<Spur, u32>,
+	pub num_discs: u32,
+	pub duration: i64,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -61,14 +83,25 @@ pub struct Song {
 	pub year: Option<i64>,
 	pub album: Option<Spur>,
 	pub artwork: Option<PathKey>,
+	pub artist_artwork: Option<PathKey>,
+	pub images: Vec<Image>,
 	pub duration: Option<i64>,
 	pub lyricists: TinyVec<[ArtistKey; 0]>,
 	pub composers: TinyVec<[ArtistKey; 0]>,
 	pub genres: TinyVec<[Spur; 1]>,
 	pub labels: TinyVec<[Spur; 0]>,
+	pub compilation: bool,
+	pub size: u64,
 	pub date_added: i64,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Image {
+	pub path: PathKey,
+	pub picture_type: PictureType,
+	pub picture_index: Option<u32>,
+}
+
 #[derive(
 	Copy, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize,
 )]
@@ -77,6 +110,9 @@ pub struct PathKey(pub Spur);
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct GenreKey(pub Spur);
 
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct LabelKey(pub Spur);
+
 #[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct ArtistKey(pub Spur);
 
@@ -93,6 +129,17 @@ pub struct SongKey {
 impl nohash_hasher::IsEnabled for SongKey {}
 
 impl Song {
+	pub fn decade(&self) -> Option<i64> {
+		self.year.map(|y| y.div_euclid(10) * 10)
+	}
+
+	// Average bitrate in kbps, estimated from file size
+	pub fn bitrate(&self) -> Option<i64> {
+		self.duration
+			.filter(|d| *d > 0)
+			.map(|d| (self.size * 8 / 1000) as i64 / d)
+	}
+
 	pub fn album_key(&self) -> Option<AlbumKey> {
 		let main_artists = match self.album_artists.is_empty() {
 			true => &self.artists,
@@ -110,6 +157,17 @@ impl Song {
 	}
 }
 
+pub fn store_image(
+	dictionary_builder: &mut dictionary::Builder,
+	image: &scanner::Image,
+) -> Option<Image> {
+	Some(Image {
+		path: (&image.path).get_or_intern(dictionary_builder)?,
+		picture_type: image.picture_type,
+		picture_index: image.picture_index,
+	})
+}
+
 pub fn store_song(
 	dictionary_builder: &mut dictionary::Builder,
 	song: &scanner::Song,
@@ -125,6 +183,19 @@ pub fn store_song(
 		None => None,
 	};
 
+	let artist_artwork = match &song.artist_artwork {
+		Some(a) => match a.get_or_intern(dictionary_builder) {
+			Some(a) => Some(a),
+			None => return None,
+		},
+		None => None,
+	};
+
+	let mut images = Vec::with_capacity(song.images.len());
+	for image in &song.images {
+		images.push(store_image(dictionary_builder, image)?);
+	}
+
 	let mut canonicalize = |s: &String| dictionary_builder.get_or_intern_canon(s);
 
 	Some(Song {
@@ -148,6 +219,8 @@ pub fn store_song(
 		year: song.year,
 		album: song.album.as_ref().and_then(&mut canonicalize),
 		artwork: artwork,
+		artist_artwork,
+		images,
 		duration: song.duration,
 		lyricists: song
 			.lyricists
@@ -163,6 +236,8 @@ pub fn store_song(
 			.collect(),
 		genres: song.genres.iter().filter_map(&mut canonicalize).collect(),
 		labels: song.labels.iter().filter_map(&mut canonicalize).collect(),
+		compilation: song.compilation,
+		size: song.size,
 		date_added: song.date_added,
 	})
 }
@@ -0,0 +1,307 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use lasso2::Spur;
+use serde::{Deserialize, Serialize};
+
+use crate::app::index::{
+	dictionary::{self, Dictionary},
+	query::FIELD_KEYWORDS,
+	storage,
+};
+use crate::app::scanner;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum SuggestionKind {
+	Keyword,
+	Artist,
+	Album,
+	Title,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Suggestion {
+	pub kind: SuggestionKind,
+	pub text: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct Completion {
+	key: String,
+	kind: SuggestionKind,
+	value: Spur,
+	num_songs: u32,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+pub struct Suggestions {
+	// Sorted by key, so that all completions of a prefix are contiguous
+	completions: Vec<Completion>,
+}
+
+impl Suggestions {
+	pub fn suggest(&self, dictionary: &Dictionary, prefix: &str, count: usize) -> Vec<Suggestion> {
+		let key = dictionary.sanitize(prefix);
+		if key.is_empty() || count == 0 {
+			return Vec::new();
+		}
+
+		let mut suggestions = FIELD_KEYWORDS
+			.iter()
+			.filter(|k| k.starts_with(&key))
+			.map(|k| Suggestion {
+				kind: SuggestionKind::Keyword,
+				text: k.to_string(),
+			})
+			.take(count)
+			.collect::<Vec<_>>();
+
+		let start = self
+			.completions
+			.partition_point(|c| c.key.as_str() < key.as_str());
+		let end =
+			start + self.completions[start..].partition_point(|c| c.key.starts_with(key.as_str()));
+
+		// Most popular completions first, then shortest
+		let compare = |a: &&Completion, b: &&Completion| -> Ordering {
+			b.num_songs
+				.cmp(&a.num_songs)
+				.then_with(|| a.key.len().cmp(&b.key.len()))
+				.then_with(|| a.key.cmp(&b.key))
+		};
+		// A value may match the prefix from several of its words
+		let mut best_completions = HashMap::<(SuggestionKind, Spur), &Completion>::new();
+		for completion in &self.completions[start..end] {
+			best_completions
+				.entry((completion.kind, completion.value))
+				.and_modify(|c| {
+					if compare(&completion, &*c) == Ordering::Less {
+						*c = completion;
+					}
+				})
+				.or_insert(completion);
+		}
+		let mut completions = best_completions.into_values().collect::<Vec<_>>();
+		let num_completions = count - suggestions.len();
+		if completions.len() > num_completions {
+			completions.select_nth_unstable_by(num_completions, compare);
+			completions.truncate(num_completions);
+		}
+		completions.sort_by(compare);
+
+		suggestions.extend(completions.into_iter().map(|c| Suggestion {
+			kind: c.kind,
+			text: dictionary.resolve(&c.value).to_owned(),
+		}));
+		suggestions
+	}
+}
+
+#[derive(Clone, Default)]
+pub struct Builder {
+	candidates: HashMap<(SuggestionKind, Spur), Candidate>,
+}
+
+#[derive(Clone)]
+struct Candidate {
+	keys: Vec<String>,
+	num_songs: u32,
+}
+
+impl Builder {
+	pub fn add_song(
+		&mut self,
+		dictionary: &dictionary::Builder,
+		scanner_song: &scanner::Song,
+		storage_song: &storage::Song,
+	) {
+		let mut add = |kind: SuggestionKind, raw_value: &str, value: Spur| {
+			self.candidates
+				.entry((kind, value))
+				.or_insert_with(|| Candidate {
+					keys: word_keys(dictionary, raw_value),
+					num_songs: 0,
+				})
+				.num_songs += 1;
+		};
+
+		let mut artists = scanner_song
+			.artists
+			.iter()
+			.zip(storage_song.artists.iter())
+			.chain(
+				scanner_song
+					.album_artists
+					.iter()
+					.zip(storage_song.album_artists.iter()),
+			)
+			.collect::<Vec<_>>();
+		artists.sort_by_key(|(_, artist_key)| artist_key.0);
+		artists.dedup_by_key(|(_, artist_key)| artist_key.0);
+		for (str, artist_key) in artists {
+			add(SuggestionKind::Artist, str, artist_key.0);
+		}
+
+		if let (Some(str), Some(spur)) = (&scanner_song.album, storage_song.album) {
+			add(SuggestionKind::Album, str, spur);
+		}
+
+		if let (Some(str), Some(spur)) = (&scanner_song.title, storage_song.title) {
+			add(SuggestionKind::Title, str, spur);
+		}
+	}
+
+	pub fn build(self) -> Suggestions {
+		let mut completions = self
+			.candidates
+			.into_iter()
+			.flat_map(|((kind, value), candidate)| {
+				candidate.keys.into_iter().map(move |key| Completion {
+					key,
+					kind,
+					value,
+					num_songs: candidate.num_songs,
+				})
+			})
+			.collect::<Vec<_>>();
+		completions.sort_by(|a, b| a.key.cmp(&b.key));
+		Suggestions { completions }
+	}
+}
+
+// Sanitized suffixes of a value starting at each of its words, eg. `pinkfloyd` and `floyd`
+fn word_keys(dictionary: &dictionary::Builder, raw_value: &str) -> Vec<String> {
+	let mut keys = Vec::new();
+	let mut previous = None;
+	for (index, character) in raw_value.char_indices() {
+		let is_word_start = !character.is_whitespace() && previous.is_none_or(char::is_whitespace);
+		previous = Some(character);
+		if !is_word_start {
+			continue;
+		}
+		let key = dictionary.sanitize(&raw_value[index..]);
+		if !key.is_empty() && !keys.contains(&key) {
+			keys.push(key);
+		}
+	}
+	keys
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use super::*;
+	use crate::app::index::storage::store_song;
+
+	fn setup_test(songs: Vec<scanner::Song>) -> (Dictionary, Suggestions) {
+		let mut dictionary_builder = dictionary::Builder::default();
+		let mut builder = Builder::default();
+		for song in songs {
+			let storage_song = store_song(&mut dictionary_builder, &song).unwrap();
+			builder.add_song(&dictionary_builder, &song, &storage_song);
+		}
+		(dictionary_builder.build(), builder.build())
+	}
+
+	fn song(title: &str, artist: &str, album: &str) -> scanner::Song {
+		scanner::Song {
+			virtual_path: PathBuf::from(format!("{artist}/{album}/{title}.mp3")),
+			title: Some(title.to_owned()),
+			artists: vec![artist.to_owned()],
+			album: Some(album.to_owned()),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn suggests_completions_by_popularity() {
+		let (dictionary, suggestions) = setup_test(vec![
+			song("Seek & Destroy", "Metallica", "Kill 'Em All"),
+			song("Whiplash", "Metallica", "Kill 'Em All"),
+			song("Iron Fist", "Motörhead", "Iron Fist"),
+			song("Metal Gods", "Judas Priest", "British Steel"),
+		]);
+
+		let results = suggestions.suggest(&dictionary, "met", 10);
+		assert_eq!(
+			results,
+			vec![
+				Suggestion {
+					kind: SuggestionKind::Artist,
+					text: "Metallica".to_owned()
+				},
+				Suggestion {
+					kind: SuggestionKind::Title,
+					text: "Metal Gods".to_owned()
+				},
+			]
+		);
+
+		let results = suggestions.suggest(&dictionary, "moto", 10);
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].text, "Motörhead");
+
+		assert!(suggestions.suggest(&dictionary, "zzz", 10).is_empty());
+		assert!(suggestions.suggest(&dictionary, "", 10).is_empty());
+	}
+
+	#[test]
+	fn suggests_keywords() {
+		let (dictionary, suggestions) = setup_test(vec![song("Arrival", "ABBA", "Arrival")]);
+
+		let results = suggestions.suggest(&dictionary, "a", 3);
+		assert_eq!(
+			results,
+			vec![
+				Suggestion {
+					kind: SuggestionKind::Keyword,
+					text: "album".to_owned()
+				},
+				Suggestion {
+					kind: SuggestionKind::Keyword,
+					text: "albumartist".to_owned()
+				},
+				Suggestion {
+					kind: SuggestionKind::Keyword,
+					text: "artist".to_owned()
+				},
+			]
+		);
+
+		let results = suggestions.suggest(&dictionary, "arr", 3);
+		assert_eq!(results.len(), 2);
+		assert!(results.iter().all(|s| s.text == "Arrival"));
+	}
+
+	#[test]
+	fn suggests_completions_of_any_word() {
+		let (dictionary, suggestions) = setup_test(vec![
+			song("Time", "Pink Floyd", "The Dark Side of the Moon"),
+			song("Floydian Slip", "Various", "Tribute"),
+			song("The The", "The The", "Soul Mining"),
+		]);
+
+		let results = suggestions.suggest(&dictionary, "floyd", 10);
+		assert_eq!(
+			results,
+			vec![
+				Suggestion {
+					kind: SuggestionKind::Artist,
+					text: "Pink Floyd".to_owned()
+				},
+				Suggestion {
+					kind: SuggestionKind::Title,
+					text: "Floydian Slip".to_owned()
+				},
+			]
+		);
+
+		let results = suggestions.suggest(&dictionary, "dark side", 10);
+		assert_eq!(results.len(), 1);
+		assert_eq!(results[0].text, "The Dark Side of the Moon");
+
+		// Values matching from several words are only suggested once
+		let results = suggestions.suggest(&dictionary, "the the", 10);
+		assert_eq!(results.len(), 2);
+	}
+}
@@ -35,8 +35,13 @@ pub fn read_legacy_config(
 
 	Ok(Some(config::storage::Config {
 		album_art_pattern: Some(album_art_pattern),
+		artist_art_pattern: None,
 		mount_dirs,
 		ddns_update_url: None,
+		compilation_artists: None,
+		duplicate_detection: None,
+		genres: vec![],
+		transliterations: Default::default(),
 		users: users.into_values().collect(),
 	}))
 }
@@ -75,6 +80,7 @@ fn read_users(db_file_path: &PathBuf) -> Result<HashMap<u32, config::storage::Us
 				admin: row.get(3)?,
 				initial_password: None,
 				hashed_password: row.get(2)?,
+				collapse_duplicates: None,
 			},
 		))
 	})?;
@@ -209,8 +215,13 @@ mod test {
 
 		let expected = config::storage::Config {
 			album_art_pattern: Some("Folder.(jpeg|jpg|png)".to_owned()),
+			artist_art_pattern: None,
 			mount_dirs: vec![],
 			ddns_update_url: None,
+			compilation_artists: None,
+			duplicate_detection: None,
+			genres: vec![],
+			transliterations: Default::default(),
 			users: vec![],
 		};
 
@@ -228,16 +239,22 @@ mod test {
 
 		let expected = config::storage::Config {
 			album_art_pattern: Some("Folder.(jpeg|jpg|png)".to_owned()),
+			artist_art_pattern: None,
 			mount_dirs: vec![config::storage::MountDir {
 				source: PathBuf::from_iter(["test-data", "small-collection"]),
 				name: "root".to_owned(),
 			}],
 			ddns_update_url: None,
+			compilation_artists: None,
+			duplicate_detection: None,
+			genres: vec![],
+			transliterations: Default::default(),
 			users: vec![config::storage::User {
 				name: "example_user".to_owned(),
 				admin: Some(true),
 				initial_password: None,
 				hashed_password: Some("$pbkdf2-sha256$i=10000,l=32$ADvDnwBv3kLUtjTJEwGcFA$oK43ICpNt2rbH21diMo6cSXL62qqLWOM7qs8f0s/9Oo".to_owned()),
+				collapse_duplicates: None,
 			}],
 		};
 
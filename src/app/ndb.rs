@@ -11,6 +11,9 @@ use crate::app::{playlist, Error};
 static MODELS: LazyLock<Models> = LazyLock::new(|| {
 	let mut models = Models::new();
 	models.define::<playlist::v1::PlaylistModel>().unwrap();
+	models.define::<playlist::v1::MembershipModel>().unwrap();
+	models.define::<playlist::v1::DanglingSongModel>().unwrap();
+	models.define::<playlist::v2::PlaylistModel>().unwrap();
 	models
 });
 
@@ -26,6 +29,11 @@ impl Manager {
 		let database = native_db::Builder::new()
 			.create(&MODELS, path)
 			.map_err(Error::NativeDatabaseCreationError)?;
+
+		let transaction = database.rw_transaction()?;
+		transaction.migrate::<playlist::PlaylistModel>()?;
+		transaction.commit()?;
+
 		let database = Arc::new(database);
 		Ok(Self { database })
 	}
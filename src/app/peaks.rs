@@ -85,6 +85,36 @@ impl Manager {
 		Ok(peaks)
 	}
 
+	/// Fingerprint of an audio file. Fingerprints are cached, so that files are only
+	/// decoded again after they are modified.
+	pub fn get_fingerprint(&self, audio_path: &Path) -> Result<Fingerprint, Error> {
+		let modified = std::fs::metadata(audio_path)
+			.and_then(|m| m.modified())
+			.map_err(|e| Error::Io(audio_path.to_owned(), e))?;
+		let fingerprint_path = {
+			let mut hasher = DefaultHasher::new();
+			audio_path.hash(&mut hasher);
+			modified.hash(&mut hasher);
+			self.peaks_dir_path
+				.join(format!("{}.fingerprint", hasher.finish()))
+		};
+
+		if let Ok(serialized) = std::fs::read(&fingerprint_path) {
+			if let Ok(fingerprint) = bitcode::deserialize::<Fingerprint>(&serialized) {
+				return Ok(fingerprint);
+			}
+		}
+
+		let fingerprint = compute_fingerprint(audio_path)?;
+		let serialized = bitcode::serialize(&fingerprint).map_err(Error::PeaksSerialization)?;
+		std::fs::create_dir_all(&self.peaks_dir_path)
+			.map_err(|e| Error::Io(self.peaks_dir_path.clone(), e))?;
+		std::fs::write(&fingerprint_path, serialized)
+			.map_err(|e| Error::Io(fingerprint_path.clone(), e))?;
+
+		Ok(fingerprint)
+	}
+
 	fn hash(path: &Path) -> u64 {
 		let mut hasher = DefaultHasher::new();
 		path.hash(&mut hasher);
@@ -95,13 +125,91 @@ impl Manager {
 fn compute_peaks(audio_path: &Path) -> Result<Peaks, Error> {
 	let peaks_per_minute = 4000;
 
-	let file =
-		std::fs::File::open(audio_path).map_err(|e| Error::Io(audio_path.to_owned(), e))?;
-	let media_source = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
-
 	let mut peaks = Peaks::default();
 	peaks.interleaved.reserve(5 * peaks_per_minute);
 
+	let (mut min, mut max) = (u8::MAX, u8::MIN);
+	let mut num_ingested = 0;
+
+	decode_mono(audio_path, |sample_rate, mono| {
+		let num_samples_per_peak =
+			((sample_rate as f32) * 60.0 / (peaks_per_minute as f32)).round() as usize;
+
+		min = u8::min(min, mono);
+		max = u8::max(max, mono);
+		num_ingested += 1;
+
+		if num_ingested >= num_samples_per_peak {
+			peaks.interleaved.push(min);
+			peaks.interleaved.push(max);
+			(min, max) = (u8::MAX, u8::MIN);
+			num_ingested = 0;
+		}
+	})?;
+
+	Ok(peaks)
+}
+
+/// Compact summary of how loudness evolves over the course of a song. Each bit
+/// records whether a short frame of audio is louder than the one before it,
+/// which survives transcoding between formats and bitrates.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint(pub Vec<u32>);
+
+impl Fingerprint {
+	/// Fraction of bits (between 0 and 1) which agree between two fingerprints
+	pub fn similarity(&self, other: &Fingerprint) -> f32 {
+		let num_bits = 32 * self.0.len().min(other.0.len());
+		if num_bits == 0 {
+			return 0.0;
+		}
+		let num_different_bits = self
+			.0
+			.iter()
+			.zip(other.0.iter())
+			.map(|(a, b)| (a ^ b).count_ones() as usize)
+			.sum::<usize>();
+		1.0 - (num_different_bits as f32 / num_bits as f32)
+	}
+}
+
+fn compute_fingerprint(audio_path: &Path) -> Result<Fingerprint, Error> {
+	let frames_per_second = 10;
+
+	let mut fingerprint = Fingerprint::default();
+	let mut num_bits = 0;
+	let mut previous_energy = None;
+	let (mut energy, mut num_ingested) = (0u64, 0);
+
+	decode_mono(audio_path, |sample_rate, mono| {
+		let num_samples_per_frame = (sample_rate / frames_per_second).max(1) as usize;
+
+		energy += (mono as i64 - 128).unsigned_abs();
+		num_ingested += 1;
+
+		if num_ingested >= num_samples_per_frame {
+			if let Some(previous_energy) = previous_energy {
+				if num_bits % 32 == 0 {
+					fingerprint.0.push(0);
+				}
+				if energy > previous_energy {
+					*fingerprint.0.last_mut().unwrap() |= 1 << (num_bits % 32);
+				}
+				num_bits += 1;
+			}
+			previous_energy = Some(energy);
+			(energy, num_ingested) = (0, 0);
+		}
+	})?;
+
+	Ok(fingerprint)
+}
+
+// Decodes an audio file and feeds its samples to `on_sample`, merged into a mono signal
+fn decode_mono<F: FnMut(u32, u8)>(audio_path: &Path, mut on_sample: F) -> Result<(), Error> {
+	let file = std::fs::File::open(audio_path).map_err(|e| Error::Io(audio_path.to_owned(), e))?;
+	let media_source = MediaSourceStream::new(Box::new(file), MediaSourceStreamOptions::default());
+
 	let mut format = symphonia::default::get_probe()
 		.format(
 			&Hint::new(),
@@ -124,9 +232,6 @@ fn compute_peaks(audio_path: &Path) -> Result<Peaks, Error> {
 		.make(&track.codec_params, &DecoderOptions::default())
 		.map_err(Error::MediaDecoderError)?;
 
-	let (mut min, mut max) = (u8::MAX, u8::MIN);
-	let mut num_ingested = 0;
-
 	loop {
 		let packet = match format.next_packet() {
 			Ok(packet) => packet,
@@ -149,8 +254,6 @@ fn compute_peaks(audio_path: &Path) -> Result<Peaks, Error> {
 
 		let num_channels = decoded.spec().channels.count();
 		let sample_rate = decoded.spec().rate;
-		let num_samples_per_peak =
-			((sample_rate as f32) * 60.0 / (peaks_per_minute as f32)).round() as usize;
 
 		let mut buffer = SampleBuffer::<u8>::new(decoded.capacity() as u64, *decoded.spec());
 		buffer.copy_interleaved_ref(decoded);
@@ -161,19 +264,31 @@ fn compute_peaks(audio_path: &Path) -> Result<Peaks, Error> {
 				mono += *sample as u32;
 			}
 			mono /= samples.len() as u32;
-
-			min = u8::min(min, mono as u8);
-			max = u8::max(max, mono as u8);
-			num_ingested += 1;
-
-			if num_ingested >= num_samples_per_peak {
-				peaks.interleaved.push(min);
-				peaks.interleaved.push(max);
-				(min, max) = (u8::MAX, u8::MIN);
-				num_ingested = 0;
-			}
+			on_sample(sample_rate, mono as u8);
 		}
 	}
 
-	Ok(peaks)
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::test::prepare_test_directory;
+	use crate::test_name;
+
+	#[test]
+	fn fingerprints_are_cached() {
+		let peaks_dir_path = prepare_test_directory(test_name!());
+		let manager = Manager::new(peaks_dir_path.clone());
+		let audio_path =
+			Path::new("test-data/small-collection/Khemmis/Hunted/02 - Candlelight.mp3");
+
+		let fingerprint = manager.get_fingerprint(audio_path).unwrap();
+		assert!(!fingerprint.0.is_empty());
+		assert_eq!(std::fs::read_dir(&peaks_dir_path).unwrap().count(), 1);
+
+		assert_eq!(manager.get_fingerprint(audio_path).unwrap(), fingerprint);
+		assert_eq!(std::fs::read_dir(&peaks_dir_path).unwrap().count(), 1);
+	}
 }
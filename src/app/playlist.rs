@@ -1,26 +1,209 @@
 use core::clone::Clone;
-use std::collections::{BTreeMap, HashMap};
-use std::path::PathBuf;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use icu_collator::{Collator, CollatorOptions, Strength};
+use image::DynamicImage;
+use log::{error, info};
 use native_db::*;
 use native_model::{native_model, Model};
 use serde::{Deserialize, Serialize};
 use tokio::task::spawn_blocking;
 
-use crate::app::{index, ndb, Error};
+use crate::app::{config, index, ndb, Error};
+
+pub mod format;
+
+// Path of the audio streaming endpoint, used to recognize songs in playlists exported as URLs
+const STREAM_URL_PATH: &str = "/api/audio/";
 
 #[derive(Clone)]
 pub struct Manager {
 	db: ndb::Manager,
+	index_manager: index::Manager,
+	config_manager: config::Manager,
+	covers_dir_path: PathBuf,
+}
+
+/// Search query whose results make up the content of a smart playlist
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SmartQuery {
+	pub query: String,
+	/// Content of an `ORDER BY` clause (eg. `year DESC, title`)
+	pub sort: Option<String>,
+	pub limit: Option<usize>,
+}
+
+impl SmartQuery {
+	fn search_query(&self) -> String {
+		match &self.sort {
+			Some(sort) => format!("({}) ORDER BY {sort}", self.query),
+			None => self.query.clone(),
+		}
+	}
+}
+
+/// Outcome of importing a playlist file
+#[derive(Debug, Default)]
+pub struct ImportReport {
+	pub num_songs: usize,
+	/// Locations listed in the playlist file which did not match any song in the collection
+	pub unresolved: Vec<String>,
+}
+
+/// Change to the songs of a playlist, applied on top of its current content
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlaylistEdit {
+	/// Adds songs at the end of the playlist
+	Append(Vec<PathBuf>),
+	/// Adds songs before the song at `position`
+	Insert {
+		position: usize,
+		songs: Vec<PathBuf>,
+	},
+	/// Removes `count` songs starting at `position`
+	Remove { position: usize, count: usize },
+	/// Moves `count` songs starting at `from`, so that the first of them ends up at position `to`
+	Move {
+		from: usize,
+		count: usize,
+		to: usize,
+	},
+	/// Removes repeated occurrences of the same song, keeping the first one
+	Dedupe,
+}
+
+impl PlaylistEdit {
+	// Edits which do not refer to positions in the playlist can be applied on top of any version
+	fn is_positional(&self) -> bool {
+		!matches!(self, Self::Append(_) | Self::Dedupe)
+	}
+
+	fn apply(&self, songs: &mut Vec<PathBuf>) -> Result<(), Error> {
+		match self {
+			Self::Append(new_songs) => songs.extend(new_songs.iter().cloned()),
+			Self::Insert {
+				position,
+				songs: new_songs,
+			} => {
+				if *position > songs.len() {
+					return Err(Error::PlaylistPositionOutOfRange);
+				}
+				songs.splice(*position..*position, new_songs.iter().cloned());
+			}
+			Self::Remove { position, count } => {
+				songs.drain(checked_range(*position, *count, songs.len())?);
+			}
+			Self::Move { from, count, to } => {
+				let range = checked_range(*from, *count, songs.len())?;
+				if *to > songs.len() - count {
+					return Err(Error::PlaylistPositionOutOfRange);
+				}
+				let moved = songs.drain(range).collect::<Vec<_>>();
+				songs.splice(*to..*to, moved);
+			}
+			Self::Dedupe => {
+				let mut seen = HashSet::new();
+				songs.retain(|s| seen.insert(s.clone()));
+			}
+		}
+		Ok(())
+	}
+}
+
+fn checked_range(position: usize, count: usize, len: usize) -> Result<Range<usize>, Error> {
+	let end = position
+		.checked_add(count)
+		.filter(|end| *end <= len)
+		.ok_or(Error::PlaylistPositionOutOfRange)?;
+	Ok(position..end)
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Visibility {
+	/// Only the owner can access the playlist
+	#[default]
+	Private,
+	/// Members can read the playlist
+	SharedRead,
+	/// Members can read and edit the playlist
+	Collaborative,
+}
+
+/// Users who have access to a playlist besides its owner
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Sharing {
+	pub visibility: Visibility,
+	pub members: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Access {
+	Read,
+	Write,
+}
+
+impl Sharing {
+	fn allows(&self, user: &str, access: Access) -> bool {
+		let is_member = self.members.iter().any(|m| m == user);
+		match (self.visibility, access) {
+			(Visibility::Private, _) => false,
+			(Visibility::SharedRead, Access::Read) => is_member,
+			(Visibility::SharedRead, Access::Write) => false,
+			(Visibility::Collaborative, _) => is_member,
+		}
+	}
+}
+
+/// Information describing a playlist, set by its owner
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+	pub description: Option<String>,
+	/// Slash-separated folder to organize playlists in (eg. `Road Trips/Summer`)
+	pub folder: Option<String>,
+	/// Pinned playlists are listed first
+	pub pinned: bool,
+}
+
+/// Image representing a playlist
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Cover {
+	/// Image uploaded by the playlist owner
+	Custom(PathBuf),
+	/// Album art of the first songs in the playlist, to be laid out as a mosaic
+	Mosaic([PathBuf; 4]),
+	/// Album art of a playlist whose songs have fewer than four distinct covers
+	Artwork(PathBuf),
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum PlaylistSource {
+	/// Playlist saved by its owner
+	#[default]
+	User,
+	/// Read-only playlist file found in a mount directory, identified by its virtual path
+	Folder(PathBuf),
 }
 
 #[derive(Debug)]
 pub struct PlaylistHeader {
+	/// User who owns the playlist, absent for folder playlists
+	pub owner: Option<String>,
 	pub name: String,
 	pub duration: Duration,
 	pub num_songs_by_genre: HashMap<String, u32>,
+	pub smart_query: Option<SmartQuery>,
+	pub source: PlaylistSource,
+	/// Incremented every time the playlist is modified
+	pub version: u64,
+	pub sharing: Sharing,
+	pub metadata: Metadata,
+	/// Whether the playlist cover is an image uploaded by its owner
+	pub custom_cover: bool,
 }
 
 #[derive(Debug)]
@@ -29,8 +212,10 @@ pub struct Playlist {
 	pub songs: Vec<PathBuf>,
 }
 
-pub type PlaylistModel = v1::PlaylistModel;
-type PlaylistModelKey = v1::PlaylistModelKey;
+pub type PlaylistModel = v2::PlaylistModel;
+type PlaylistModelKey = v2::PlaylistModelKey;
+type MembershipModel = v1::MembershipModel;
+type DanglingSongModel = v1::DanglingSongModel;
 
 pub mod v1 {
 
@@ -53,14 +238,135 @@ pub mod v1 {
 			(&self.owner, &self.name)
 		}
 	}
+
+	/// Playlists shared with a user, so they can be listed without reading every playlist
+	#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+	#[native_model(id = 2, version = 1)]
+	#[native_db]
+	pub struct MembershipModel {
+		#[primary_key]
+		pub member: String,
+		/// Owner and name of each playlist
+		pub playlists: BTreeSet<(String, String)>,
+	}
+
+	/// Song listed in playlists which is missing from the collection, described well enough
+	/// to be relinked if it reappears at a different location
+	#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+	#[native_model(id = 3, version = 1)]
+	#[native_db(primary_key(custom_id -> String))]
+	pub struct DanglingSongModel {
+		pub virtual_path: PathBuf,
+		pub title: Option<String>,
+		pub artists: Vec<String>,
+		pub album: Option<String>,
+		pub duration: Option<i64>,
+	}
+
+	impl DanglingSongModel {
+		fn custom_id(&self) -> String {
+			self.virtual_path.to_string_lossy().into_owned()
+		}
+	}
+
+	impl From<index::Song> for DanglingSongModel {
+		fn from(s: index::Song) -> Self {
+			Self {
+				virtual_path: s.virtual_path,
+				title: s.title,
+				artists: s.artists,
+				album: s.album,
+				duration: s.duration,
+			}
+		}
+	}
+
+	impl From<DanglingSongModel> for index::Song {
+		fn from(s: DanglingSongModel) -> Self {
+			Self {
+				virtual_path: s.virtual_path,
+				title: s.title,
+				artists: s.artists,
+				album: s.album,
+				duration: s.duration,
+				..Default::default()
+			}
+		}
+	}
+}
+
+pub mod v2 {
+
+	use super::*;
+
+	#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+	#[native_model(id = 1, version = 2, from = v1::PlaylistModel)]
+	#[native_db(primary_key(custom_id -> (&str, &str)))]
+	pub struct PlaylistModel {
+		#[secondary_key]
+		pub owner: String,
+		pub name: String,
+		// Duration and genres of smart playlists are refreshed after every scan
+		pub duration: Duration,
+		pub num_songs_by_genre: BTreeMap<String, u32>,
+		pub virtual_paths: Vec<PathBuf>,
+		pub smart_query: Option<SmartQuery>,
+		pub version: u64,
+		pub sharing: Sharing,
+		pub metadata: Metadata,
+		/// File name of the uploaded cover image, within the covers directory
+		pub cover: Option<String>,
+	}
+
+	impl PlaylistModel {
+		fn custom_id(&self) -> (&str, &str) {
+			(&self.owner, &self.name)
+		}
+	}
+
+	impl From<v1::PlaylistModel> for PlaylistModel {
+		fn from(p: v1::PlaylistModel) -> Self {
+			Self {
+				owner: p.owner,
+				name: p.name,
+				duration: p.duration,
+				num_songs_by_genre: p.num_songs_by_genre,
+				virtual_paths: p.virtual_paths,
+				smart_query: None,
+				version: 1,
+				sharing: Sharing::default(),
+				metadata: Metadata::default(),
+				cover: None,
+			}
+		}
+	}
+
+	impl From<PlaylistModel> for v1::PlaylistModel {
+		fn from(p: PlaylistModel) -> Self {
+			Self {
+				owner: p.owner,
+				name: p.name,
+				duration: p.duration,
+				num_songs_by_genre: p.num_songs_by_genre,
+				virtual_paths: p.virtual_paths,
+			}
+		}
+	}
 }
 
 impl From<PlaylistModel> for PlaylistHeader {
 	fn from(p: PlaylistModel) -> Self {
 		Self {
+			owner: Some(p.owner),
 			name: p.name,
 			duration: p.duration,
 			num_songs_by_genre: p.num_songs_by_genre.into_iter().collect(),
+			smart_query: p.smart_query,
+			source: PlaylistSource::User,
+			version: p.version,
+			sharing: p.sharing,
+			metadata: p.metadata,
+			custom_cover: p.cover.is_some(),
 		}
 	}
 }
@@ -75,72 +381,371 @@ impl From<PlaylistModel> for Playlist {
 	}
 }
 
+impl From<index::FolderPlaylist> for PlaylistHeader {
+	fn from(p: index::FolderPlaylist) -> Self {
+		Self {
+			owner: None,
+			name: p
+				.virtual_path
+				.file_stem()
+				.map(|s| s.to_string_lossy().into_owned())
+				.unwrap_or_default(),
+			duration: p.duration,
+			num_songs_by_genre: p.num_songs_by_genre.into_iter().collect(),
+			smart_query: None,
+			source: PlaylistSource::Folder(p.virtual_path),
+			version: 0,
+			sharing: Sharing::default(),
+			metadata: Metadata::default(),
+			custom_cover: false,
+		}
+	}
+}
+
+impl From<index::FolderPlaylist> for Playlist {
+	fn from(mut p: index::FolderPlaylist) -> Self {
+		let songs = std::mem::take(&mut p.songs);
+		Self {
+			songs,
+			header: p.into(),
+		}
+	}
+}
+
+fn summarize_songs(songs: &[index::Song]) -> (Duration, BTreeMap<String, u32>) {
+	let duration = songs
+		.iter()
+		.filter_map(|s| s.duration.map(|d| d as u64))
+		.sum();
+
+	let mut num_songs_by_genre = BTreeMap::<String, u32>::new();
+	for song in songs {
+		for genre in &song.genres {
+			*num_songs_by_genre.entry(genre.clone()).or_default() += 1;
+		}
+	}
+
+	(Duration::from_secs(duration), num_songs_by_genre)
+}
+
+// Playlist about to be overwritten, if any
+fn existing_playlist(
+	transaction: &transaction::RwTransaction<'_>,
+	owner: &str,
+	name: &str,
+) -> Result<Option<PlaylistModel>, Error> {
+	Ok(transaction.get().primary::<PlaylistModel>((owner, name))?)
+}
+
+// Keeps the playlists listed for each member in sync with the members of a playlist
+fn update_memberships(
+	transaction: &transaction::RwTransaction<'_>,
+	owner: &str,
+	name: &str,
+	previous_members: &[String],
+	members: &[String],
+) -> Result<(), Error> {
+	let playlist = (owner.to_owned(), name.to_owned());
+	for member in previous_members.iter().filter(|m| !members.contains(m)) {
+		let Some(mut membership) = transaction
+			.get()
+			.primary::<MembershipModel>(member.as_str())?
+		else {
+			continue;
+		};
+		membership.playlists.remove(&playlist);
+		if membership.playlists.is_empty() {
+			transaction.remove::<MembershipModel>(membership)?;
+		} else {
+			transaction.upsert::<MembershipModel>(membership)?;
+		}
+	}
+	for member in members.iter().filter(|m| !previous_members.contains(m)) {
+		let mut membership = transaction
+			.get()
+			.primary::<MembershipModel>(member.as_str())?
+			.unwrap_or_else(|| MembershipModel {
+				member: member.clone(),
+				..Default::default()
+			});
+		membership.playlists.insert(playlist.clone());
+		transaction.upsert::<MembershipModel>(membership)?;
+	}
+	Ok(())
+}
+
+fn display_title(song: &index::Song) -> Option<String> {
+	let title = song.title.as_ref()?;
+	if song.artists.is_empty() {
+		Some(title.clone())
+	} else {
+		Some(format!("{} - {title}", song.artists.join(", ")))
+	}
+}
+
 impl Manager {
-	pub fn new(db: ndb::Manager) -> Self {
-		Self { db }
+	pub fn new(
+		db: ndb::Manager,
+		index_manager: index::Manager,
+		config_manager: config::Manager,
+		covers_dir_path: PathBuf,
+	) -> Self {
+		Self {
+			db,
+			index_manager,
+			config_manager,
+			covers_dir_path,
+		}
 	}
 
-	pub async fn list_playlists(&self, owner: &str) -> Result<Vec<PlaylistHeader>, Error> {
-		spawn_blocking({
+	pub async fn list_playlists(&self, user: &str) -> Result<Vec<PlaylistHeader>, Error> {
+		let mut playlists = spawn_blocking({
 			let manager = self.clone();
-			let owner = owner.to_owned();
-			move || {
+			let user = user.to_owned();
+			move || -> Result<Vec<PlaylistHeader>, Error> {
 				let transaction = manager.db.r_transaction()?;
 				let mut playlists = transaction
 					.scan()
 					.secondary::<PlaylistModel>(PlaylistModelKey::owner)?
-					.range(owner.as_str()..=owner.as_str())?
+					.range(user.as_str()..=user.as_str())?
 					.filter_map(|p| p.ok())
 					.map(PlaylistHeader::from)
 					.collect::<Vec<_>>();
 
-				let collator_options = {
-					let mut o = CollatorOptions::new();
-					o.strength = Some(Strength::Secondary);
-					o
-				};
-				let collator = Collator::try_new(&Default::default(), collator_options).unwrap();
-
-				playlists.sort_by(|a, b| collator.compare(&a.name, &b.name));
+				let shared_playlists = transaction
+					.get()
+					.primary::<MembershipModel>(user.as_str())?
+					.map(|m| m.playlists)
+					.unwrap_or_default();
+				for (owner, name) in shared_playlists {
+					let Some(playlist) = transaction
+						.get()
+						.primary::<PlaylistModel>((owner.as_str(), name.as_str()))?
+					else {
+						continue;
+					};
+					if playlist.sharing.allows(&user, Access::Read) {
+						let mut header = PlaylistHeader::from(playlist);
+						// Playlists are pinned by their owner
+						header.metadata.pinned = false;
+						playlists.push(header);
+					}
+				}
 				Ok(playlists)
 			}
 		})
-		.await?
+		.await??;
+
+		for folder_playlist in self.index_manager.get_folder_playlists().await {
+			playlists.push(PlaylistHeader::from(folder_playlist));
+		}
+
+		let collator_options = {
+			let mut o = CollatorOptions::new();
+			o.strength = Some(Strength::Secondary);
+			o
+		};
+		let collator = Collator::try_new(&Default::default(), collator_options).unwrap();
+
+		playlists.sort_by(|a, b| {
+			b.metadata
+				.pinned
+				.cmp(&a.metadata.pinned)
+				.then_with(|| collator.compare(&a.name, &b.name))
+		});
+		Ok(playlists)
 	}
 
-	pub async fn save_playlist(
+	/// Checks that a user has access to a playlist. Playlists which a user cannot read are
+	/// reported as missing.
+	pub async fn authorize(
 		&self,
 		name: &str,
 		owner: &str,
-		songs: Vec<index::Song>,
+		user: &str,
+		access: Access,
+	) -> Result<(), Error> {
+		if owner == user {
+			return Ok(());
+		}
+		let playlist = self.read_playlist_model(name, owner).await?;
+		if !playlist.sharing.allows(user, Access::Read) {
+			return Err(Error::PlaylistNotFound);
+		}
+		if !playlist.sharing.allows(user, access) {
+			return Err(Error::PlaylistAccessDenied);
+		}
+		Ok(())
+	}
+
+	pub async fn set_playlist_sharing(
+		&self,
+		name: &str,
+		owner: &str,
+		mut sharing: Sharing,
 	) -> Result<(), Error> {
+		sharing.members.retain(|m| m != owner);
+		sharing.members.sort();
+		sharing.members.dedup();
+		for member in &sharing.members {
+			self.config_manager.get_user(member).await?;
+		}
+
 		spawn_blocking({
 			let manager = self.clone();
 			let owner = owner.to_owned();
 			let name = name.to_owned();
 			move || {
 				let transaction = manager.db.rw_transaction()?;
+				let playlist = existing_playlist(&transaction, &owner, &name)?
+					.ok_or(Error::PlaylistNotFound)?;
+				update_memberships(
+					&transaction,
+					&owner,
+					&name,
+					&playlist.sharing.members,
+					&sharing.members,
+				)?;
+				transaction.upsert::<PlaylistModel>(PlaylistModel {
+					version: playlist.version + 1,
+					sharing,
+					..playlist
+				})?;
+				transaction.commit()?;
+				Ok(())
+			}
+		})
+		.await?
+	}
+
+	/// Revokes access of a deleted user to the playlists shared with them
+	pub async fn remove_member(&self, user: &str) -> Result<(), Error> {
+		spawn_blocking({
+			let manager = self.clone();
+			let user = user.to_owned();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				let Some(membership) = transaction
+					.get()
+					.primary::<MembershipModel>(user.as_str())?
+				else {
+					return Ok(());
+				};
+				for (owner, name) in &membership.playlists {
+					let Some(mut playlist) = existing_playlist(&transaction, owner, name)? else {
+						continue;
+					};
+					playlist.sharing.members.retain(|m| *m != user);
+					transaction.upsert::<PlaylistModel>(PlaylistModel {
+						version: playlist.version + 1,
+						..playlist
+					})?;
+				}
+				transaction.remove::<MembershipModel>(membership)?;
+				transaction.commit()?;
+				Ok(())
+			}
+		})
+		.await?
+	}
+
+	pub async fn read_folder_playlist(&self, virtual_path: PathBuf) -> Result<Playlist, Error> {
+		let folder_playlist = self.index_manager.get_folder_playlist(virtual_path).await?;
+		Ok(folder_playlist.into())
+	}
+
+	async fn evaluate(&self, smart_query: &SmartQuery) -> Result<Vec<index::Song>, Error> {
+		let page = self
+			.index_manager
+			.search(
+				smart_query.search_query(),
+				index::SearchOrder::Alphabetical,
+				false,
+				0,
+				smart_query.limit,
+			)
+			.await?;
+		Ok(page.songs.into_iter().map(|r| r.song).collect())
+	}
+
+	/// Updates the statistics stored for smart playlists, whose songs may have changed since
+	/// they were last evaluated
+	pub async fn refresh_smart_playlists(&self) -> Result<(), Error> {
+		let smart_playlists = spawn_blocking({
+			let manager = self.clone();
+			move || -> Result<Vec<_>, Error> {
+				let transaction = manager.db.r_transaction()?;
+				let smart_playlists = transaction
+					.scan()
+					.primary::<PlaylistModel>()?
+					.all()?
+					.filter_map(|p| p.ok())
+					.filter_map(|p| Some((p.owner, p.name, p.version, p.smart_query?)))
+					.collect();
+				Ok(smart_playlists)
+			}
+		})
+		.await??;
 
-				let duration = songs
-					.iter()
-					.filter_map(|s| s.duration.map(|d| d as u64))
-					.sum();
+		let mut summaries = Vec::new();
+		for (owner, name, version, smart_query) in smart_playlists {
+			match self.evaluate(&smart_query).await {
+				Ok(songs) => summaries.push((owner, name, version, summarize_songs(&songs))),
+				Err(e) => error!("Could not evaluate smart playlist `{name}` of `{owner}`: {e}"),
+			}
+		}
 
-				let mut num_songs_by_genre = BTreeMap::<String, u32>::new();
-				for song in &songs {
-					for genre in &song.genres {
-						*num_songs_by_genre.entry(genre.clone()).or_default() += 1;
+		spawn_blocking({
+			let manager = self.clone();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				for (owner, name, version, (duration, num_songs_by_genre)) in summaries {
+					// Playlists saved in the meantime already have up-to-date statistics
+					let Some(playlist) = existing_playlist(&transaction, &owner, &name)? else {
+						continue;
+					};
+					if playlist.version != version {
+						continue;
 					}
+					transaction.upsert::<PlaylistModel>(PlaylistModel {
+						duration,
+						num_songs_by_genre,
+						..playlist
+					})?;
 				}
+				transaction.commit()?;
+				Ok(())
+			}
+		})
+		.await?
+	}
+
+	pub async fn save_playlist(
+		&self,
+		name: &str,
+		owner: &str,
+		songs: Vec<index::Song>,
+	) -> Result<(), Error> {
+		spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
 
+				let (duration, num_songs_by_genre) = summarize_songs(&songs);
 				let virtual_paths = songs.into_iter().map(|s| s.virtual_path).collect();
+				let existing = existing_playlist(&transaction, &owner, &name)?.unwrap_or_default();
 
 				transaction.upsert::<PlaylistModel>(PlaylistModel {
 					owner: owner.to_owned(),
 					name: name.to_owned(),
-					duration: Duration::from_secs(duration),
+					duration,
 					num_songs_by_genre,
 					virtual_paths,
+					smart_query: None,
+					version: existing.version + 1,
+					..existing
 				})?;
 
 				transaction.commit()?;
@@ -151,65 +756,657 @@ impl Manager {
 		.await?
 	}
 
-	pub async fn read_playlist(&self, name: &str, owner: &str) -> Result<Playlist, Error> {
-		spawn_blocking({
-			let manager = self.clone();
-			let owner = owner.to_owned();
-			let name = name.to_owned();
-			move || {
-				let transaction = manager.db.r_transaction()?;
-				match transaction.get().primary::<PlaylistModel>((owner, name)) {
-					Ok(Some(p)) => Ok(Playlist::from(p)),
-					Ok(None) => Err(Error::PlaylistNotFound),
-					Err(e) => Err(Error::NativeDatabase(e)),
-				}
-			}
-		})
-		.await?
-	}
+	pub async fn save_smart_playlist(
+		&self,
+		name: &str,
+		owner: &str,
+		smart_query: SmartQuery,
+	) -> Result<(), Error> {
+		// Also rejects invalid queries before they are saved
+		let songs = self.evaluate(&smart_query).await?;
 
-	pub async fn delete_playlist(&self, name: &str, owner: &str) -> Result<(), Error> {
 		spawn_blocking({
 			let manager = self.clone();
 			let owner = owner.to_owned();
 			let name = name.to_owned();
 			move || {
 				let transaction = manager.db.rw_transaction()?;
-				let playlist = match transaction
-					.get()
-					.primary::<PlaylistModel>((owner.as_str(), name.as_str()))
-				{
-					Ok(Some(p)) => Ok(p),
-					Ok(None) => Err(Error::PlaylistNotFound),
-					Err(e) => Err(Error::NativeDatabase(e)),
-				}?;
-				transaction.remove::<PlaylistModel>(playlist)?;
+				let (duration, num_songs_by_genre) = summarize_songs(&songs);
+				let existing = existing_playlist(&transaction, &owner, &name)?.unwrap_or_default();
+				transaction.upsert::<PlaylistModel>(PlaylistModel {
+					owner,
+					name,
+					duration,
+					num_songs_by_genre,
+					virtual_paths: Vec::new(),
+					smart_query: Some(smart_query),
+					version: existing.version + 1,
+					..existing
+				})?;
 				transaction.commit()?;
 				Ok(())
 			}
 		})
 		.await?
 	}
-}
 
-#[cfg(test)]
-mod test {
-	use std::path::PathBuf;
+	/// Replaces a smart playlist with a regular playlist containing its current songs
+	pub async fn freeze_playlist(&self, name: &str, owner: &str) -> Result<(), Error> {
+		let playlist = self.read_playlist_model(name, owner).await?;
+		let Some(smart_query) = &playlist.smart_query else {
+			return Ok(());
+		};
+		let songs = self.evaluate(smart_query).await?;
+		self.save_playlist(name, owner, songs).await
+	}
 
-	use crate::app::index;
-	use crate::app::test::{self, Context};
-	use crate::test_name;
+	/// Applies an edit to the songs of a regular playlist. When a `version` is provided, the
+	/// edit is rejected if the playlist was modified since that version. Without a `version`,
+	/// edits which refer to positions in the playlist are rejected if the playlist is modified
+	/// while they are being applied.
+	pub async fn edit_playlist(
+		&self,
+		name: &str,
+		owner: &str,
+		edit: PlaylistEdit,
+		version: Option<u64>,
+	) -> Result<PlaylistHeader, Error> {
+		// Songs which are not in the collection are left out, like in `save_playlist`
+		let edit = match edit {
+			PlaylistEdit::Append(songs) => PlaylistEdit::Append(self.known_songs(songs).await),
+			PlaylistEdit::Insert { position, songs } => PlaylistEdit::Insert {
+				position,
+				songs: self.known_songs(songs).await,
+			},
+			edit => edit,
+		};
 
-	const TEST_USER: &str = "test_user";
-	const TEST_PASSWORD: &str = "password";
-	const TEST_PLAYLIST_NAME: &str = "Chill & Grill";
-	const TEST_MOUNT_NAME: &str = "root";
+		loop {
+			let playlist = self.read_playlist_model(name, owner).await?;
+			if playlist.smart_query.is_some() {
+				return Err(Error::SmartPlaylistNotEditable);
+			}
+			let expected_version = version.unwrap_or(playlist.version);
+			if expected_version != playlist.version {
+				return Err(Error::PlaylistVersionConflict(playlist.version));
+			}
 
-	async fn list_all_songs(ctx: &Context) -> Vec<index::Song> {
-		let paths = ctx
-			.index_manager
-			.flatten(PathBuf::from(TEST_MOUNT_NAME))
-			.await
+			// Songs which may end up in the playlist, to summarize its new content
+			let mut virtual_paths = playlist.virtual_paths;
+			if let PlaylistEdit::Append(songs) | PlaylistEdit::Insert { songs, .. } = &edit {
+				virtual_paths.extend(songs.iter().cloned());
+			}
+			let songs = self
+				.index_manager
+				.get_songs(virtual_paths.clone())
+				.await
+				.into_iter()
+				.zip(virtual_paths)
+				.filter_map(|(song, virtual_path)| Some((virtual_path, song.ok()?)))
+				.collect::<HashMap<_, _>>();
+
+			// Edits based on the latest version are retried if another edit came first, unless
+			// they refer to positions which may now designate different songs
+			match self
+				.apply_playlist_edit(name, owner, expected_version, edit.clone(), songs)
+				.await
+			{
+				Err(Error::PlaylistVersionConflict(_))
+					if version.is_none() && !edit.is_positional() =>
+				{
+					continue
+				}
+				result => return result,
+			}
+		}
+	}
+
+	async fn known_songs(&self, virtual_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+		self.index_manager
+			.get_songs(virtual_paths)
+			.await
+			.into_iter()
+			.filter_map(|s| s.ok())
+			.map(|s| s.virtual_path)
+			.collect()
+	}
+
+	// Reads, edits and writes a playlist within a single transaction, provided it is still at
+	// the expected version
+	async fn apply_playlist_edit(
+		&self,
+		name: &str,
+		owner: &str,
+		version: u64,
+		edit: PlaylistEdit,
+		songs: HashMap<PathBuf, index::Song>,
+	) -> Result<PlaylistHeader, Error> {
+		spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				let playlist = existing_playlist(&transaction, &owner, &name)?
+					.ok_or(Error::PlaylistNotFound)?;
+				if playlist.smart_query.is_some() {
+					return Err(Error::SmartPlaylistNotEditable);
+				}
+				if playlist.version != version {
+					return Err(Error::PlaylistVersionConflict(playlist.version));
+				}
+
+				let mut virtual_paths = playlist.virtual_paths.clone();
+				edit.apply(&mut virtual_paths)?;
+				let (duration, num_songs_by_genre) = summarize_songs(
+					&virtual_paths
+						.iter()
+						.filter_map(|p| songs.get(p).cloned())
+						.collect::<Vec<_>>(),
+				);
+
+				let playlist = PlaylistModel {
+					duration,
+					num_songs_by_genre,
+					virtual_paths,
+					version: version + 1,
+					..playlist
+				};
+				transaction.upsert::<PlaylistModel>(playlist.clone())?;
+				transaction.commit()?;
+				Ok(playlist.into())
+			}
+		})
+		.await?
+	}
+
+	async fn read_playlist_model(&self, name: &str, owner: &str) -> Result<PlaylistModel, Error> {
+		spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			move || {
+				let transaction = manager.db.r_transaction()?;
+				match transaction.get().primary::<PlaylistModel>((owner, name)) {
+					Ok(Some(p)) => Ok(p),
+					Ok(None) => Err(Error::PlaylistNotFound),
+					Err(e) => Err(Error::NativeDatabase(e)),
+				}
+			}
+		})
+		.await?
+	}
+
+	pub async fn read_playlist(&self, name: &str, owner: &str) -> Result<Playlist, Error> {
+		let playlist = Playlist::from(self.read_playlist_model(name, owner).await?);
+
+		let Some(smart_query) = &playlist.header.smart_query else {
+			return Ok(playlist);
+		};
+
+		let songs = self.evaluate(smart_query).await?;
+		let (duration, num_songs_by_genre) = summarize_songs(&songs);
+		Ok(Playlist {
+			header: PlaylistHeader {
+				duration,
+				num_songs_by_genre: num_songs_by_genre.into_iter().collect(),
+				..playlist.header
+			},
+			songs: songs.into_iter().map(|s| s.virtual_path).collect(),
+		})
+	}
+
+	pub async fn import_playlist(
+		&self,
+		name: &str,
+		owner: &str,
+		format: format::Format,
+		content: &[u8],
+	) -> Result<ImportReport, Error> {
+		let entries = format::parse(format, &format::decode(content))?;
+
+		let mounts = self.config_manager.get_mounts().await;
+		let mut candidates = Vec::new();
+		for (i, entry) in entries.iter().enumerate() {
+			for path in self.locate(&entry.location, &mounts).await {
+				candidates.push((i, path));
+			}
+		}
+
+		let (indices, paths): (Vec<_>, Vec<_>) = candidates.into_iter().unzip();
+		let mut songs = vec![None; entries.len()];
+		for (i, song) in indices
+			.into_iter()
+			.zip(self.index_manager.get_songs(paths).await)
+		{
+			if songs[i].is_none() {
+				songs[i] = song.ok();
+			}
+		}
+
+		let mut report = ImportReport::default();
+		let mut resolved_songs = Vec::new();
+		for (entry, song) in entries.into_iter().zip(songs) {
+			match song {
+				Some(song) => resolved_songs.push(song),
+				None => report.unresolved.push(entry.location),
+			}
+		}
+		report.num_songs = resolved_songs.len();
+
+		self.save_playlist(name, owner, resolved_songs).await?;
+		Ok(report)
+	}
+
+	// Virtual paths which may designate a location listed in a playlist file, most likely first
+	async fn locate(&self, location: &str, mounts: &[config::MountDir]) -> Vec<PathBuf> {
+		if let Some(path) = format::file_url_path(location) {
+			return self
+				.config_manager
+				.virtualize_path(path)
+				.await
+				.into_iter()
+				.collect();
+		}
+
+		if location.contains("://") {
+			return location
+				.split_once(STREAM_URL_PATH)
+				.map(|(_, p)| p.split(['?', '#']).next().unwrap_or_default())
+				.map(|p| PathBuf::from(format::decode_path(p)))
+				.into_iter()
+				.collect();
+		}
+
+		if let Ok(path) = self.config_manager.virtualize_path(location).await {
+			return vec![path];
+		}
+
+		// Relative paths may or may not include the mount name
+		let relative_path = location
+			.split(['/', '\\'])
+			.filter(|c| !c.is_empty() && *c != "." && *c != "..")
+			.collect::<PathBuf>();
+		let mut paths = vec![relative_path.clone()];
+		paths.extend(
+			mounts
+				.iter()
+				.map(|m| Path::new(&m.name).join(&relative_path)),
+		);
+		paths
+	}
+
+	/// Writes a playlist file. Songs are listed by virtual path, or by streaming URL when a
+	/// `base_url` (eg. `http://localhost:5050`) is provided.
+	pub async fn export_playlist(
+		&self,
+		name: &str,
+		owner: &str,
+		format: format::Format,
+		base_url: Option<&str>,
+	) -> Result<String, Error> {
+		let playlist = self.read_playlist(name, owner).await?;
+		let songs = self.index_manager.get_songs(playlist.songs.clone()).await;
+
+		let entries = playlist
+			.songs
+			.iter()
+			.zip(songs)
+			.map(|(virtual_path, song)| {
+				let song = song.ok();
+				let location = match base_url {
+					Some(base_url) => format!(
+						"{}{STREAM_URL_PATH}{}",
+						base_url.trim_end_matches('/'),
+						format::encode_path(virtual_path)
+					),
+					None => format::portable_path(virtual_path),
+				};
+				format::Entry {
+					location,
+					title: song.as_ref().and_then(display_title),
+					duration: song
+						.and_then(|s| s.duration)
+						.map(|d| Duration::from_secs(d as u64)),
+				}
+			})
+			.collect::<Vec<_>>();
+
+		Ok(format::write(format, name, &entries))
+	}
+
+	/// Songs listed in a playlist which are not in the collection
+	pub async fn get_unresolved_songs(
+		&self,
+		name: &str,
+		owner: &str,
+	) -> Result<Vec<PathBuf>, Error> {
+		let playlist = self.read_playlist(name, owner).await?;
+		let songs = self.index_manager.get_songs(playlist.songs.clone()).await;
+		Ok(playlist
+			.songs
+			.into_iter()
+			.zip(songs)
+			.filter_map(|(virtual_path, song)| song.is_err().then_some(virtual_path))
+			.collect())
+	}
+
+	/// Points playlist entries to the new location of songs which were moved or renamed since
+	/// `previous_index`. Entries which cannot be matched confidently are left untouched, and
+	/// looked for again after later scans.
+	pub async fn relink_playlists(&self, previous_index: index::Index) -> Result<(), Error> {
+		let (virtual_paths, dangling_songs) = spawn_blocking({
+			let manager = self.clone();
+			move || -> Result<(HashSet<PathBuf>, Vec<index::Song>), Error> {
+				let transaction = manager.db.r_transaction()?;
+				let virtual_paths = transaction
+					.scan()
+					.primary::<PlaylistModel>()?
+					.all()?
+					.filter_map(|p| p.ok())
+					.flat_map(|p| p.virtual_paths)
+					.collect();
+				let dangling_songs = transaction
+					.scan()
+					.primary::<DanglingSongModel>()?
+					.all()?
+					.filter_map(|s| s.ok())
+					.map(index::Song::from)
+					.collect();
+				Ok((virtual_paths, dangling_songs))
+			}
+		})
+		.await??;
+
+		let relinking = self
+			.index_manager
+			.relink_songs(
+				previous_index,
+				virtual_paths.into_iter().collect(),
+				dangling_songs,
+			)
+			.await;
+		let new_locations = relinking.new_locations;
+
+		spawn_blocking({
+			let manager = self.clone();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+
+				// Songs which went missing are remembered, as they may reappear after later scans
+				let dangling_songs = transaction
+					.scan()
+					.primary::<DanglingSongModel>()?
+					.all()?
+					.filter_map(|s| s.ok())
+					.collect::<Vec<_>>();
+				for song in dangling_songs {
+					transaction.remove::<DanglingSongModel>(song)?;
+				}
+				for song in relinking.missing_songs {
+					transaction.upsert::<DanglingSongModel>(song.into())?;
+				}
+
+				let playlists = transaction
+					.scan()
+					.primary::<PlaylistModel>()?
+					.all()?
+					.filter_map(|p| p.ok())
+					.filter(|p| {
+						p.virtual_paths
+							.iter()
+							.any(|v| new_locations.contains_key(v))
+					})
+					.collect::<Vec<_>>();
+
+				let mut num_relinked = 0;
+				for playlist in playlists {
+					let virtual_paths = playlist
+						.virtual_paths
+						.iter()
+						.map(|v| match new_locations.get(v) {
+							Some(new_location) => {
+								num_relinked += 1;
+								new_location.clone()
+							}
+							None => v.clone(),
+						})
+						.collect();
+					transaction.upsert::<PlaylistModel>(PlaylistModel {
+						virtual_paths,
+						version: playlist.version + 1,
+						..playlist
+					})?;
+				}
+				transaction.commit()?;
+
+				if num_relinked > 0 {
+					info!("Relinked {num_relinked} playlist entries to moved songs");
+				}
+				Ok(())
+			}
+		})
+		.await?
+	}
+
+	pub async fn set_playlist_metadata(
+		&self,
+		name: &str,
+		owner: &str,
+		metadata: Metadata,
+	) -> Result<(), Error> {
+		let metadata = Metadata {
+			description: metadata
+				.description
+				.map(|d| d.trim().to_owned())
+				.filter(|d| !d.is_empty()),
+			folder: metadata.folder.and_then(|f| {
+				let folder = f
+					.split('/')
+					.map(str::trim)
+					.filter(|s| !s.is_empty())
+					.collect::<Vec<_>>()
+					.join("/");
+				(!folder.is_empty()).then_some(folder)
+			}),
+			pinned: metadata.pinned,
+		};
+
+		spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				let playlist = existing_playlist(&transaction, &owner, &name)?
+					.ok_or(Error::PlaylistNotFound)?;
+				transaction.upsert::<PlaylistModel>(PlaylistModel {
+					version: playlist.version + 1,
+					metadata,
+					..playlist
+				})?;
+				transaction.commit()?;
+				Ok(())
+			}
+		})
+		.await?
+	}
+
+	/// Image representing a playlist: either a cover uploaded by its owner, or album art
+	/// from its songs
+	pub async fn get_playlist_cover(&self, name: &str, owner: &str) -> Result<Cover, Error> {
+		let playlist = self.read_playlist_model(name, owner).await?;
+		if let Some(file_name) = &playlist.cover {
+			return Ok(Cover::Custom(self.covers_dir_path.join(file_name)));
+		}
+
+		let playlist = self.read_playlist(name, owner).await?;
+		let mut artworks = Vec::new();
+		for song in self.index_manager.get_songs(playlist.songs).await {
+			let Some(artwork) = song.ok().and_then(|s| s.artwork) else {
+				continue;
+			};
+			if !artworks.contains(&artwork) {
+				artworks.push(artwork);
+			}
+			if artworks.len() == 4 {
+				break;
+			}
+		}
+
+		let mut real_paths = Vec::new();
+		for artwork in artworks {
+			real_paths.push(self.config_manager.resolve_virtual_path(artwork).await?);
+		}
+
+		match <[PathBuf; 4]>::try_from(real_paths) {
+			Ok(real_paths) => Ok(Cover::Mosaic(real_paths)),
+			Err(real_paths) => match real_paths.into_iter().next() {
+				Some(real_path) => Ok(Cover::Artwork(real_path)),
+				None => Err(Error::PlaylistCoverNotFound),
+			},
+		}
+	}
+
+	/// Uses an image as the cover of a playlist, instead of album art from its songs
+	pub async fn set_playlist_cover(
+		&self,
+		name: &str,
+		owner: &str,
+		content: Vec<u8>,
+	) -> Result<(), Error> {
+		self.read_playlist_model(name, owner).await?;
+
+		let file_name = {
+			let mut hasher = DefaultHasher::new();
+			(owner, name, &content).hash(&mut hasher);
+			format!("{}.jpg", hasher.finish())
+		};
+
+		tokio::fs::create_dir_all(&self.covers_dir_path)
+			.await
+			.map_err(|e| Error::Io(self.covers_dir_path.clone(), e))?;
+
+		spawn_blocking({
+			let path = self.covers_dir_path.join(&file_name);
+			move || {
+				let image =
+					image::load_from_memory(&content).map_err(Error::PlaylistCoverDecoding)?;
+				DynamicImage::ImageRgb8(image.into_rgb8())
+					.save(&path)
+					.map_err(|e| Error::Image(path.clone(), e))
+			}
+		})
+		.await??;
+
+		self.replace_playlist_cover(name, owner, Some(file_name))
+			.await
+	}
+
+	/// Reverts a playlist cover to album art from its songs
+	pub async fn remove_playlist_cover(&self, name: &str, owner: &str) -> Result<(), Error> {
+		self.replace_playlist_cover(name, owner, None).await
+	}
+
+	async fn replace_playlist_cover(
+		&self,
+		name: &str,
+		owner: &str,
+		cover: Option<String>,
+	) -> Result<(), Error> {
+		let previous_cover = spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			let cover = cover.clone();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				let playlist = existing_playlist(&transaction, &owner, &name)?
+					.ok_or(Error::PlaylistNotFound)?;
+				let previous_cover = playlist.cover.clone();
+				transaction.upsert::<PlaylistModel>(PlaylistModel {
+					version: playlist.version + 1,
+					cover,
+					..playlist
+				})?;
+				transaction.commit()?;
+				Ok::<_, Error>(previous_cover)
+			}
+		})
+		.await??;
+
+		// Uploading the same image again reuses the same file
+		if previous_cover != cover {
+			self.delete_cover_file(previous_cover).await;
+		}
+		Ok(())
+	}
+
+	async fn delete_cover_file(&self, file_name: Option<String>) {
+		if let Some(file_name) = file_name {
+			let path = self.covers_dir_path.join(file_name);
+			if let Err(e) = tokio::fs::remove_file(&path).await {
+				error!(
+					"Could not delete playlist cover `{}`: {}",
+					path.display(),
+					e
+				);
+			}
+		}
+	}
+
+	pub async fn delete_playlist(&self, name: &str, owner: &str) -> Result<(), Error> {
+		let cover = spawn_blocking({
+			let manager = self.clone();
+			let owner = owner.to_owned();
+			let name = name.to_owned();
+			move || {
+				let transaction = manager.db.rw_transaction()?;
+				let playlist = match transaction
+					.get()
+					.primary::<PlaylistModel>((owner.as_str(), name.as_str()))
+				{
+					Ok(Some(p)) => Ok(p),
+					Ok(None) => Err(Error::PlaylistNotFound),
+					Err(e) => Err(Error::NativeDatabase(e)),
+				}?;
+				let cover = playlist.cover.clone();
+				update_memberships(&transaction, &owner, &name, &playlist.sharing.members, &[])?;
+				transaction.remove::<PlaylistModel>(playlist)?;
+				transaction.commit()?;
+				Ok::<_, Error>(cover)
+			}
+		})
+		.await??;
+
+		self.delete_cover_file(cover).await;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+	use std::time::Duration;
+
+	use super::{
+		format::Format, Access, Cover, Metadata, PlaylistEdit, PlaylistModel, PlaylistSource,
+		Sharing, SmartQuery, Visibility,
+	};
+	use crate::app::test::{self, Context};
+	use crate::app::{index, Error};
+	use crate::test_name;
+
+	const TEST_USER: &str = "test_user";
+	const TEST_PASSWORD: &str = "password";
+	const TEST_PLAYLIST_NAME: &str = "Chill & Grill";
+	const TEST_MOUNT_NAME: &str = "root";
+
+	async fn list_all_songs(ctx: &Context) -> Vec<index::Song> {
+		let paths = ctx
+			.index_manager
+			.flatten(PathBuf::from(TEST_MOUNT_NAME))
+			.await
 			.unwrap()
 			.into_iter()
 			.collect::<Vec<_>>();
@@ -371,4 +1568,891 @@ mod test {
 
 		assert_eq!(names, vec!["ax", "Ay", "àz", "B", "b"]);
 	}
+
+	#[tokio::test]
+	async fn smart_playlist_is_evaluated_on_read() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		let smart_query = SmartQuery {
+			query: "artist = khemmis".to_owned(),
+			sort: Some("title DESC".to_owned()),
+			limit: Some(3),
+		};
+		ctx.playlist_manager
+			.save_smart_playlist(TEST_PLAYLIST_NAME, TEST_USER, smart_query.clone())
+			.await
+			.unwrap();
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(playlist.songs.is_empty());
+
+		ctx.scanner.run_scan().await.unwrap();
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert_eq!(playlist.header.smart_query, Some(smart_query));
+		assert_eq!(playlist.songs.len(), 3);
+
+		let first_song_path: PathBuf =
+			[TEST_MOUNT_NAME, "Khemmis", "Hunted", "03 - Three Gates.mp3"]
+				.iter()
+				.collect();
+		assert_eq!(playlist.songs[0], first_song_path);
+
+		let playlists = ctx
+			.playlist_manager
+			.list_playlists(TEST_USER)
+			.await
+			.unwrap();
+		assert_eq!(playlists.len(), 1);
+		assert!(playlists[0].duration > Duration::ZERO);
+	}
+
+	#[tokio::test]
+	async fn save_smart_playlist_rejects_invalid_query() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.build()
+			.await;
+
+		let smart_query = SmartQuery {
+			query: "year >".to_owned(),
+			..Default::default()
+		};
+		let result = ctx
+			.playlist_manager
+			.save_smart_playlist(TEST_PLAYLIST_NAME, TEST_USER, smart_query)
+			.await;
+		assert!(matches!(result, Err(Error::SearchQueryParseError(_))));
+
+		let playlists = ctx
+			.playlist_manager
+			.list_playlists(TEST_USER)
+			.await
+			.unwrap();
+		assert!(playlists.is_empty());
+	}
+
+	#[tokio::test]
+	async fn can_freeze_smart_playlist() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+
+		let smart_query = SmartQuery {
+			query: "artist = khemmis".to_owned(),
+			..Default::default()
+		};
+		ctx.playlist_manager
+			.save_smart_playlist(TEST_PLAYLIST_NAME, TEST_USER, smart_query)
+			.await
+			.unwrap();
+
+		ctx.playlist_manager
+			.freeze_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(playlist.header.smart_query.is_none());
+		assert_eq!(playlist.songs.len(), 5);
+	}
+
+	#[tokio::test]
+	async fn exported_playlists_can_be_imported() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+		let songs = list_all_songs(&ctx).await;
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, songs)
+			.await
+			.unwrap();
+		let original = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+
+		for (format, base_url) in [
+			(Format::M3U8, None),
+			(Format::PLS, Some("http://localhost:5050")),
+			(Format::XSPF, None),
+			(Format::XSPF, Some("http://localhost:5050/")),
+		] {
+			let content = ctx
+				.playlist_manager
+				.export_playlist(TEST_PLAYLIST_NAME, TEST_USER, format, base_url)
+				.await
+				.unwrap();
+
+			let report = ctx
+				.playlist_manager
+				.import_playlist("Imported", TEST_USER, format, content.as_bytes())
+				.await
+				.unwrap();
+			assert_eq!(report.num_songs, 13);
+			assert!(report.unresolved.is_empty());
+
+			let imported = ctx
+				.playlist_manager
+				.read_playlist("Imported", TEST_USER)
+				.await
+				.unwrap();
+			assert_eq!(imported.songs, original.songs);
+		}
+	}
+
+	#[tokio::test]
+	async fn import_resolves_real_and_relative_paths() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+
+		let content = "test-data/small-collection/Khemmis/Hunted/01 - Above The Water.mp3\n\
+			..\\Khemmis\\Hunted\\02 - Candlelight.mp3\n\
+			root/Khemmis/Hunted/03 - Three Gates.mp3\n\
+			/elsewhere/song.mp3\n\
+			https://example.com/song.mp3\n";
+
+		let report = ctx
+			.playlist_manager
+			.import_playlist(
+				TEST_PLAYLIST_NAME,
+				TEST_USER,
+				Format::M3U,
+				content.as_bytes(),
+			)
+			.await
+			.unwrap();
+		assert_eq!(report.num_songs, 3);
+		assert_eq!(
+			report.unresolved,
+			vec!["/elsewhere/song.mp3", "https://example.com/song.mp3"]
+		);
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		let expected: Vec<PathBuf> = [
+			"01 - Above The Water.mp3",
+			"02 - Candlelight.mp3",
+			"03 - Three Gates.mp3",
+		]
+		.iter()
+		.map(|f| [TEST_MOUNT_NAME, "Khemmis", "Hunted", f].iter().collect())
+		.collect();
+		assert_eq!(playlist.songs, expected);
+	}
+
+	#[tokio::test]
+	async fn folder_playlists_are_listed_for_every_user() {
+		const OTHER_USER: &str = "other_user";
+
+		let builder = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.user(OTHER_USER, TEST_PASSWORD, false);
+
+		let collection_path = builder.test_directory.join("collection");
+		let album_path = collection_path.join("Khemmis").join("Hunted");
+		std::fs::create_dir_all(&album_path).unwrap();
+		for song in ["01 - Above The Water.mp3", "02 - Candlelight.mp3"] {
+			let source: PathBuf = ["test-data", "small-collection", "Khemmis", "Hunted", song]
+				.iter()
+				.collect();
+			std::fs::copy(source, album_path.join(song)).unwrap();
+		}
+		let playlist_path = collection_path.join("Khemmis").join("Favorites.m3u");
+		std::fs::write(
+			&playlist_path,
+			"Hunted/01 - Above The Water.mp3\nHunted/02 - Candlelight.mp3\n",
+		)
+		.unwrap();
+
+		let ctx = builder
+			.mount(TEST_MOUNT_NAME, collection_path.to_str().unwrap())
+			.build()
+			.await;
+		ctx.scanner.run_scan().await.unwrap();
+
+		let virtual_path: PathBuf = [TEST_MOUNT_NAME, "Khemmis", "Favorites.m3u"]
+			.iter()
+			.collect();
+		for user in [TEST_USER, OTHER_USER] {
+			let playlists = ctx.playlist_manager.list_playlists(user).await.unwrap();
+			assert_eq!(playlists.len(), 1);
+			assert_eq!(playlists[0].name, "Favorites");
+			assert_eq!(
+				playlists[0].source,
+				PlaylistSource::Folder(virtual_path.clone())
+			);
+			assert!(playlists[0].duration > Duration::ZERO);
+		}
+
+		let playlist = ctx
+			.playlist_manager
+			.read_folder_playlist(virtual_path.clone())
+			.await
+			.unwrap();
+		assert_eq!(playlist.songs.len(), 2);
+
+		// Playlist files are kept in sync with the collection
+		std::fs::remove_file(&playlist_path).unwrap();
+		ctx.scanner.run_scan().await.unwrap();
+
+		let playlists = ctx
+			.playlist_manager
+			.list_playlists(TEST_USER)
+			.await
+			.unwrap();
+		assert!(playlists.is_empty());
+		let result = ctx
+			.playlist_manager
+			.read_folder_playlist(virtual_path)
+			.await;
+		assert!(matches!(result, Err(Error::PlaylistNotFound)));
+	}
+
+	#[test]
+	fn only_edits_without_positions_are_retried() {
+		assert!(!PlaylistEdit::Append(vec![]).is_positional());
+		assert!(!PlaylistEdit::Dedupe.is_positional());
+		assert!(PlaylistEdit::Remove {
+			position: 3,
+			count: 1
+		}
+		.is_positional());
+		assert!(PlaylistEdit::Insert {
+			position: 0,
+			songs: vec![]
+		}
+		.is_positional());
+		assert!(PlaylistEdit::Move {
+			from: 0,
+			count: 1,
+			to: 2
+		}
+		.is_positional());
+	}
+
+	#[test]
+	fn can_apply_playlist_edits() {
+		let paths = |names: &[&str]| names.iter().map(PathBuf::from).collect::<Vec<_>>();
+		let apply = |edit: PlaylistEdit, songs: &[&str]| {
+			let mut songs = paths(songs);
+			edit.apply(&mut songs).map(|_| songs)
+		};
+
+		assert_eq!(
+			apply(PlaylistEdit::Append(paths(&["c"])), &["a", "b"]).unwrap(),
+			paths(&["a", "b", "c"])
+		);
+		assert_eq!(
+			apply(
+				PlaylistEdit::Insert {
+					position: 1,
+					songs: paths(&["x", "y"])
+				},
+				&["a", "b"]
+			)
+			.unwrap(),
+			paths(&["a", "x", "y", "b"])
+		);
+		assert_eq!(
+			apply(
+				PlaylistEdit::Remove {
+					position: 1,
+					count: 2
+				},
+				&["a", "b", "c", "d"]
+			)
+			.unwrap(),
+			paths(&["a", "d"])
+		);
+		assert_eq!(
+			apply(
+				PlaylistEdit::Move {
+					from: 0,
+					count: 2,
+					to: 2
+				},
+				&["a", "b", "c", "d"]
+			)
+			.unwrap(),
+			paths(&["c", "d", "a", "b"])
+		);
+		assert_eq!(
+			apply(
+				PlaylistEdit::Move {
+					from: 3,
+					count: 1,
+					to: 0
+				},
+				&["a", "b", "c", "d"]
+			)
+			.unwrap(),
+			paths(&["d", "a", "b", "c"])
+		);
+		assert_eq!(
+			apply(PlaylistEdit::Dedupe, &["a", "b", "a", "c", "b"]).unwrap(),
+			paths(&["a", "b", "c"])
+		);
+
+		for edit in [
+			PlaylistEdit::Insert {
+				position: 3,
+				songs: paths(&["x"]),
+			},
+			PlaylistEdit::Remove {
+				position: 1,
+				count: 2,
+			},
+			PlaylistEdit::Remove {
+				position: usize::MAX,
+				count: 1,
+			},
+			PlaylistEdit::Move {
+				from: 0,
+				count: 1,
+				to: 2,
+			},
+		] {
+			assert!(matches!(
+				apply(edit, &["a", "b"]),
+				Err(Error::PlaylistPositionOutOfRange)
+			));
+		}
+	}
+
+	#[tokio::test]
+	async fn can_edit_playlist() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+		let songs = list_all_songs(&ctx).await;
+		let paths = songs
+			.iter()
+			.map(|s| s.virtual_path.clone())
+			.collect::<Vec<_>>();
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, songs[0..2].to_vec())
+			.await
+			.unwrap();
+
+		let header = ctx
+			.playlist_manager
+			.edit_playlist(
+				TEST_PLAYLIST_NAME,
+				TEST_USER,
+				PlaylistEdit::Append(vec![
+					paths[2].clone(),
+					PathBuf::from("not_a_song.mp3"),
+					paths[0].clone(),
+				]),
+				Some(1),
+			)
+			.await
+			.unwrap();
+		assert_eq!(header.version, 2);
+		assert!(header.duration > Duration::ZERO);
+
+		let header = ctx
+			.playlist_manager
+			.edit_playlist(TEST_PLAYLIST_NAME, TEST_USER, PlaylistEdit::Dedupe, None)
+			.await
+			.unwrap();
+		assert_eq!(header.version, 3);
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert_eq!(playlist.songs, paths[0..3].to_vec());
+		assert_eq!(playlist.header.version, 3);
+	}
+
+	#[tokio::test]
+	async fn edit_playlist_rejects_outdated_version() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+		let songs = list_all_songs(&ctx).await;
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, songs.clone())
+			.await
+			.unwrap();
+
+		let remove_first = PlaylistEdit::Remove {
+			position: 0,
+			count: 1,
+		};
+
+		ctx.playlist_manager
+			.edit_playlist(TEST_PLAYLIST_NAME, TEST_USER, remove_first.clone(), Some(1))
+			.await
+			.unwrap();
+
+		let result = ctx
+			.playlist_manager
+			.edit_playlist(TEST_PLAYLIST_NAME, TEST_USER, remove_first, Some(1))
+			.await;
+		assert!(matches!(result, Err(Error::PlaylistVersionConflict(2))));
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert_eq!(playlist.songs.len(), songs.len() - 1);
+	}
+
+	#[tokio::test]
+	async fn cannot_edit_smart_playlist() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+
+		let smart_query = SmartQuery {
+			query: "khemmis".to_owned(),
+			..Default::default()
+		};
+		ctx.playlist_manager
+			.save_smart_playlist(TEST_PLAYLIST_NAME, TEST_USER, smart_query)
+			.await
+			.unwrap();
+
+		let result = ctx
+			.playlist_manager
+			.edit_playlist(TEST_PLAYLIST_NAME, TEST_USER, PlaylistEdit::Dedupe, None)
+			.await;
+		assert!(matches!(result, Err(Error::SmartPlaylistNotEditable)));
+	}
+
+	#[tokio::test]
+	async fn shared_playlists_are_listed_for_members() {
+		const MEMBER: &str = "member";
+		const STRANGER: &str = "stranger";
+
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.user(MEMBER, TEST_PASSWORD, false)
+			.user(STRANGER, TEST_PASSWORD, false)
+			.build()
+			.await;
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, Vec::new())
+			.await
+			.unwrap();
+
+		ctx.playlist_manager
+			.set_playlist_sharing(
+				TEST_PLAYLIST_NAME,
+				TEST_USER,
+				Sharing {
+					visibility: Visibility::SharedRead,
+					members: vec![MEMBER.to_owned()],
+				},
+			)
+			.await
+			.unwrap();
+
+		let playlists = ctx.playlist_manager.list_playlists(MEMBER).await.unwrap();
+		assert_eq!(playlists.len(), 1);
+		assert_eq!(playlists[0].owner.as_deref(), Some(TEST_USER));
+
+		let playlists = ctx.playlist_manager.list_playlists(STRANGER).await.unwrap();
+		assert!(playlists.is_empty());
+
+		// Saving the playlist again keeps it shared
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, Vec::new())
+			.await
+			.unwrap();
+		let playlists = ctx.playlist_manager.list_playlists(MEMBER).await.unwrap();
+		assert_eq!(playlists.len(), 1);
+
+		// Pins only apply to the owner
+		ctx.playlist_manager
+			.set_playlist_metadata(
+				TEST_PLAYLIST_NAME,
+				TEST_USER,
+				Metadata {
+					pinned: true,
+					..Default::default()
+				},
+			)
+			.await
+			.unwrap();
+		let playlists = ctx.playlist_manager.list_playlists(MEMBER).await.unwrap();
+		assert!(!playlists[0].metadata.pinned);
+
+		// Deleted users lose access, even if an account with the same name is created later
+		ctx.playlist_manager.remove_member(MEMBER).await.unwrap();
+		let playlists = ctx.playlist_manager.list_playlists(MEMBER).await.unwrap();
+		assert!(playlists.is_empty());
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(playlist.header.sharing.members.is_empty());
+
+		// Deleted playlists are no longer listed for their members
+		ctx.playlist_manager
+			.set_playlist_sharing(
+				TEST_PLAYLIST_NAME,
+				TEST_USER,
+				Sharing {
+					visibility: Visibility::SharedRead,
+					members: vec![STRANGER.to_owned()],
+				},
+			)
+			.await
+			.unwrap();
+		ctx.playlist_manager
+			.delete_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		let playlists = ctx.playlist_manager.list_playlists(STRANGER).await.unwrap();
+		assert!(playlists.is_empty());
+	}
+
+	#[tokio::test]
+	async fn authorize_respects_visibility() {
+		const MEMBER: &str = "member";
+		const STRANGER: &str = "stranger";
+
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.user(MEMBER, TEST_PASSWORD, false)
+			.user(STRANGER, TEST_PASSWORD, false)
+			.build()
+			.await;
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, Vec::new())
+			.await
+			.unwrap();
+
+		let authorize = |user: &'static str, access: Access| {
+			let playlist_manager = ctx.playlist_manager.clone();
+			async move {
+				playlist_manager
+					.authorize(TEST_PLAYLIST_NAME, TEST_USER, user, access)
+					.await
+			}
+		};
+
+		let set_visibility = |visibility: Visibility| {
+			let playlist_manager = ctx.playlist_manager.clone();
+			async move {
+				playlist_manager
+					.set_playlist_sharing(
+						TEST_PLAYLIST_NAME,
+						TEST_USER,
+						Sharing {
+							visibility,
+							members: vec![MEMBER.to_owned()],
+						},
+					)
+					.await
+					.unwrap();
+			}
+		};
+
+		assert!(authorize(TEST_USER, Access::Write).await.is_ok());
+		assert!(matches!(
+			authorize(MEMBER, Access::Read).await,
+			Err(Error::PlaylistNotFound)
+		));
+
+		set_visibility(Visibility::SharedRead).await;
+		assert!(authorize(MEMBER, Access::Read).await.is_ok());
+		assert!(matches!(
+			authorize(MEMBER, Access::Write).await,
+			Err(Error::PlaylistAccessDenied)
+		));
+		assert!(matches!(
+			authorize(STRANGER, Access::Read).await,
+			Err(Error::PlaylistNotFound)
+		));
+
+		set_visibility(Visibility::Collaborative).await;
+		assert!(authorize(MEMBER, Access::Write).await.is_ok());
+		assert!(matches!(
+			authorize(STRANGER, Access::Write).await,
+			Err(Error::PlaylistNotFound)
+		));
+	}
+
+	#[tokio::test]
+	async fn moved_songs_are_relinked_after_scan() {
+		let builder = test::ContextBuilder::new(test_name!()).user(TEST_USER, TEST_PASSWORD, false);
+
+		let collection_path = builder.test_directory.join("collection");
+		let album_path = collection_path.join("Khemmis").join("Hunted");
+		std::fs::create_dir_all(&album_path).unwrap();
+		for song in ["01 - Above The Water.mp3", "02 - Candlelight.mp3"] {
+			let source: PathBuf = ["test-data", "small-collection", "Khemmis", "Hunted", song]
+				.iter()
+				.collect();
+			std::fs::copy(source, album_path.join(song)).unwrap();
+		}
+
+		let ctx = builder
+			.mount(TEST_MOUNT_NAME, collection_path.to_str().unwrap())
+			.build()
+			.await;
+		ctx.scanner.run_scan().await.unwrap();
+
+		let songs = list_all_songs(&ctx).await;
+		assert_eq!(songs.len(), 2);
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, songs.clone())
+			.await
+			.unwrap();
+
+		// Move one song to another directory and delete the other one
+		let new_album_path = collection_path.join("Khemmis").join("Hunted (2016)");
+		std::fs::create_dir_all(&new_album_path).unwrap();
+		std::fs::rename(
+			album_path.join("01 - Above The Water.mp3"),
+			new_album_path.join("01 - Above The Water.mp3"),
+		)
+		.unwrap();
+		std::fs::remove_file(album_path.join("02 - Candlelight.mp3")).unwrap();
+		ctx.scanner.run_scan().await.unwrap();
+
+		let playlist = ctx
+			.playlist_manager
+			.read_playlist(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		let new_path: PathBuf = [
+			TEST_MOUNT_NAME,
+			"Khemmis",
+			"Hunted (2016)",
+			"01 - Above The Water.mp3",
+		]
+		.iter()
+		.collect();
+		assert_eq!(
+			playlist.songs,
+			vec![new_path, songs[1].virtual_path.clone()]
+		);
+		assert_eq!(playlist.header.version, 2);
+
+		let unresolved = ctx
+			.playlist_manager
+			.get_unresolved_songs(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert_eq!(unresolved, vec![songs[1].virtual_path.clone()]);
+
+		// Songs which reappear after a later scan are relinked too
+		let source: PathBuf = [
+			"test-data",
+			"small-collection",
+			"Khemmis",
+			"Hunted",
+			"02 - Candlelight.mp3",
+		]
+		.iter()
+		.collect();
+		std::fs::copy(source, new_album_path.join("02 - Candlelight.mp3")).unwrap();
+		ctx.scanner.run_scan().await.unwrap();
+
+		let unresolved = ctx
+			.playlist_manager
+			.get_unresolved_songs(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(unresolved.is_empty());
+	}
+
+	#[tokio::test]
+	async fn can_set_playlist_metadata() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.build()
+			.await;
+
+		for name in ["a", "b", "c"] {
+			ctx.playlist_manager
+				.save_playlist(name, TEST_USER, Vec::new())
+				.await
+				.unwrap();
+		}
+
+		ctx.playlist_manager
+			.set_playlist_metadata(
+				"c",
+				TEST_USER,
+				Metadata {
+					description: Some("  Songs for the road  ".to_owned()),
+					folder: Some("/Road Trips//Summer/ ".to_owned()),
+					pinned: true,
+				},
+			)
+			.await
+			.unwrap();
+
+		let playlists = ctx
+			.playlist_manager
+			.list_playlists(TEST_USER)
+			.await
+			.unwrap();
+
+		let names = playlists
+			.iter()
+			.map(|p| p.name.as_str())
+			.collect::<Vec<_>>();
+		assert_eq!(names, vec!["c", "a", "b"]);
+		assert_eq!(
+			playlists[0].metadata,
+			Metadata {
+				description: Some("Songs for the road".to_owned()),
+				folder: Some("Road Trips/Summer".to_owned()),
+				pinned: true,
+			}
+		);
+		assert_eq!(playlists[0].version, 2);
+	}
+
+	#[tokio::test]
+	async fn can_set_and_remove_playlist_cover() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.mount(TEST_MOUNT_NAME, "test-data/small-collection")
+			.build()
+			.await;
+
+		ctx.scanner.run_scan().await.unwrap();
+		let songs = list_all_songs(&ctx).await;
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, songs)
+			.await
+			.unwrap();
+
+		// Uploading the same image twice keeps the cover
+		let image = std::fs::read("test-data/artwork/Folder.png").unwrap();
+		for _ in 0..2 {
+			ctx.playlist_manager
+				.set_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER, image.clone())
+				.await
+				.unwrap();
+		}
+
+		let cover = ctx
+			.playlist_manager
+			.get_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		let Cover::Custom(cover_path) = cover else {
+			panic!("Unexpected cover: {cover:?}");
+		};
+		assert!(cover_path.exists());
+
+		ctx.playlist_manager
+			.remove_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(!cover_path.exists());
+
+		let cover = ctx
+			.playlist_manager
+			.get_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER)
+			.await
+			.unwrap();
+		assert!(matches!(cover, Cover::Artwork(_) | Cover::Mosaic(_)));
+	}
+
+	#[tokio::test]
+	async fn set_playlist_cover_rejects_invalid_image() {
+		let ctx = test::ContextBuilder::new(test_name!())
+			.user(TEST_USER, TEST_PASSWORD, false)
+			.build()
+			.await;
+
+		ctx.playlist_manager
+			.save_playlist(TEST_PLAYLIST_NAME, TEST_USER, Vec::new())
+			.await
+			.unwrap();
+
+		let result = ctx
+			.playlist_manager
+			.set_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER, b"not an image".to_vec())
+			.await;
+		assert!(matches!(result, Err(Error::PlaylistCoverDecoding(_))));
+
+		let result = ctx
+			.playlist_manager
+			.get_playlist_cover(TEST_PLAYLIST_NAME, TEST_USER)
+			.await;
+		assert!(matches!(result, Err(Error::PlaylistCoverNotFound)));
+	}
+
+	#[test]
+	fn v1_playlists_are_migrated() {
+		let playlist = super::v1::PlaylistModel {
+			owner: TEST_USER.to_owned(),
+			name: TEST_PLAYLIST_NAME.to_owned(),
+			virtual_paths: vec![PathBuf::from("root/song.mp3")],
+			..Default::default()
+		};
+
+		let encoded = native_model::encode(&playlist).unwrap();
+		let (playlist, _) = native_model::decode::<PlaylistModel>(encoded).unwrap();
+
+		assert_eq!(playlist.name, TEST_PLAYLIST_NAME);
+		assert_eq!(playlist.virtual_paths, vec![PathBuf::from("root/song.mp3")]);
+		assert_eq!(playlist.metadata, Metadata::default());
+		assert_eq!(playlist.cover, None);
+	}
 }
@@ -0,0 +1,422 @@
+use std::{borrow::Cow, collections::BTreeMap, path::Path, time::Duration};
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use quick_xml::{escape::escape, events::Event, Reader};
+
+use crate::app::Error;
+
+// Characters which can appear unescaped in the path component of a URI
+const PATH_ESCAPES: &AsciiSet = &NON_ALPHANUMERIC
+	.remove(b'-')
+	.remove(b'_')
+	.remove(b'.')
+	.remove(b'~')
+	.remove(b'/');
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Format {
+	M3U,
+	M3U8,
+	PLS,
+	XSPF,
+}
+
+impl Format {
+	pub fn from_extension(path: &Path) -> Option<Self> {
+		let extension = path.extension()?.to_str()?.to_lowercase();
+		match extension.as_str() {
+			"m3u" => Some(Format::M3U),
+			"m3u8" => Some(Format::M3U8),
+			"pls" => Some(Format::PLS),
+			"xspf" => Some(Format::XSPF),
+			_ => None,
+		}
+	}
+
+	pub fn mime_type(&self) -> &'static str {
+		match self {
+			Format::M3U => "audio/x-mpegurl",
+			Format::M3U8 => "audio/x-mpegurl; charset=utf-8",
+			Format::PLS => "audio/x-scpls",
+			Format::XSPF => "application/xspf+xml",
+		}
+	}
+}
+
+/// A song listed in a playlist file
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Entry {
+	/// Path or URL of the song, as written in the playlist file
+	pub location: String,
+	pub title: Option<String>,
+	pub duration: Option<Duration>,
+}
+
+/// Decodes the content of a playlist file. M3U files predating M3U8 are commonly Latin-1
+/// encoded, so content which is not valid UTF-8 is interpreted as Latin-1.
+pub fn decode(bytes: &[u8]) -> Cow<'_, str> {
+	let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+	match std::str::from_utf8(bytes) {
+		Ok(s) => Cow::Borrowed(s),
+		Err(_) => Cow::Owned(bytes.iter().map(|b| *b as char).collect()),
+	}
+}
+
+pub fn parse(format: Format, content: &str) -> Result<Vec<Entry>, Error> {
+	match format {
+		Format::M3U | Format::M3U8 => Ok(parse_m3u(content)),
+		Format::PLS => Ok(parse_pls(content)),
+		Format::XSPF => parse_xspf(content),
+	}
+}
+
+pub fn write(format: Format, name: &str, entries: &[Entry]) -> String {
+	match format {
+		Format::M3U | Format::M3U8 => write_m3u(entries),
+		Format::PLS => write_pls(entries),
+		Format::XSPF => write_xspf(name, entries),
+	}
+}
+
+/// Percent-encodes a path so it can be used in a URI. Paths are always written with `/`
+/// separators, regardless of the host platform.
+pub fn encode_path(path: &Path) -> String {
+	path.components()
+		.map(|c| utf8_percent_encode(&c.as_os_str().to_string_lossy(), PATH_ESCAPES).to_string())
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+/// Formats a path with `/` separators, regardless of the host platform
+pub fn portable_path(path: &Path) -> String {
+	path.components()
+		.map(|c| c.as_os_str().to_string_lossy())
+		.collect::<Vec<_>>()
+		.join("/")
+}
+
+pub fn decode_path(path: &str) -> String {
+	percent_decode_str(path).decode_utf8_lossy().into_owned()
+}
+
+/// Extracts the real path from a `file://` URL
+pub fn file_url_path(location: &str) -> Option<String> {
+	let path = decode_path(location.strip_prefix("file://")?);
+	// Windows paths are written as file:///C:/...
+	match path.strip_prefix('/') {
+		Some(p) if p.chars().nth(1) == Some(':') => Some(p.to_owned()),
+		_ => Some(path),
+	}
+}
+
+fn parse_m3u(content: &str) -> Vec<Entry> {
+	let mut entries = Vec::new();
+	let mut info = None;
+	for line in content.lines().map(str::trim) {
+		if let Some(extinf) = line.strip_prefix("#EXTINF:") {
+			info = Some(parse_extinf(extinf));
+		} else if !line.is_empty() && !line.starts_with('#') {
+			let (duration, title) = info.take().unwrap_or_default();
+			entries.push(Entry {
+				location: line.to_owned(),
+				title,
+				duration,
+			});
+		}
+	}
+	entries
+}
+
+// Parses the `123,Artist - Title` part of an `#EXTINF` line. Extended players may insert
+// attributes between the duration and the comma.
+fn parse_extinf(extinf: &str) -> (Option<Duration>, Option<String>) {
+	let (attributes, title) = extinf.split_once(',').unwrap_or((extinf, ""));
+	let duration = attributes
+		.split_whitespace()
+		.next()
+		.and_then(|d| d.parse::<u64>().ok())
+		.map(Duration::from_secs);
+	let title = Some(title.trim())
+		.filter(|t| !t.is_empty())
+		.map(str::to_owned);
+	(duration, title)
+}
+
+fn parse_pls(content: &str) -> Vec<Entry> {
+	let mut entries = BTreeMap::<u32, Entry>::new();
+	for line in content.lines() {
+		let Some((key, value)) = line.split_once('=') else {
+			continue;
+		};
+		let key = key.trim().to_ascii_lowercase();
+		let value = value.trim();
+		let (field, index) =
+			key.split_at(key.find(|c: char| c.is_ascii_digit()).unwrap_or(key.len()));
+		let Ok(index) = index.parse::<u32>() else {
+			continue;
+		};
+		let entry = entries.entry(index).or_default();
+		match field {
+			"file" => entry.location = value.to_owned(),
+			"title" => entry.title = Some(value.to_owned()).filter(|t| !t.is_empty()),
+			"length" => entry.duration = value.parse::<u64>().ok().map(Duration::from_secs),
+			_ => (),
+		}
+	}
+	entries
+		.into_values()
+		.filter(|e| !e.location.is_empty())
+		.collect()
+}
+
+fn parse_xspf(content: &str) -> Result<Vec<Entry>, Error> {
+	let mut reader = Reader::from_str(content);
+	reader.config_mut().trim_text(true);
+
+	let mut entries = Vec::new();
+	let mut track: Option<Entry> = None;
+	let mut element = Vec::new();
+	loop {
+		let text = match reader.read_event().map_err(Error::PlaylistXspfParse)? {
+			Event::Start(e) => {
+				element = e.local_name().as_ref().to_vec();
+				if element == b"track" {
+					track = Some(Entry::default());
+				}
+				continue;
+			}
+			Event::End(e) => {
+				if e.local_name().as_ref() == b"track" {
+					entries.extend(track.take().filter(|t| !t.location.is_empty()));
+				}
+				element.clear();
+				continue;
+			}
+			Event::Text(t) => t.unescape().map_err(Error::PlaylistXspfParse)?,
+			Event::CData(t) => Cow::Owned(String::from_utf8_lossy(&t.into_inner()).into_owned()),
+			Event::Eof => break,
+			_ => continue,
+		};
+
+		let Some(entry) = &mut track else {
+			continue;
+		};
+		match element.as_slice() {
+			// Locations without a scheme are relative URIs
+			b"location" if entry.location.is_empty() => {
+				entry.location = if text.contains("://") {
+					text.trim().to_owned()
+				} else {
+					decode_path(text.trim())
+				}
+			}
+			b"title" => entry.title = Some(text.into_owned()),
+			b"duration" => {
+				entry.duration = text.trim().parse::<u64>().ok().map(Duration::from_millis)
+			}
+			_ => (),
+		}
+	}
+
+	Ok(entries)
+}
+
+fn write_m3u(entries: &[Entry]) -> String {
+	let mut output = String::from("#EXTM3U\n");
+	for entry in entries {
+		let duration = entry.duration.map(|d| d.as_secs() as i64).unwrap_or(-1);
+		let title = entry.title.as_deref().unwrap_or_default();
+		output.push_str(&format!("#EXTINF:{duration},{title}\n"));
+		output.push_str(&entry.location);
+		output.push('\n');
+	}
+	output
+}
+
+fn write_pls(entries: &[Entry]) -> String {
+	let mut output = String::from("[playlist]\n");
+	for (i, entry) in entries.iter().enumerate() {
+		let n = i + 1;
+		output.push_str(&format!("File{n}={}\n", entry.location));
+		if let Some(title) = &entry.title {
+			output.push_str(&format!("Title{n}={title}\n"));
+		}
+		let duration = entry.duration.map(|d| d.as_secs() as i64).unwrap_or(-1);
+		output.push_str(&format!("Length{n}={duration}\n"));
+	}
+	output.push_str(&format!("NumberOfEntries={}\nVersion=2\n", entries.len()));
+	output
+}
+
+fn write_xspf(name: &str, entries: &[Entry]) -> String {
+	let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+	output.push_str("<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n");
+	output.push_str(&format!("\t<title>{}</title>\n", escape(name)));
+	output.push_str("\t<trackList>\n");
+	for entry in entries {
+		let location = if entry.location.contains("://") {
+			entry.location.clone()
+		} else {
+			encode_path(Path::new(&entry.location))
+		};
+		output.push_str("\t\t<track>\n");
+		output.push_str(&format!(
+			"\t\t\t<location>{}</location>\n",
+			escape(location)
+		));
+		if let Some(title) = &entry.title {
+			output.push_str(&format!("\t\t\t<title>{}</title>\n", escape(title)));
+		}
+		if let Some(duration) = entry.duration {
+			output.push_str(&format!(
+				"\t\t\t<duration>{}</duration>\n",
+				duration.as_millis()
+			));
+		}
+		output.push_str("\t\t</track>\n");
+	}
+	output.push_str("\t</trackList>\n</playlist>\n");
+	output
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn entry(location: &str, title: Option<&str>, duration: Option<u64>) -> Entry {
+		Entry {
+			location: location.to_owned(),
+			title: title.map(str::to_owned),
+			duration: duration.map(Duration::from_secs),
+		}
+	}
+
+	#[test]
+	fn can_parse_m3u() {
+		let content = "#EXTM3U\r\n\
+			#EXTINF:245,Khemmis - Above The Water\r\n\
+			Khemmis/Hunted/01 - Above The Water.mp3\r\n\
+			\r\n\
+			# A comment\r\n\
+			/music/Khemmis/Hunted/02 - Candlelight.mp3\r\n\
+			#EXTINF:-1 tvg-id=\"x\",Three Gates\r\n\
+			Khemmis/Hunted/03 - Three Gates.mp3\r\n";
+		assert_eq!(
+			parse(Format::M3U8, content).unwrap(),
+			vec![
+				entry(
+					"Khemmis/Hunted/01 - Above The Water.mp3",
+					Some("Khemmis - Above The Water"),
+					Some(245)
+				),
+				entry("/music/Khemmis/Hunted/02 - Candlelight.mp3", None, None),
+				entry(
+					"Khemmis/Hunted/03 - Three Gates.mp3",
+					Some("Three Gates"),
+					None
+				),
+			]
+		);
+	}
+
+	#[test]
+	fn can_parse_pls() {
+		let content = "[playlist]\n\
+			File2=Khemmis/Hunted/02 - Candlelight.mp3\n\
+			File1=Khemmis/Hunted/01 - Above The Water.mp3\n\
+			Title1=Above The Water\n\
+			Length1=245\n\
+			Length2=-1\n\
+			Title3=Orphan title\n\
+			NumberOfEntries=2\n\
+			Version=2\n";
+		assert_eq!(
+			parse(Format::PLS, content).unwrap(),
+			vec![
+				entry(
+					"Khemmis/Hunted/01 - Above The Water.mp3",
+					Some("Above The Water"),
+					Some(245)
+				),
+				entry("Khemmis/Hunted/02 - Candlelight.mp3", None, None),
+			]
+		);
+	}
+
+	#[test]
+	fn can_parse_xspf() {
+		let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+			<playlist version="1" xmlns="http://xspf.org/ns/0/">
+				<title>Doom</title>
+				<trackList>
+					<track>
+						<location>file:///music/Khemmis/Hunted/01%20-%20Above%20The%20Water.mp3</location>
+						<title>Above The Water &amp; More</title>
+						<duration>245000</duration>
+					</track>
+					<track>
+						<location>Khemmis/Hunted/02%20-%20Candlelight.mp3</location>
+					</track>
+					<track>
+						<title>No location</title>
+					</track>
+				</trackList>
+			</playlist>"#;
+		assert_eq!(
+			parse(Format::XSPF, content).unwrap(),
+			vec![
+				entry(
+					"file:///music/Khemmis/Hunted/01%20-%20Above%20The%20Water.mp3",
+					Some("Above The Water & More"),
+					Some(245)
+				),
+				entry("Khemmis/Hunted/02 - Candlelight.mp3", None, None),
+			]
+		);
+	}
+
+	#[test]
+	fn rejects_malformed_xspf() {
+		let content = "<playlist><trackList><track></trackList></playlist>";
+		assert!(parse(Format::XSPF, content).is_err());
+	}
+
+	#[test]
+	fn can_read_file_urls() {
+		assert_eq!(
+			file_url_path("file:///music/Mot%C3%B6rhead/Ace%20of%20Spades.mp3").as_deref(),
+			Some("/music/Motörhead/Ace of Spades.mp3")
+		);
+		assert_eq!(
+			file_url_path("file:///C:/Music/song.mp3").as_deref(),
+			Some("C:/Music/song.mp3")
+		);
+		assert_eq!(file_url_path("/music/song.mp3"), None);
+	}
+
+	#[test]
+	fn decodes_latin1() {
+		assert_eq!(decode(b"Mot\xF6rhead.mp3"), "Motörhead.mp3");
+		assert_eq!(decode("\u{FEFF}Motörhead.mp3".as_bytes()), "Motörhead.mp3");
+	}
+
+	#[test]
+	fn formats_round_trip() {
+		let entries = vec![
+			entry(
+				"root/Khemmis/Hunted/01 - Above The Water.mp3",
+				Some("Khemmis - Above The Water"),
+				Some(245),
+			),
+			entry(
+				"root/Tobokegao/Picnic/01 - ピクニック (Picnic).mp3",
+				None,
+				None,
+			),
+		];
+		for format in [Format::M3U, Format::M3U8, Format::PLS, Format::XSPF] {
+			let content = write(format, "Doom & Picnic", &entries);
+			assert_eq!(parse(format, &content).unwrap(), entries);
+		}
+	}
+}
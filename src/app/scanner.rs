@@ -3,7 +3,9 @@ use notify::{RecommendedWatcher, Watcher};
 use notify_debouncer_full::{Debouncer, FileIdMap};
 use rayon::{Scope, ThreadPoolBuilder};
 use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc::{channel, Sender, TryRecvError};
@@ -15,14 +17,35 @@ use tokio::sync::{Notify, RwLock};
 use tokio::task::JoinSet;
 use tokio::time::Instant;
 
-use crate::app::{config, formats, index, Error};
+use crate::app::{config, formats, index, peaks, playlist, Error};
 
-#[derive(Debug, PartialEq, Eq)]
+const IMAGE_FORMATS: [&str; 6] = ["bmp", "gif", "jpeg", "jpg", "png", "webp"];
+// Larger files are unlikely to be playlists and are not read into memory
+const MAX_PLAYLIST_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Default, PartialEq, Eq)]
 pub struct Directory {
 	pub virtual_path: PathBuf,
+	pub playlists: Vec<Playlist>,
+	pub images: Vec<Image>,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+/// Playlist file found within a directory
+#[derive(Debug, PartialEq, Eq)]
+pub struct Playlist {
+	pub virtual_path: PathBuf,
+	pub songs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Image {
+	pub path: PathBuf,
+	pub picture_type: formats::PictureType,
+	// Position of the picture within the tags of an audio file, `None` for image files
+	pub picture_index: Option<u32>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Hash)]
 pub struct Song {
 	pub real_path: PathBuf,
 	pub virtual_path: PathBuf,
@@ -34,12 +57,17 @@ pub struct Song {
 	pub year: Option<i64>,
 	pub album: Option<String>,
 	pub artwork: Option<PathBuf>,
+	pub artist_artwork: Option<PathBuf>,
+	pub images: Vec<Image>,
 	pub duration: Option<i64>,
 	pub lyricists: Vec<String>,
 	pub composers: Vec<String>,
 	pub genres: Vec<String>,
 	pub labels: Vec<String>,
+	pub compilation: bool,
+	pub size: u64,
 	pub date_added: i64,
+	pub fingerprint: Option<peaks::Fingerprint>,
 }
 
 #[derive(Clone, Default)]
@@ -54,6 +82,11 @@ pub enum State {
 #[derive(Clone)]
 struct Parameters {
 	artwork_regex: Option<Regex>,
+	artist_artwork_regex: Option<Regex>,
+	compilation_artists: Vec<String>,
+	duplicate_detection: config::storage::DuplicateDetection,
+	genres: Vec<config::storage::Genre>,
+	transliterations: HashMap<char, String>,
 	mount_dirs: Vec<config::MountDir>,
 }
 
@@ -61,6 +94,12 @@ impl PartialEq for Parameters {
 	fn eq(&self, other: &Self) -> bool {
 		self.artwork_regex.as_ref().map(|r| r.as_str())
 			== other.artwork_regex.as_ref().map(|r| r.as_str())
+			&& self.artist_artwork_regex.as_ref().map(|r| r.as_str())
+				== other.artist_artwork_regex.as_ref().map(|r| r.as_str())
+			&& self.compilation_artists == other.compilation_artists
+			&& self.duplicate_detection == other.duplicate_detection
+			&& self.genres == other.genres
+			&& self.transliterations == other.transliterations
 			&& self.mount_dirs == other.mount_dirs
 	}
 }
@@ -77,6 +116,8 @@ pub struct Status {
 pub struct Scanner {
 	index_manager: index::Manager,
 	config_manager: config::Manager,
+	playlist_manager: playlist::Manager,
+	peaks_manager: peaks::Manager,
 	file_watcher: Arc<RwLock<Option<Debouncer<RecommendedWatcher, FileIdMap>>>>,
 	on_file_change: Arc<Notify>,
 	pending_scan: Arc<Notify>,
@@ -88,10 +129,14 @@ impl Scanner {
 	pub async fn new(
 		index_manager: index::Manager,
 		config_manager: config::Manager,
+		playlist_manager: playlist::Manager,
+		peaks_manager: peaks::Manager,
 	) -> Result<Self, Error> {
 		let scanner = Self {
 			index_manager,
 			config_manager: config_manager.clone(),
+			playlist_manager,
+			peaks_manager,
 			file_watcher: Arc::default(),
 			on_file_change: Arc::default(),
 			pending_scan: Arc::new(Notify::new()),
@@ -180,8 +225,15 @@ impl Scanner {
 	async fn read_parameters(&self) -> Parameters {
 		let album_art_pattern = self.config_manager.get_index_album_art_pattern().await;
 		let artwork_regex = Regex::new(&format!("(?i){}", &album_art_pattern)).ok();
+		let artist_art_pattern = self.config_manager.get_index_artist_art_pattern().await;
+		let artist_artwork_regex = Regex::new(&format!("(?i){}", &artist_art_pattern)).ok();
 		Parameters {
 			artwork_regex,
+			artist_artwork_regex,
+			compilation_artists: self.config_manager.get_compilation_artists().await,
+			duplicate_detection: self.config_manager.get_duplicate_detection().await,
+			genres: self.config_manager.get_genres().await,
+			transliterations: self.config_manager.get_transliterations().await,
 			mount_dirs: self.config_manager.get_mounts().await,
 		}
 	}
@@ -214,10 +266,18 @@ impl Scanner {
 
 		let new_parameters = self.read_parameters().await;
 		*self.parameters.write().await = Some(new_parameters.clone());
+		let compilation_artists = new_parameters.compilation_artists.clone();
+		let genres = new_parameters.genres.clone();
+		let transliterations = new_parameters.transliterations.clone();
 
 		let (scan_directories_output, collection_directories_input) = channel();
 		let (scan_songs_output, collection_songs_input) = channel();
-		let scan = Scan::new(scan_directories_output, scan_songs_output, new_parameters);
+		let scan = Scan::new(
+			scan_directories_output,
+			scan_songs_output,
+			new_parameters,
+			self.peaks_manager.clone(),
+		);
 
 		let mut scan_task_set = JoinSet::new();
 		let mut index_task_set = JoinSet::new();
@@ -272,6 +332,9 @@ impl Scanner {
 
 		index_task_set.spawn_blocking(move || {
 			let mut index_builder = index::Builder::default();
+			index_builder.set_compilation_artists(compilation_artists);
+			index_builder.set_transliterations(transliterations);
+			index_builder.set_genres(genres);
 			let mut num_songs_scanned = 0;
 
 			loop {
@@ -320,7 +383,16 @@ impl Scanner {
 		secondary_task_set.abort_all();
 
 		self.index_manager.persist_index(&index).await?;
-		self.index_manager.replace_index(index).await;
+		let previous_index = self.index_manager.replace_index(index).await;
+
+		// Playlists may reference songs which were moved or renamed during this scan
+		if let Err(e) = self.playlist_manager.relink_playlists(previous_index).await {
+			error!("Error while relinking playlists: {e}");
+		}
+
+		if let Err(e) = self.playlist_manager.refresh_smart_playlists().await {
+			error!("Error while refreshing smart playlists: {e}");
+		}
 
 		{
 			let mut status = self.status.write().await;
@@ -341,6 +413,7 @@ struct Scan {
 	directories_output: Sender<Directory>,
 	songs_output: Sender<Song>,
 	parameters: Parameters,
+	peaks_manager: peaks::Manager,
 }
 
 impl Scan {
@@ -348,11 +421,13 @@ impl Scan {
 		directories_output: Sender<Directory>,
 		songs_output: Sender<Song>,
 		parameters: Parameters,
+		peaks_manager: peaks::Manager,
 	) -> Self {
 		Self {
 			directories_output,
 			songs_output,
 			parameters,
+			peaks_manager,
 		}
 	}
 
@@ -366,7 +441,16 @@ impl Scan {
 
 		let directories_output = self.directories_output.clone();
 		let songs_output = self.songs_output.clone();
-		let artwork_regex = self.parameters.artwork_regex.clone();
+		let options = ReadOptions {
+			artwork_regex: self.parameters.artwork_regex.clone(),
+			artist_artwork_regex: self.parameters.artist_artwork_regex.clone(),
+			artist_artwork: None,
+			compilation_artist: self.parameters.compilation_artists.first().cloned(),
+			fingerprints: (self.parameters.duplicate_detection
+				== config::storage::DuplicateDetection::Fingerprint)
+				.then(|| self.peaks_manager.clone()),
+			mount_dirs: self.parameters.mount_dirs.clone(),
+		};
 
 		let thread_pool = ThreadPoolBuilder::new().num_threads(num_threads).build()?;
 		thread_pool.scope({
@@ -379,7 +463,7 @@ impl Scan {
 							mount.name,
 							directories_output.clone(),
 							songs_output.clone(),
-							artwork_regex.clone(),
+							options.clone(),
 						);
 					});
 				}
@@ -390,13 +474,25 @@ impl Scan {
 	}
 }
 
+#[derive(Clone)]
+struct ReadOptions {
+	artwork_regex: Option<Regex>,
+	artist_artwork_regex: Option<Regex>,
+	// Artist image found in the parent directory
+	artist_artwork: Option<PathBuf>,
+	compilation_artist: Option<String>,
+	// Source of audio fingerprints, when fingerprints are used to detect duplicates
+	fingerprints: Option<peaks::Manager>,
+	mount_dirs: Vec<config::MountDir>,
+}
+
 fn process_directory<P: AsRef<Path>, Q: AsRef<Path>>(
 	scope: &Scope,
 	real_path: P,
 	virtual_path: Q,
 	directories_output: Sender<Directory>,
 	songs_output: Sender<Song>,
-	artwork_regex: Option<Regex>,
+	options: ReadOptions,
 ) {
 	let read_dir = match fs::read_dir(&real_path) {
 		Ok(read_dir) => read_dir,
@@ -411,7 +507,10 @@ fn process_directory<P: AsRef<Path>, Q: AsRef<Path>>(
 	};
 
 	let mut songs = vec![];
-	let mut artwork_file = None;
+	let mut subdirectories = vec![];
+	let mut images = vec![];
+	let mut playlists = vec![];
+	let mut artist_artwork_files = vec![];
 
 	for entry in read_dir {
 		let entry = match entry {
@@ -442,22 +541,33 @@ fn process_directory<P: AsRef<Path>, Q: AsRef<Path>>(
 		let entry_virtual_path = virtual_path.as_ref().join(&name);
 
 		if is_dir {
-			scope.spawn({
-				let directories_output = directories_output.clone();
-				let songs_output = songs_output.clone();
-				let artwork_regex = artwork_regex.clone();
-				|scope| {
-					process_directory(
-						scope,
-						entry_real_path,
-						entry_virtual_path,
-						directories_output,
-						songs_output,
-						artwork_regex,
-					);
-				}
+			subdirectories.push((entry_real_path, entry_virtual_path));
+		} else if let Some(format) = playlist::format::Format::from_extension(&entry_real_path) {
+			playlists.extend(read_playlist(
+				&entry_real_path,
+				entry_virtual_path,
+				format,
+				&options.mount_dirs,
+			));
+		} else if let Some(mut metadata) = formats::read_metadata(&entry_real_path) {
+			// Flagged compilations without an album artist are grouped under the compilation artist
+			if metadata.compilation && metadata.album_artists.is_empty() {
+				metadata
+					.album_artists
+					.extend(options.compilation_artist.clone());
+			}
+			let fingerprint = options.fingerprints.as_ref().and_then(|peaks_manager| {
+				peaks_manager
+					.get_fingerprint(&entry_real_path)
+					.inspect_err(|e| {
+						error!(
+							"Could not fingerprint `{}`: {}",
+							entry_real_path.display(),
+							e
+						)
+					})
+					.ok()
 			});
-		} else if let Some(metadata) = formats::read_metadata(&entry_real_path) {
 			songs.push(Song {
 				real_path: entry_real_path.clone(),
 				virtual_path: entry_virtual_path.clone(),
@@ -468,35 +578,213 @@ fn process_directory<P: AsRef<Path>, Q: AsRef<Path>>(
 				album_artists: metadata.album_artists,
 				year: metadata.year.map(|n| n as i64),
 				album: metadata.album,
-				artwork: metadata.has_artwork.then(|| entry_virtual_path.clone()),
+				artwork: (!metadata.pictures.is_empty()).then(|| entry_virtual_path.clone()),
+				artist_artwork: None,
+				images: metadata
+					.pictures
+					.iter()
+					.enumerate()
+					.map(|(i, picture_type)| Image {
+						path: entry_virtual_path.clone(),
+						picture_type: *picture_type,
+						picture_index: Some(i as u32),
+					})
+					.collect(),
 				duration: metadata.duration.map(|n| n as i64),
 				lyricists: metadata.lyricists,
 				composers: metadata.composers,
 				genres: metadata.genres,
 				labels: metadata.labels,
+				compilation: metadata.compilation,
+				size: get_file_size(&entry_real_path).unwrap_or_default(),
 				date_added: get_date_created(&entry_real_path).unwrap_or_default(),
+				fingerprint,
 			});
-		} else if artwork_file.is_none()
-			&& artwork_regex
-				.as_ref()
-				.is_some_and(|r| r.is_match(name.to_str().unwrap_or_default()))
+		} else if options
+			.artist_artwork_regex
+			.as_ref()
+			.is_some_and(|r| r.is_match(name.to_str().unwrap_or_default()))
 		{
-			artwork_file = Some(entry_virtual_path);
+			artist_artwork_files.push(entry_virtual_path);
+		} else {
+			let file_name = name.to_str().unwrap_or_default();
+			let is_artwork = options
+				.artwork_regex
+				.as_ref()
+				.is_some_and(|r| r.is_match(file_name));
+			if is_artwork || is_image(&entry_real_path) {
+				images.push((is_artwork, entry_virtual_path));
+			}
 		}
 	}
 
+	// Album art is picked by name so that it does not depend on directory listing order,
+	// images matching the album art pattern come first
+	images.sort_by(|(a_artwork, a), (b_artwork, b)| b_artwork.cmp(a_artwork).then(a.cmp(b)));
+	let artwork_file = images
+		.iter()
+		.find(|(is_artwork, _)| *is_artwork)
+		.map(|(_, path)| path.clone());
+	let images = images
+		.into_iter()
+		.map(|(is_artwork, path)| Image {
+			picture_type: match is_artwork {
+				true => formats::PictureType::Front,
+				false => guess_picture_type(&path),
+			},
+			path,
+			picture_index: None,
+		})
+		.collect::<Vec<_>>();
+
+	// Like album art, the artist image is picked by name. Other matches are not album images.
+	artist_artwork_files.sort();
+	let artist_artwork_file = artist_artwork_files.into_iter().next();
+
+	// Artist images apply to songs next to them and in immediate subdirectories (eg. albums),
+	// but not further down: an image at the root of a mount does not depict every artist.
+	let artist_artwork = artist_artwork_file
+		.clone()
+		.or_else(|| options.artist_artwork.clone());
+	let options = ReadOptions {
+		artist_artwork: artist_artwork_file,
+		..options
+	};
+
+	for (entry_real_path, entry_virtual_path) in subdirectories {
+		scope.spawn({
+			let directories_output = directories_output.clone();
+			let songs_output = songs_output.clone();
+			let options = options.clone();
+			|scope| {
+				process_directory(
+					scope,
+					entry_real_path,
+					entry_virtual_path,
+					directories_output,
+					songs_output,
+					options,
+				);
+			}
+		});
+	}
+
 	for mut song in songs {
 		song.artwork = song.artwork.or_else(|| artwork_file.clone());
+		song.artist_artwork = artist_artwork.clone();
 		songs_output.send(song).ok();
 	}
 
+	playlists.sort_by(|a, b| a.virtual_path.cmp(&b.virtual_path));
+
 	directories_output
 		.send(Directory {
 			virtual_path: virtual_path.as_ref().to_owned(),
+			playlists,
+			images,
 		})
 		.ok();
 }
 
+fn read_playlist(
+	real_path: &Path,
+	virtual_path: PathBuf,
+	format: playlist::format::Format,
+	mount_dirs: &[config::MountDir],
+) -> Option<Playlist> {
+	let mut content = Vec::new();
+	fs::File::open(real_path)
+		.and_then(|f| f.take(MAX_PLAYLIST_FILE_SIZE + 1).read_to_end(&mut content))
+		.inspect_err(|e| error!("Could not read `{}`: {}", real_path.display(), e))
+		.ok()?;
+	if content.len() as u64 > MAX_PLAYLIST_FILE_SIZE {
+		error!(
+			"Skipped `{}` because it is larger than {MAX_PLAYLIST_FILE_SIZE} bytes",
+			real_path.display()
+		);
+		return None;
+	}
+	let entries = playlist::format::parse(format, &playlist::format::decode(&content))
+		.inspect_err(|e| error!("Could not parse `{}`: {}", real_path.display(), e))
+		.ok()?;
+	let directory = virtual_path.parent().unwrap_or(Path::new(""));
+	let songs = entries
+		.iter()
+		.filter_map(|e| resolve_playlist_entry(&e.location, directory, mount_dirs))
+		.collect();
+	Some(Playlist {
+		virtual_path,
+		songs,
+	})
+}
+
+// Virtual path of a song listed in a playlist file. Relative paths are relative to the
+// directory containing the playlist file.
+fn resolve_playlist_entry(
+	location: &str,
+	directory: &Path,
+	mount_dirs: &[config::MountDir],
+) -> Option<PathBuf> {
+	let location = match playlist::format::file_url_path(location) {
+		Some(path) => path,
+		None if location.contains("://") => return None,
+		None => location.to_owned(),
+	};
+
+	let is_absolute = Path::new(&location).has_root() || location.chars().nth(1) == Some(':');
+	if is_absolute {
+		return mount_dirs
+			.iter()
+			.find_map(|m| m.virtualize_path(Path::new(&location)));
+	}
+
+	let mut path = directory.to_owned();
+	for component in location.split(['/', '\\']) {
+		match component {
+			"" | "." => (),
+			".." => {
+				path.pop();
+			}
+			c => path.push(c),
+		}
+	}
+	Some(path)
+}
+
+fn is_image(path: &Path) -> bool {
+	path.extension()
+		.and_then(|e| e.to_str())
+		.map(|e| e.to_lowercase())
+		.is_some_and(|e| IMAGE_FORMATS.contains(&e.as_str()))
+}
+
+fn guess_picture_type(path: &Path) -> formats::PictureType {
+	let name = path
+		.file_stem()
+		.map(|s| s.to_string_lossy().to_lowercase())
+		.unwrap_or_default();
+	let has_word = |words: &[&str]| {
+		name.split(|c: char| !c.is_alphanumeric())
+			.map(|w| w.trim_end_matches(|c: char| c.is_ascii_digit()))
+			.any(|w| words.contains(&w))
+	};
+	if has_word(&["front", "cover", "folder"]) {
+		formats::PictureType::Front
+	} else if has_word(&["back", "rear"]) {
+		formats::PictureType::Back
+	} else if has_word(&["booklet", "inlay", "insert", "inside", "leaflet"]) {
+		formats::PictureType::Booklet
+	} else if has_word(&["cd", "disc", "disk", "media", "vinyl"]) {
+		formats::PictureType::Media
+	} else {
+		formats::PictureType::Other
+	}
+}
+
+fn get_file_size<P: AsRef<Path>>(path: P) -> Option<u64> {
+	fs::metadata(path).map(|m| m.len()).ok()
+}
+
 fn get_date_created<P: AsRef<Path>>(path: P) -> Option<i64> {
 	if let Ok(t) = fs::metadata(path).and_then(|m| m.created().or_else(|_| m.modified())) {
 		t.duration_since(std::time::UNIX_EPOCH)
@@ -512,6 +800,7 @@ mod test {
 	use std::path::PathBuf;
 
 	use crate::app::test::{self};
+	use crate::test::prepare_test_directory;
 	use crate::test_name;
 
 	use super::*;
@@ -522,13 +811,19 @@ mod test {
 		let (songs_sender, songs_receiver) = channel();
 		let parameters = Parameters {
 			artwork_regex: None,
+			artist_artwork_regex: None,
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
 			mount_dirs: vec![config::MountDir {
 				source: ["test-data", "small-collection"].iter().collect(),
 				name: "root".to_owned(),
 			}],
 		};
 
-		let scan = Scan::new(directories_sender, songs_sender, parameters);
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
 		scan.run().unwrap();
 
 		let directories = directories_receiver.iter().collect::<Vec<_>>();
@@ -544,13 +839,19 @@ mod test {
 		let (songs_sender, songs_receiver) = channel();
 		let parameters = Parameters {
 			artwork_regex: None,
+			artist_artwork_regex: None,
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
 			mount_dirs: vec![config::MountDir {
 				source: ["test-data", "small-collection"].iter().collect(),
 				name: "root".to_owned(),
 			}],
 		};
 
-		let scan = Scan::new(directories_sender, songs_sender, parameters);
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
 		scan.run().unwrap();
 
 		let songs = songs_receiver.iter().collect::<Vec<_>>();
@@ -569,13 +870,19 @@ mod test {
 			let (songs_sender, songs_receiver) = channel();
 			let parameters = Parameters {
 				artwork_regex: Some(Regex::new(pattern).unwrap()),
+				artist_artwork_regex: None,
+				compilation_artists: vec![],
+				duplicate_detection: Default::default(),
+				genres: vec![],
+				transliterations: HashMap::new(),
 				mount_dirs: vec![config::MountDir {
 					source: ["test-data", "small-collection"].iter().collect(),
 					name: "root".to_owned(),
 				}],
 			};
 
-			let scan = Scan::new(directories_sender, songs_sender, parameters);
+			let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+			let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
 			scan.run().unwrap();
 
 			let songs = songs_receiver.iter().collect::<Vec<_>>();
@@ -586,6 +893,205 @@ mod test {
 		}
 	}
 
+	#[tokio::test]
+	async fn scan_finds_artist_artwork() {
+		let artwork_path = PathBuf::from_iter(["root", "Khemmis", "Hunted", "Folder.jpg"]);
+		let (directories_sender, _) = channel();
+		let (songs_sender, songs_receiver) = channel();
+		let parameters = Parameters {
+			artwork_regex: None,
+			artist_artwork_regex: Some(Regex::new("Folder.jpg").unwrap()),
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
+			mount_dirs: vec![config::MountDir {
+				source: ["test-data", "small-collection"].iter().collect(),
+				name: "root".to_owned(),
+			}],
+		};
+
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
+		scan.run().unwrap();
+
+		let songs = songs_receiver.iter().collect::<Vec<_>>();
+		let khemmis_songs = songs
+			.iter()
+			.filter(|s| {
+				s.virtual_path
+					.starts_with(["root", "Khemmis"].iter().collect::<PathBuf>())
+			})
+			.collect::<Vec<_>>();
+		assert_eq!(khemmis_songs.len(), 5);
+		assert!(khemmis_songs
+			.iter()
+			.all(|s| s.artist_artwork.as_ref() == Some(&artwork_path)));
+		assert!(songs
+			.iter()
+			.filter(|s| !khemmis_songs.contains(s))
+			.all(|s| s.artist_artwork.is_none()));
+	}
+
+	#[tokio::test]
+	async fn artist_artwork_is_not_inherited_past_one_level() {
+		let collection_path = prepare_test_directory(test_name!());
+		let source_path: PathBuf = ["test-data", "small-collection", "Khemmis", "Hunted"]
+			.iter()
+			.collect();
+		for directory in [["Khemmis", "Hunted"], ["Other", "Album"]] {
+			let path = collection_path.join(directory[0]).join(directory[1]);
+			fs::create_dir_all(&path).unwrap();
+			fs::copy(
+				source_path.join("01 - Above The Water.mp3"),
+				path.join("01 - Above The Water.mp3"),
+			)
+			.unwrap();
+		}
+		fs::copy(
+			source_path.join("Folder.jpg"),
+			collection_path.join("artist.jpg"),
+		)
+		.unwrap();
+		fs::copy(
+			source_path.join("Folder.jpg"),
+			collection_path.join("Khemmis").join("artist.jpg"),
+		)
+		.unwrap();
+
+		let (directories_sender, _) = channel();
+		let (songs_sender, songs_receiver) = channel();
+		let parameters = Parameters {
+			artwork_regex: None,
+			artist_artwork_regex: Some(Regex::new("artist.jpg").unwrap()),
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
+			mount_dirs: vec![config::MountDir {
+				source: collection_path,
+				name: "root".to_owned(),
+			}],
+		};
+
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
+		scan.run().unwrap();
+
+		let songs = songs_receiver.iter().collect::<Vec<_>>();
+		assert_eq!(songs.len(), 2);
+		for song in songs {
+			let expected_artwork = match song
+				.virtual_path
+				.starts_with(["root", "Khemmis"].iter().collect::<PathBuf>())
+			{
+				true => Some(PathBuf::from_iter(["root", "Khemmis", "artist.jpg"])),
+				false => None,
+			};
+			assert_eq!(song.artist_artwork, expected_artwork);
+		}
+	}
+
+	#[tokio::test]
+	async fn artist_artwork_is_picked_by_name() {
+		let collection_path = prepare_test_directory(test_name!());
+		let source_path: PathBuf = ["test-data", "small-collection", "Khemmis", "Hunted"]
+			.iter()
+			.collect();
+		fs::copy(
+			source_path.join("01 - Above The Water.mp3"),
+			collection_path.join("01 - Above The Water.mp3"),
+		)
+		.unwrap();
+		for name in ["artist.png", "artist.jpg"] {
+			fs::copy(source_path.join("Folder.jpg"), collection_path.join(name)).unwrap();
+		}
+
+		let (directories_sender, directories_receiver) = channel();
+		let (songs_sender, songs_receiver) = channel();
+		let parameters = Parameters {
+			artwork_regex: None,
+			artist_artwork_regex: Some(Regex::new("artist.(jpg|png)").unwrap()),
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
+			mount_dirs: vec![config::MountDir {
+				source: collection_path,
+				name: "root".to_owned(),
+			}],
+		};
+
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
+		scan.run().unwrap();
+
+		let songs = songs_receiver.iter().collect::<Vec<_>>();
+		assert_eq!(songs.len(), 1);
+		assert_eq!(
+			songs[0].artist_artwork,
+			Some(PathBuf::from_iter(["root", "artist.jpg"]))
+		);
+		assert!(directories_receiver.iter().all(|d| d.images.is_empty()));
+	}
+
+	#[tokio::test]
+	async fn scan_finds_album_images() {
+		let (directories_sender, directories_receiver) = channel();
+		let (songs_sender, songs_receiver) = channel();
+		let parameters = Parameters {
+			artwork_regex: None,
+			artist_artwork_regex: None,
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
+			mount_dirs: vec![config::MountDir {
+				source: ["test-data", "small-collection"].iter().collect(),
+				name: "root".to_owned(),
+			}],
+		};
+
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
+		scan.run().unwrap();
+
+		let hunted = PathBuf::from_iter(["root", "Khemmis", "Hunted"]);
+		let folder_image = Image {
+			path: hunted.join("Folder.jpg"),
+			picture_type: formats::PictureType::Front,
+			picture_index: None,
+		};
+		let directories = directories_receiver.iter().collect::<Vec<_>>();
+		let directory = directories
+			.iter()
+			.find(|d| d.virtual_path == hunted)
+			.unwrap();
+		assert_eq!(directory.images, vec![folder_image]);
+
+		// Folder images are stored once per directory, not on every song
+		let songs = songs_receiver.iter().collect::<Vec<_>>();
+		assert!(songs
+			.iter()
+			.filter(|s| s.virtual_path.starts_with(&hunted))
+			.all(|s| s.images.iter().all(|i| i.picture_index.is_some())));
+	}
+
+	#[test]
+	fn guesses_picture_type_from_file_name() {
+		let cases = [
+			("Folder.jpg", formats::PictureType::Front),
+			("cover-front.png", formats::PictureType::Front),
+			("Back.jpg", formats::PictureType::Back),
+			("Booklet 03.jpg", formats::PictureType::Booklet),
+			("cd2.png", formats::PictureType::Media),
+			("discography.jpg", formats::PictureType::Other),
+		];
+		for (name, picture_type) in cases {
+			assert_eq!(guess_picture_type(Path::new(name)), picture_type);
+		}
+	}
+
 	#[tokio::test]
 	async fn scanner_reacts_to_config_changes() {
 		let ctx = test::ContextBuilder::new(test_name!()).build().await;
@@ -611,4 +1117,99 @@ mod test {
 		.await
 		.expect("Index did not populate");
 	}
+
+	#[tokio::test]
+	async fn scan_finds_playlist_files() {
+		let collection_path = prepare_test_directory(test_name!());
+		let artist_path = collection_path.join("Khemmis");
+		fs::create_dir_all(&artist_path).unwrap();
+		fs::write(
+			artist_path.join("Favorites.m3u8"),
+			"#EXTM3U\nHunted/05 - Hunted.mp3\n../Tobokegao/Picnic/01 - Picnic.mp3\n",
+		)
+		.unwrap();
+
+		let (directories_sender, directories_receiver) = channel();
+		let (songs_sender, _) = channel();
+		let parameters = Parameters {
+			artwork_regex: None,
+			artist_artwork_regex: None,
+			compilation_artists: vec![],
+			duplicate_detection: Default::default(),
+			genres: vec![],
+			transliterations: HashMap::new(),
+			mount_dirs: vec![config::MountDir {
+				source: collection_path,
+				name: "root".to_owned(),
+			}],
+		};
+
+		let peaks_manager = peaks::Manager::new(prepare_test_directory(test_name!()));
+		let scan = Scan::new(directories_sender, songs_sender, parameters, peaks_manager);
+		scan.run().unwrap();
+
+		let playlists = directories_receiver
+			.iter()
+			.flat_map(|d| d.playlists)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			playlists,
+			vec![Playlist {
+				virtual_path: ["root", "Khemmis", "Favorites.m3u8"].iter().collect(),
+				songs: vec![
+					["root", "Khemmis", "Hunted", "05 - Hunted.mp3"]
+						.iter()
+						.collect(),
+					["root", "Tobokegao", "Picnic", "01 - Picnic.mp3"]
+						.iter()
+						.collect(),
+				],
+			}]
+		);
+	}
+
+	#[test]
+	fn skips_oversized_playlist_files() {
+		let directory = prepare_test_directory(test_name!());
+		let real_path = directory.join("Huge.m3u");
+		fs::write(&real_path, vec![b'\n'; MAX_PLAYLIST_FILE_SIZE as usize + 1]).unwrap();
+
+		let playlist = read_playlist(
+			&real_path,
+			["root", "Huge.m3u"].iter().collect(),
+			playlist::format::Format::M3U,
+			&[],
+		);
+		assert_eq!(playlist, None);
+	}
+
+	#[test]
+	fn resolves_playlist_entries() {
+		let mount_dirs = vec![config::MountDir {
+			source: PathBuf::from("/music"),
+			name: "root".to_owned(),
+		}];
+		let directory: PathBuf = ["root", "Khemmis"].iter().collect();
+		let resolve = |location| resolve_playlist_entry(location, &directory, &mount_dirs);
+
+		let expected: PathBuf = ["root", "Khemmis", "Hunted", "05 - Hunted.mp3"]
+			.iter()
+			.collect();
+		assert_eq!(resolve("Hunted/05 - Hunted.mp3"), Some(expected.clone()));
+		assert_eq!(resolve("./Hunted\\05 - Hunted.mp3"), Some(expected.clone()));
+		assert_eq!(
+			resolve("../Khemmis/Hunted/05 - Hunted.mp3"),
+			Some(expected.clone())
+		);
+		assert_eq!(
+			resolve("/music/Khemmis/Hunted/05 - Hunted.mp3"),
+			Some(expected.clone())
+		);
+		assert_eq!(
+			resolve("file:///music/Khemmis/Hunted/05%20-%20Hunted.mp3"),
+			Some(expected)
+		);
+		assert_eq!(resolve("/elsewhere/song.mp3"), None);
+		assert_eq!(resolve("http://example.com/song.mp3"), None);
+	}
 }
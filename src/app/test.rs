@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use crate::app::config::storage::*;
-use crate::app::{auth, config, index, ndb, playlist, scanner};
+use crate::app::{auth, config, index, ndb, peaks, playlist, scanner};
 use crate::test::*;
 
 pub struct Context {
@@ -51,10 +51,20 @@ impl ContextBuilder {
 			.unwrap();
 		let ndb_manager = ndb::Manager::new(&self.test_directory).unwrap();
 		let index_manager = index::Manager::new(&self.test_directory).await.unwrap();
-		let scanner = scanner::Scanner::new(index_manager.clone(), config_manager.clone())
-			.await
-			.unwrap();
-		let playlist_manager = playlist::Manager::new(ndb_manager.clone());
+		let playlist_manager = playlist::Manager::new(
+			ndb_manager.clone(),
+			index_manager.clone(),
+			config_manager.clone(),
+			self.test_directory.join("playlist_covers"),
+		);
+		let scanner = scanner::Scanner::new(
+			index_manager.clone(),
+			config_manager.clone(),
+			playlist_manager.clone(),
+			peaks::Manager::new(self.test_directory.join("peaks")),
+		)
+		.await
+		.unwrap();
 
 		config_manager.apply_config(self.config).await.unwrap();
 
@@ -7,7 +7,7 @@ use image::codecs::jpeg::JpegEncoder;
 use image::{DynamicImage, GenericImage, GenericImageView, ImageBuffer};
 use tokio::task::spawn_blocking;
 
-use crate::app::Error;
+use crate::app::{formats::PictureType, Error};
 use crate::utils::{get_audio_format, AudioFormat};
 
 #[derive(Clone, Debug, Hash)]
@@ -15,6 +15,8 @@ pub struct Options {
 	pub max_dimension: Option<u32>,
 	pub resize_if_almost_square: bool,
 	pub pad_to_square: bool,
+	// Embedded picture to use for audio files. Defaults to the front cover.
+	pub picture_index: Option<u32>,
 }
 
 impl Default for Options {
@@ -23,6 +25,7 @@ impl Default for Options {
 			max_dimension: Some(400),
 			resize_if_almost_square: true,
 			pad_to_square: true,
+			picture_index: None,
 		}
 	}
 }
@@ -50,6 +53,35 @@ impl Manager {
 		}
 	}
 
+	/// Thumbnail showing four images laid out in a 2x2 grid
+	pub async fn get_mosaic(
+		&self,
+		image_paths: &[PathBuf; 4],
+		options: &Options,
+	) -> Result<PathBuf, Error> {
+		let path = {
+			let mut hasher = DefaultHasher::new();
+			image_paths.hash(&mut hasher);
+			options.hash(&mut hasher);
+			self.thumbnails_dir_path
+				.join(format!("mosaic-{}.jpg", hasher.finish()))
+		};
+
+		if let Ok(true) = tokio::fs::try_exists(&path).await {
+			return Ok(path);
+		}
+
+		let mosaic = spawn_blocking({
+			let image_paths = image_paths.clone();
+			let options = options.clone();
+			move || generate_mosaic(&image_paths, &options)
+		})
+		.await??;
+
+		self.write_thumbnail(mosaic, &path, &image_paths[0]).await?;
+		Ok(path)
+	}
+
 	fn get_thumbnail_path(&self, image_path: &Path, options: &Options) -> PathBuf {
 		let hash = Manager::hash(image_path, options);
 		let mut thumbnail_path = self.thumbnails_dir_path.clone();
@@ -77,12 +109,22 @@ impl Manager {
 		})
 		.await??;
 
+		let path = self.get_thumbnail_path(image_path, options);
+		self.write_thumbnail(thumbnail, &path, image_path).await?;
+		Ok(path)
+	}
+
+	async fn write_thumbnail(
+		&self,
+		thumbnail: DynamicImage,
+		path: &Path,
+		image_path: &Path,
+	) -> Result<(), Error> {
 		tokio::fs::create_dir_all(&self.thumbnails_dir_path)
 			.await
 			.map_err(|e| Error::Io(self.thumbnails_dir_path.clone(), e))?;
 
-		let path = self.get_thumbnail_path(image_path, options);
-		let out_file = tokio::fs::File::create(&path)
+		let out_file = tokio::fs::File::create(path)
 			.await
 			.map_err(|e| Error::Io(self.thumbnails_dir_path.clone(), e))?;
 
@@ -96,7 +138,7 @@ impl Manager {
 		.await?
 		.map_err(|e| Error::Image(image_path.to_owned(), e))?;
 
-		Ok(path)
+		Ok(())
 	}
 
 	fn hash(path: &Path, options: &Options) -> u64 {
@@ -108,7 +150,8 @@ impl Manager {
 }
 
 fn generate_thumbnail(image_path: &Path, options: &Options) -> Result<DynamicImage, Error> {
-	let source_image = DynamicImage::ImageRgb8(read(image_path)?.into_rgb8());
+	let source_image =
+		DynamicImage::ImageRgb8(read(image_path, options.picture_index)?.into_rgb8());
 	let (source_width, source_height) = source_image.dimensions();
 	let largest_dimension = cmp::max(source_width, source_height);
 	let out_dimension = cmp::min(
@@ -145,61 +188,107 @@ fn generate_thumbnail(image_path: &Path, options: &Options) -> Result<DynamicIma
 	Ok(final_image)
 }
 
-fn read(image_path: &Path) -> Result<DynamicImage, Error> {
+fn generate_mosaic(image_paths: &[PathBuf; 4], options: &Options) -> Result<DynamicImage, Error> {
+	let tile_options = Options {
+		max_dimension: options.max_dimension.map(|d| d / 2),
+		resize_if_almost_square: true,
+		pad_to_square: true,
+		..options.clone()
+	};
+	let tiles = image_paths
+		.iter()
+		.map(|p| generate_thumbnail(p, &tile_options))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	// Tiles can be smaller than requested when source images are small
+	let tile_dimension = tiles.iter().map(|t| t.width()).min().unwrap_or_default();
+	let mut mosaic = DynamicImage::new_rgb8(2 * tile_dimension, 2 * tile_dimension);
+	for (i, (tile, image_path)) in tiles.iter().zip(image_paths).enumerate() {
+		let tile = tile.thumbnail_exact(tile_dimension, tile_dimension);
+		let x = (i as u32 % 2) * tile_dimension;
+		let y = (i as u32 / 2) * tile_dimension;
+		mosaic
+			.copy_from(&tile, x, y)
+			.map_err(|e| Error::Image(image_path.to_owned(), e))?;
+	}
+
+	Ok(mosaic)
+}
+
+fn read(image_path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	match get_audio_format(image_path) {
-		Some(AudioFormat::AIFF) => read_aiff(image_path),
-		Some(AudioFormat::FLAC) => read_flac(image_path),
-		Some(AudioFormat::MP3) => read_mp3(image_path),
+		Some(AudioFormat::AIFF) => read_aiff(image_path, picture_index),
+		Some(AudioFormat::FLAC) => read_flac(image_path, picture_index),
+		Some(AudioFormat::MP3) => read_mp3(image_path, picture_index),
 		Some(AudioFormat::OGG) => read_vorbis(image_path),
 		Some(AudioFormat::OPUS) => read_opus(image_path),
-		Some(AudioFormat::WAVE) => read_wave(image_path),
+		Some(AudioFormat::WAVE) => read_wave(image_path, picture_index),
 		Some(AudioFormat::APE) | Some(AudioFormat::MPC) => read_ape(image_path),
-		Some(AudioFormat::MP4) | Some(AudioFormat::M4B) => read_mp4(image_path),
+		Some(AudioFormat::MP4) | Some(AudioFormat::M4B) => read_mp4(image_path, picture_index),
 		None => image::open(image_path).map_err(|e| Error::Image(image_path.to_owned(), e)),
 	}
 }
 
+// Picks the requested picture, or the front cover (falling back to the first picture)
+fn select_picture<T>(pictures: Vec<(PictureType, T)>, picture_index: Option<u32>) -> Option<T> {
+	let index = match picture_index {
+		Some(i) => i as usize,
+		None => pictures
+			.iter()
+			.position(|(t, _)| *t == PictureType::Front)
+			.unwrap_or_default(),
+	};
+	pictures.into_iter().nth(index).map(|(_, p)| p)
+}
+
 fn read_ape(_: &Path) -> Result<DynamicImage, Error> {
 	Err(Error::UnsupportedFormat("ape"))
 }
 
-fn read_flac(path: &Path) -> Result<DynamicImage, Error> {
+fn read_flac(path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	let tag =
 		metaflac::Tag::read_from_path(path).map_err(|e| Error::Metaflac(path.to_owned(), e))?;
-	if let Some(p) = tag.pictures().next() {
+	let pictures = tag.pictures().map(|p| (p.picture_type.into(), p)).collect();
+	if let Some(p) = select_picture(pictures, picture_index) {
 		return image::load_from_memory(&p.data).map_err(|e| Error::Image(path.to_owned(), e));
 	}
 	Err(Error::EmbeddedArtworkNotFound(path.to_owned()))
 }
 
-fn read_mp3(path: &Path) -> Result<DynamicImage, Error> {
+fn read_mp3(path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	let tag = id3::Tag::read_from_path(path).map_err(|e| Error::Id3(path.to_owned(), e))?;
-	read_id3(path, &tag)
+	read_id3(path, &tag, picture_index)
 }
 
-fn read_aiff(path: &Path) -> Result<DynamicImage, Error> {
+fn read_aiff(path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	let tag = id3::Tag::read_from_path(path).map_err(|e| Error::Id3(path.to_owned(), e))?;
-	read_id3(path, &tag)
+	read_id3(path, &tag, picture_index)
 }
 
-fn read_wave(path: &Path) -> Result<DynamicImage, Error> {
+fn read_wave(path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	let tag = id3::Tag::read_from_path(path).map_err(|e| Error::Id3(path.to_owned(), e))?;
-	read_id3(path, &tag)
+	read_id3(path, &tag, picture_index)
 }
 
-fn read_id3(path: &Path, tag: &id3::Tag) -> Result<DynamicImage, Error> {
-	tag.pictures()
-		.next()
+fn read_id3(
+	path: &Path,
+	tag: &id3::Tag,
+	picture_index: Option<u32>,
+) -> Result<DynamicImage, Error> {
+	let pictures = tag.pictures().map(|p| (p.picture_type.into(), p)).collect();
+	select_picture(pictures, picture_index)
 		.ok_or_else(|| Error::EmbeddedArtworkNotFound(path.to_owned()))
 		.and_then(|d| {
 			image::load_from_memory(&d.data).map_err(|e| Error::Image(path.to_owned(), e))
 		})
 }
 
-fn read_mp4(path: &Path) -> Result<DynamicImage, Error> {
+fn read_mp4(path: &Path, picture_index: Option<u32>) -> Result<DynamicImage, Error> {
 	let tag =
 		mp4ameta::Tag::read_from_path(path).map_err(|e| Error::Mp4aMeta(path.to_owned(), e))?;
-	tag.artwork()
+	// MP4 artwork is untyped, the first image is the cover
+	tag.artworks()
+		.nth(picture_index.unwrap_or_default() as usize)
 		.ok_or_else(|| Error::EmbeddedArtworkNotFound(path.to_owned()))
 		.and_then(|d| image::load_from_memory(d.data).map_err(|e| Error::Image(path.to_owned(), e)))
 }
@@ -226,49 +315,67 @@ mod test {
 			.unwrap()
 			.to_rgb8();
 
-		let folder_img = read(Path::new("test-data/artwork/Folder.png"))
+		let folder_img = read(Path::new("test-data/artwork/Folder.png"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(folder_img, ext_img);
 
-		let aiff_img = read(Path::new("test-data/artwork/sample.aif"))
+		let aiff_img = read(Path::new("test-data/artwork/sample.aif"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(aiff_img, embedded_img);
 
-		let ape_img = read(Path::new("test-data/artwork/sample.ape"))
+		let ape_img = read(Path::new("test-data/artwork/sample.ape"), None)
 			.map(|d| d.to_rgb8())
 			.ok();
 		assert_eq!(ape_img, None);
 
-		let flac_img = read(Path::new("test-data/artwork/sample.flac"))
+		let flac_img = read(Path::new("test-data/artwork/sample.flac"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(flac_img, embedded_img);
 
-		let mp3_img = read(Path::new("test-data/artwork/sample.mp3"))
+		let mp3_img = read(Path::new("test-data/artwork/sample.mp3"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(mp3_img, embedded_img);
 
-		let m4a_img = read(Path::new("test-data/artwork/sample.m4a"))
+		let m4a_img = read(Path::new("test-data/artwork/sample.m4a"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(m4a_img, embedded_img);
 
-		let ogg_img = read(Path::new("test-data/artwork/sample.ogg"))
+		let ogg_img = read(Path::new("test-data/artwork/sample.ogg"), None)
 			.map(|d| d.to_rgb8())
 			.ok();
 		assert_eq!(ogg_img, None);
 
-		let opus_img = read(Path::new("test-data/artwork/sample.opus"))
+		let opus_img = read(Path::new("test-data/artwork/sample.opus"), None)
 			.map(|d| d.to_rgb8())
 			.ok();
 		assert_eq!(opus_img, None);
 
-		let wave_img = read(Path::new("test-data/artwork/sample.wav"))
+		let wave_img = read(Path::new("test-data/artwork/sample.wav"), None)
 			.unwrap()
 			.to_rgb8();
 		assert_eq!(wave_img, embedded_img);
 	}
+
+	#[test]
+	fn can_generate_mosaic() {
+		let image_path = PathBuf::from("test-data/artwork/Folder.png");
+		let image_paths = [
+			image_path.clone(),
+			image_path.clone(),
+			image_path.clone(),
+			image_path,
+		];
+		let options = Options {
+			max_dimension: Some(200),
+			..Default::default()
+		};
+		// Source image is only 4x4 pixels
+		let mosaic = generate_mosaic(&image_paths, &options).unwrap();
+		assert_eq!(mosaic.dimensions(), (8, 8));
+	}
 }
@@ -1,7 +1,13 @@
-use std::path::PathBuf;
+use std::{
+	collections::hash_map::DefaultHasher,
+	hash::{Hash, Hasher},
+	path::PathBuf,
+};
 
 use axum::{
+	body::Bytes,
 	extract::{DefaultBodyLimit, Path, Query, State},
+	http::header,
 	response::{IntoResponse, Response},
 	routing::get,
 	Json,
@@ -37,6 +43,7 @@ pub fn router() -> OpenApiRouter<App> {
 		.routes(routes!(post_user))
 		.routes(routes!(delete_user, put_user))
 		.routes(routes!(get_users))
+		.routes(routes!(get_preferences, put_preferences))
 		// File browser
 		.routes(routes!(get_browse_root))
 		.routes(routes!(get_browse))
@@ -45,22 +52,54 @@ pub fn router() -> OpenApiRouter<App> {
 		// Semantic
 		.routes(routes!(get_albums))
 		.routes(routes!(get_recent_albums))
+		.routes(routes!(get_compilations))
 		.routes(routes!(get_random_albums))
 		.routes(routes!(get_artists))
 		.routes(routes!(get_artist))
 		.routes(routes!(get_album))
+		.routes(routes!(get_album_images))
+		.routes(routes!(get_stats))
+		.routes(routes!(get_detailed_stats))
+		.routes(routes!(get_health))
+		.routes(routes!(get_duplicates))
 		.routes(routes!(get_genres))
 		.routes(routes!(get_genre))
 		.routes(routes!(get_genre_albums))
 		.routes(routes!(get_genre_artists))
 		.routes(routes!(get_genre_songs))
+		.routes(routes!(get_labels))
+		.routes(routes!(get_label))
+		.routes(routes!(get_composers))
+		.routes(routes!(get_lyricists))
+		.routes(routes!(get_decades))
+		.routes(routes!(get_decade))
 		.route("/random", get(get_random_albums)) // Deprecated
 		.route("/recent", get(get_recent_albums)) // Deprecated
 		// Search
+		.routes(routes!(get_search_suggestions))
 		.routes(routes!(get_search))
+		.routes(routes!(get_search_all))
 		// Playlist management
 		.routes(routes!(get_playlists))
-		.routes(routes!(put_playlist, get_playlist, delete_playlist))
+		.routes(routes!(
+			put_playlist,
+			patch_playlist,
+			get_playlist,
+			delete_playlist
+		))
+		.routes(routes!(put_smart_playlist))
+		.routes(routes!(freeze_playlist))
+		.routes(routes!(put_playlist_sharing))
+		.routes(routes!(get_playlist_unresolved))
+		.routes(routes!(put_playlist_metadata))
+		.routes(routes!(
+			get_playlist_cover,
+			put_playlist_cover,
+			delete_playlist_cover
+		))
+		.routes(routes!(post_playlist_import))
+		.routes(routes!(get_playlist_export))
+		.routes(routes!(get_folder_playlist))
 		// Media
 		.routes(routes!(get_songs))
 		.routes(routes!(get_peaks))
@@ -134,6 +173,11 @@ async fn get_settings(
 			.await
 			.as_str()
 			.to_owned(),
+		artist_art_pattern: config_manager
+			.get_index_artist_art_pattern()
+			.await
+			.as_str()
+			.to_owned(),
 		ddns_update_url: config_manager
 			.get_ddns_update_url()
 			.await
@@ -168,6 +212,13 @@ async fn put_settings(
 		config_manager.set_index_album_art_pattern(regex).await?;
 	}
 
+	if let Some(pattern) = new_settings.artist_art_pattern {
+		let Ok(regex) = Regex::new(&pattern) else {
+			return Err(APIError::InvalidArtistArtPattern);
+		};
+		config_manager.set_index_artist_art_pattern(regex).await?;
+	}
+
 	if let Some(url_string) = new_settings.ddns_update_url {
 		let uri = match url_string.trim() {
 			"" => None,
@@ -277,6 +328,54 @@ async fn get_users(
 	Ok(Json(users))
 }
 
+#[utoipa::path(
+	get,
+	path = "/preferences",
+	tag = "User Management",
+	description = "Returns the preferences of the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = dto::Preferences),
+	)
+)]
+async fn get_preferences(
+	auth: Auth,
+	State(config_manager): State<config::Manager>,
+) -> Result<Json<dto::Preferences>, APIError> {
+	let user = config_manager.get_user(auth.get_username()).await?;
+	Ok(Json(user.into()))
+}
+
+#[utoipa::path(
+	put,
+	path = "/preferences",
+	tag = "User Management",
+	description = "Amends the preferences of the current user.\n\n`null` fields are left unchanged.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	request_body = dto::PreferencesUpdate,
+	responses(
+		(status = 200),
+	)
+)]
+async fn put_preferences(
+	auth: Auth,
+	State(config_manager): State<config::Manager>,
+	Json(preferences_update): Json<dto::PreferencesUpdate>,
+) -> Result<(), APIError> {
+	if let Some(collapse) = preferences_update.new_collapse_duplicates {
+		config_manager
+			.set_collapse_duplicates(auth.get_username(), collapse)
+			.await?;
+	}
+	Ok(())
+}
+
 #[utoipa::path(
 	post,
 	path = "/user",
@@ -361,6 +460,7 @@ async fn put_user(
 async fn delete_user(
 	admin_rights: AdminRights,
 	State(config_manager): State<config::Manager>,
+	State(playlist_manager): State<playlist::Manager>,
 	Path(name): Path<String>,
 ) -> Result<(), APIError> {
 	if let Some(auth) = &admin_rights.get_auth() {
@@ -369,6 +469,7 @@ async fn delete_user(
 		}
 	}
 	config_manager.delete_user(&name).await?;
+	playlist_manager.remove_member(&name).await?;
 	Ok(())
 }
 
@@ -430,6 +531,46 @@ fn index_files_to_response(files: Vec<index::File>, api_version: APIMajorVersion
 }
 
 const SONG_LIST_CAPACITY: usize = 200;
+const SEARCH_PLAYLISTS_LIMIT: usize = 5;
+const SEARCH_SUGGESTIONS_LIMIT: usize = 10;
+
+struct SearchCursor {
+	generation: u64,
+	// Hash of the parameters which produced the page, so a cursor cannot be reused for a different search
+	request: u64,
+	offset: usize,
+}
+
+fn hash_search_request(query: &str, order: index::SearchOrder, collapse_duplicates: bool) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	query.hash(&mut hasher);
+	order.hash(&mut hasher);
+	collapse_duplicates.hash(&mut hasher);
+	hasher.finish()
+}
+
+fn encode_search_cursor(cursor: &SearchCursor) -> String {
+	format!(
+		"{}.{:x}.{}",
+		cursor.generation, cursor.request, cursor.offset
+	)
+}
+
+fn decode_search_cursor(cursor: &str) -> Result<SearchCursor, APIError> {
+	let mut parts = cursor.split('.');
+	let (Some(generation), Some(request), Some(offset), None) =
+		(parts.next(), parts.next(), parts.next(), parts.next())
+	else {
+		return Err(APIError::InvalidSearchCursor);
+	};
+	Ok(SearchCursor {
+		generation: generation
+			.parse()
+			.map_err(|_| APIError::InvalidSearchCursor)?,
+		request: u64::from_str_radix(request, 16).map_err(|_| APIError::InvalidSearchCursor)?,
+		offset: offset.parse().map_err(|_| APIError::InvalidSearchCursor)?,
+	})
+}
 
 async fn make_song_list(paths: Vec<PathBuf>, index_manager: &index::Manager) -> dto::SongList {
 	let first_paths = paths.iter().take(SONG_LIST_CAPACITY).cloned().collect();
@@ -476,6 +617,13 @@ fn albums_to_response(albums: Vec<index::Album>, api_version: APIMajorVersion) -
 	}
 }
 
+async fn collapses_duplicates(auth: &Auth, config_manager: &config::Manager) -> bool {
+	config_manager
+		.get_user(auth.get_username())
+		.await
+		.is_ok_and(|u| u.collapses_duplicates())
+}
+
 #[utoipa::path(
 	get,
 	path = "/browse",
@@ -598,11 +746,12 @@ async fn get_flatten(
 	get,
 	path = "/albums",
 	tag = "Collection",
-	description = "Lists all albums in the music collection.",
+	description = "Lists albums in the music collection, with optional sorting and pagination.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
+	params(dto::ListingParameters),
 	responses(
 		(status = 200, body = Vec<dto::AlbumHeader>),
 	)
@@ -610,10 +759,11 @@ async fn get_flatten(
 async fn get_albums(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
+	Query(options): Query<dto::ListingParameters>,
 ) -> Result<Json<Vec<dto::AlbumHeader>>, APIError> {
 	Ok(Json(
 		index_manager
-			.get_albums()
+			.get_albums(options.into())
 			.await
 			.into_iter()
 			.map(|a| a.into())
@@ -625,11 +775,12 @@ async fn get_albums(
 	get,
 	path = "/artists",
 	tag = "Collection",
-	description = "Lists all artists in the music collection.",
+	description = "Lists artists in the music collection, with optional sorting and pagination.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
+	params(dto::ListingParameters),
 	responses(
 		(status = 200, body = Vec<dto::ArtistHeader>),
 	)
@@ -637,10 +788,11 @@ async fn get_albums(
 async fn get_artists(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
+	Query(options): Query<dto::ListingParameters>,
 ) -> Result<Json<Vec<dto::ArtistHeader>>, APIError> {
 	Ok(Json(
 		index_manager
-			.get_artists()
+			.get_artists(options.into())
 			.await
 			.into_iter()
 			.map(|a| a.into())
@@ -688,7 +840,8 @@ async fn get_artist(
 	)
 )]
 async fn get_album(
-	_auth: Auth,
+	auth: Auth,
+	State(config_manager): State<config::Manager>,
 	State(index_manager): State<index::Manager>,
 	Path((name, artists)): Path<(String, String)>,
 ) -> Result<Json<dto::Album>, APIError> {
@@ -696,7 +849,41 @@ async fn get_album(
 		.split(API_ARRAY_SEPARATOR)
 		.map(str::to_owned)
 		.collect::<Vec<_>>();
-	Ok(Json(index_manager.get_album(artists, name).await?.into()))
+	let mut album = index_manager.get_album(artists, name).await?;
+	if collapses_duplicates(&auth, &config_manager).await {
+		album.songs = index_manager.collapse_album_duplicates(album.songs).await;
+	}
+	Ok(Json(album.into()))
+}
+
+#[utoipa::path(
+	get,
+	path = "/album/{name}/by/{artists}/images",
+	tag = "Collection",
+	description = "Lists all images associated with an album: image files in its directories and pictures embedded in its songs. Front covers are listed first.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(
+		("name", example = "The Piano Sonatas"),
+		("artists", example = "Claude Frank", description = "Artists the album is attributed to, separated by unicode \\u{000C} characters."),
+	),
+	responses(
+		(status = 200, body = Vec<dto::AlbumImage>),
+	)
+)]
+async fn get_album_images(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	Path((name, artists)): Path<(String, String)>,
+) -> Result<Json<Vec<dto::AlbumImage>>, APIError> {
+	let artists = artists
+		.split(API_ARRAY_SEPARATOR)
+		.map(str::to_owned)
+		.collect::<Vec<_>>();
+	let images = index_manager.get_album_images(artists, name).await?;
+	Ok(Json(images.into_iter().map(|i| i.into()).collect()))
 }
 
 #[utoipa::path(
@@ -802,6 +989,91 @@ async fn get_recent_albums(
 	albums_to_response(albums, api_version)
 }
 
+#[utoipa::path(
+	get,
+	path = "/stats",
+	tag = "Collection",
+	description = "Returns statistics about the music collection.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = dto::CollectionStats),
+	)
+)]
+async fn get_stats(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+) -> Json<dto::CollectionStats> {
+	Json(index_manager.get_stats().await.into())
+}
+
+#[utoipa::path(
+	get,
+	path = "/stats/detailed",
+	tag = "Collection",
+	description = "Returns statistics about the music collection, including storage usage, missing metadata and recent additions.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = dto::DetailedCollectionStats),
+	)
+)]
+async fn get_detailed_stats(
+	_admin_rights: AdminRights,
+	State(index_manager): State<index::Manager>,
+) -> Json<dto::DetailedCollectionStats> {
+	Json(index_manager.get_stats().await.into())
+}
+
+#[utoipa::path(
+	get,
+	path = "/health",
+	tag = "Collection",
+	description = "Lists albums with tagging inconsistencies, along with the rules they fail.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(dto::HealthParameters),
+	responses(
+		(status = 200, body = Vec<dto::AlbumHealth>),
+	)
+)]
+async fn get_health(
+	_admin_rights: AdminRights,
+	State(index_manager): State<index::Manager>,
+	Query(options): Query<dto::HealthParameters>,
+) -> Json<Vec<dto::AlbumHealth>> {
+	let rule = options.rule.map(Into::into);
+	let report = index_manager.get_health(rule).await;
+	Json(report.into_iter().map(|h| h.into()).collect())
+}
+
+#[utoipa::path(
+	get,
+	path = "/duplicates",
+	tag = "Collection",
+	description = "Lists groups of songs which are copies of each other (eg. the same track in FLAC and MP3 formats). Songs within each group are listed from best to worst format.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = Vec<dto::DuplicateGroup>),
+	)
+)]
+async fn get_duplicates(
+	_admin_rights: AdminRights,
+	State(index_manager): State<index::Manager>,
+) -> Json<Vec<dto::DuplicateGroup>> {
+	let duplicates = index_manager.get_duplicates().await;
+	Json(duplicates.into_iter().map(|g| g.into()).collect())
+}
+
 #[utoipa::path(
 	get,
 	path = "/genres",
@@ -838,7 +1110,7 @@ async fn get_genres(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(("name", example = "Classical")),
+	params(("name", example = "Classical"), dto::GenreParameters),
 	responses(
 		(status = 200, body = Vec<dto::Genre>),
 	)
@@ -847,20 +1119,27 @@ async fn get_genre(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
 	Path(name): Path<String>,
+	Query(options): Query<dto::GenreParameters>,
 ) -> Result<Json<dto::Genre>, APIError> {
-	Ok(Json(index_manager.get_genre(name).await?.into()))
+	let include_subgenres = options.include_subgenres.unwrap_or(false);
+	let genre = index_manager.get_genre(name, include_subgenres).await?;
+	Ok(Json(genre.into()))
 }
 
 #[utoipa::path(
 	get,
 	path = "/genre/{name}/albums",
 	tag = "Collection",
-	description = "Returns all albums associated with a music genre.",
+	description = "Returns albums associated with a music genre, with optional sorting and pagination.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(("name", example = "Classical")),
+	params(
+		("name", example = "Classical"),
+		dto::GenreParameters,
+		dto::ListingParameters
+	),
 	responses(
 		(status = 200, body = Vec<dto::AlbumHeader>),
 	)
@@ -869,11 +1148,13 @@ async fn get_genre_albums(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
 	Path(name): Path<String>,
+	Query(genre_options): Query<dto::GenreParameters>,
+	Query(options): Query<dto::ListingParameters>,
 ) -> Result<Json<Vec<dto::AlbumHeader>>, APIError> {
+	let include_subgenres = genre_options.include_subgenres.unwrap_or(false);
 	let albums = index_manager
-		.get_genre(name)
+		.get_genre_albums(name, include_subgenres, options.into())
 		.await?
-		.albums
 		.into_iter()
 		.map(|a| a.into())
 		.collect();
@@ -884,12 +1165,16 @@ async fn get_genre_albums(
 	get,
 	path = "/genre/{name}/artists",
 	tag = "Collection",
-	description = "Returns all artists associated with a music genre.",
+	description = "Returns artists associated with a music genre, with optional sorting and pagination.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(("name", example = "Classical")),
+	params(
+		("name", example = "Classical"),
+		dto::GenreParameters,
+		dto::ListingParameters
+	),
 	responses(
 		(status = 200, body = Vec<dto::ArtistHeader>),
 	)
@@ -898,11 +1183,13 @@ async fn get_genre_artists(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
 	Path(name): Path<String>,
+	Query(genre_options): Query<dto::GenreParameters>,
+	Query(options): Query<dto::ListingParameters>,
 ) -> Result<Json<Vec<dto::ArtistHeader>>, APIError> {
+	let include_subgenres = genre_options.include_subgenres.unwrap_or(false);
 	let artists = index_manager
-		.get_genre(name)
+		.get_genre_artists(name, include_subgenres, options.into())
 		.await?
-		.artists
 		.into_iter()
 		.map(|a| a.into())
 		.collect();
@@ -918,7 +1205,7 @@ async fn get_genre_artists(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(("name", example = "Classical")),
+	params(("name", example = "Classical"), dto::GenreParameters),
 	responses(
 		(status = 200, body = dto::SongList),
 	)
@@ -927,8 +1214,13 @@ async fn get_genre_songs(
 	_auth: Auth,
 	State(index_manager): State<index::Manager>,
 	Path(name): Path<String>,
+	Query(options): Query<dto::GenreParameters>,
 ) -> Result<Json<dto::SongList>, APIError> {
-	let songs = index_manager.get_genre(name).await?.songs;
+	let include_subgenres = options.include_subgenres.unwrap_or(false);
+	let songs = index_manager
+		.get_genre(name, include_subgenres)
+		.await?
+		.songs;
 	let song_list = dto::SongList {
 		paths: songs.iter().map(|s| s.virtual_path.clone()).collect(),
 		first_songs: songs
@@ -942,150 +1234,882 @@ async fn get_genre_songs(
 
 #[utoipa::path(
 	get,
-	path = "/search/{*query}",
+	path = "/labels",
 	tag = "Collection",
-	description = "Returns songs matching a search query. The query syntax is documented in the search section of the Polaris web UI.",
+	description = "Lists all record labels in the collection.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(
-		("Accept-Version" = Option<i32>, Header, minimum = 7, maximum = 8),
-		("query", allow_reserved, example = "sonata && moonlight"),
-	),
 	responses(
-		(status = 200, body = dto::SongList),
+		(status = 200, body = Vec<dto::LabelHeader>),
 	)
 )]
-async fn get_search(
+async fn get_labels(
 	_auth: Auth,
-	api_version: APIMajorVersion,
 	State(index_manager): State<index::Manager>,
-	Path(query): Path<String>,
-) -> Response {
-	let songs = match index_manager.search(query).await {
-		Ok(f) => f,
-		Err(e) => return APIError::from(e).into_response(),
-	};
-
-	let song_list = dto::SongList {
-		paths: songs.iter().map(|s| s.virtual_path.clone()).collect(),
-		first_songs: songs
+) -> Result<Json<Vec<dto::LabelHeader>>, APIError> {
+	Ok(Json(
+		index_manager
+			.get_labels()
+			.await
 			.into_iter()
-			.take(SONG_LIST_CAPACITY)
-			.map(|s| s.into())
+			.map(|l| l.into())
 			.collect(),
-	};
-
-	match api_version {
-		APIMajorVersion::V7 => Json(
-			song_list
-				.paths
-				.iter()
-				.map(|p| dto::v7::CollectionFile::Song(p.into()))
-				.collect::<Vec<_>>(),
-		)
-		.into_response(),
-		APIMajorVersion::V8 => Json(song_list).into_response(),
-	}
+	))
 }
 
 #[utoipa::path(
 	get,
-	path = "/playlists",
-	tag = "Playlists",
-	description = "Lists playlists owned by the current user.",
+	path = "/labels/{name}",
+	tag = "Collection",
+	description = "Returns the albums and artists associated with a record label.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
+	params(("name", example = "Nuclear Blast")),
 	responses(
-		(status = 200, body = Vec<dto::PlaylistHeader>),
+		(status = 200, body = dto::Label),
 	)
 )]
-async fn get_playlists(
-	auth: Auth,
-	State(playlist_manager): State<playlist::Manager>,
-) -> Result<Json<Vec<dto::PlaylistHeader>>, APIError> {
-	let playlists = playlist_manager.list_playlists(auth.get_username()).await?;
-	let playlists = playlists.into_iter().map(|p| p.into()).collect();
-
-	Ok(Json(playlists))
+async fn get_label(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	Path(name): Path<String>,
+) -> Result<Json<dto::Label>, APIError> {
+	Ok(Json(index_manager.get_label(name).await?.into()))
 }
 
 #[utoipa::path(
-	put,
-	path = "/playlist/{name}",
-	tag = "Playlists",
-	description = "Creates or updates a playlist for the current user.",
+	get,
+	path = "/compilations",
+	tag = "Collection",
+	description = "Lists compilation albums, either flagged as such in their metadata or attributed to one of the configured compilation artists.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(("name", example = "Chill Jazz")),
-	request_body = dto::SavePlaylistInput,
+	params(dto::ListingParameters),
+	responses(
+		(status = 200, body = Vec<dto::AlbumHeader>),
+	)
 )]
-async fn put_playlist(
-	auth: Auth,
-	State(playlist_manager): State<playlist::Manager>,
+async fn get_compilations(
+	_auth: Auth,
 	State(index_manager): State<index::Manager>,
-	Path(name): Path<String>,
-	playlist: Json<dto::SavePlaylistInput>,
-) -> Result<(), APIError> {
-	let songs = index_manager
-		.get_songs(playlist.tracks.clone())
-		.await
-		.into_iter()
-		.filter_map(|s| s.ok())
-		.collect();
-	playlist_manager
-		.save_playlist(&name, auth.get_username(), songs)
-		.await?;
-	Ok(())
+	Query(options): Query<dto::ListingParameters>,
+) -> Result<Json<Vec<dto::AlbumHeader>>, APIError> {
+	Ok(Json(
+		index_manager
+			.get_compilations(options.into())
+			.await
+			.into_iter()
+			.map(|a| a.into())
+			.collect(),
+	))
 }
 
 #[utoipa::path(
 	get,
-	path = "/playlist/{name}",
-	tag = "Playlists",
-	description = "Retrieves a playlist owned by the current user.",
+	path = "/composers",
+	tag = "Collection",
+	description = "Lists artists credited as composer on at least one album.",
 	security(
 		("auth_token" = []),
 		("auth_query_param" = []),
 	),
-	params(
-		("Accept-Version" = Option<i32>, Header, minimum = 7, maximum = 8),
-		("name", example = "Chill Jazz"),
-	),
+	params(dto::ListingParameters),
 	responses(
-		(status = 200, body = dto::Playlist),
+		(status = 200, body = Vec<dto::ArtistHeader>),
 	)
 )]
-async fn get_playlist(
-	auth: Auth,
-	api_version: APIMajorVersion,
+async fn get_composers(
+	_auth: Auth,
 	State(index_manager): State<index::Manager>,
-	State(playlist_manager): State<playlist::Manager>,
-	Path(name): Path<String>,
-) -> Response {
-	let playlist = match playlist_manager
-		.read_playlist(&name, auth.get_username())
-		.await
-	{
-		Ok(s) => s,
-		Err(e) => return APIError::from(e).into_response(),
-	};
-
-	match api_version {
-		APIMajorVersion::V7 => Json(playlist.songs).into_response(),
-		APIMajorVersion::V8 => Json(dto::Playlist {
-			header: playlist.header.into(),
+	Query(options): Query<dto::ListingParameters>,
+) -> Result<Json<Vec<dto::ArtistHeader>>, APIError> {
+	Ok(Json(
+		index_manager
+			.get_composers(options.into())
+			.await
+			.into_iter()
+			.map(|a| a.into())
+			.collect(),
+	))
+}
+
+#[utoipa::path(
+	get,
+	path = "/lyricists",
+	tag = "Collection",
+	description = "Lists artists credited as lyricist on at least one album.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(dto::ListingParameters),
+	responses(
+		(status = 200, body = Vec<dto::ArtistHeader>),
+	)
+)]
+async fn get_lyricists(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	Query(options): Query<dto::ListingParameters>,
+) -> Result<Json<Vec<dto::ArtistHeader>>, APIError> {
+	Ok(Json(
+		index_manager
+			.get_lyricists(options.into())
+			.await
+			.into_iter()
+			.map(|a| a.into())
+			.collect(),
+	))
+}
+
+#[utoipa::path(
+	get,
+	path = "/decades",
+	tag = "Collection",
+	description = "Lists all decades with music in the collection.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = Vec<dto::DecadeHeader>),
+	)
+)]
+async fn get_decades(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+) -> Result<Json<Vec<dto::DecadeHeader>>, APIError> {
+	Ok(Json(
+		index_manager
+			.get_decades()
+			.await
+			.into_iter()
+			.map(|d| d.into())
+			.collect(),
+	))
+}
+
+#[utoipa::path(
+	get,
+	path = "/decades/{decade}",
+	tag = "Collection",
+	description = "Returns the albums and artists released during a decade.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("decade", example = "1990s")),
+	responses(
+		(status = 200, body = dto::Decade),
+		(status = 400),
+		(status = 404),
+	)
+)]
+async fn get_decade(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	Path(decade): Path<String>,
+) -> Result<Json<dto::Decade>, APIError> {
+	let decade = decade
+		.trim_end_matches('s')
+		.parse::<i64>()
+		.ok()
+		.filter(|d| d.rem_euclid(10) == 0)
+		.ok_or(APIError::InvalidDecade)?;
+	Ok(Json(index_manager.get_decade(decade).await?.into()))
+}
+
+#[utoipa::path(
+	get,
+	path = "/search_suggestions",
+	tag = "Collection",
+	description = "Returns completions for a partially typed search query: artist names, album names, song titles and search field keywords. Fast enough to be called on every keystroke.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(dto::SuggestParameters),
+	responses(
+		(status = 200, body = Vec<dto::Suggestion>),
+	)
+)]
+async fn get_search_suggestions(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	Query(options): Query<dto::SuggestParameters>,
+) -> Json<Vec<dto::Suggestion>> {
+	let count = options.count.unwrap_or(SEARCH_SUGGESTIONS_LIMIT);
+	let suggestions = index_manager.suggest(options.prefix, count).await;
+	Json(suggestions.into_iter().map(|s| s.into()).collect())
+}
+
+#[utoipa::path(
+	get,
+	path = "/search/{*query}",
+	tag = "Collection",
+	description = "Returns songs matching a search query. The query syntax is documented in the search section of the Polaris web UI.\n\nResults are sorted alphabetically unless `order` is set to `relevance`.\n\nLarge result sets can be fetched one page at a time by setting `limit`, and then passing the `next_cursor` value of each page as the `cursor` parameter of the next request. Cursors can only be used with the query and order that produced them, and expire when the content of the collection changes.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(
+		("Accept-Version" = Option<i32>, Header, minimum = 7, maximum = 8),
+		("query", allow_reserved, example = "sonata && moonlight"),
+		dto::SearchParameters,
+	),
+	responses(
+		(status = 200, body = dto::SearchResults),
+		(status = 400, body = dto::SearchQueryError),
+		(status = 410),
+	)
+)]
+async fn get_search(
+	auth: Auth,
+	api_version: APIMajorVersion,
+	State(config_manager): State<config::Manager>,
+	State(index_manager): State<index::Manager>,
+	Path(query): Path<String>,
+	Query(options): Query<dto::SearchParameters>,
+) -> Response {
+	if options.limit == Some(0) {
+		return APIError::InvalidSearchLimit.into_response();
+	}
+
+	let order = options.order.map(Into::into).unwrap_or_default();
+	let collapse_duplicates = collapses_duplicates(&auth, &config_manager).await;
+	let request = hash_search_request(&query, order, collapse_duplicates);
+	let cursor = match options
+		.cursor
+		.as_deref()
+		.map(decode_search_cursor)
+		.transpose()
+	{
+		Ok(c) => c,
+		Err(e) => return e.into_response(),
+	};
+	if cursor.as_ref().is_some_and(|c| c.request != request) {
+		return APIError::InvalidSearchCursor.into_response();
+	}
+	let offset = match &cursor {
+		Some(c) => c.offset,
+		None => options.offset.unwrap_or_default(),
+	};
+
+	let page = match index_manager
+		.search(query, order, collapse_duplicates, offset, options.limit)
+		.await
+	{
+		Ok(f) => f,
+		Err(e) => return APIError::from(e).into_response(),
+	};
+
+	if cursor.is_some_and(|c| c.generation != page.generation) {
+		return APIError::SearchCursorExpired.into_response();
+	}
+
+	let next_offset = offset + page.songs.len();
+	let next_cursor = (next_offset < page.total).then(|| {
+		encode_search_cursor(&SearchCursor {
+			generation: page.generation,
+			request,
+			offset: next_offset,
+		})
+	});
+
+	let scores = page.songs.iter().map(|r| r.score).collect();
+	let song_list = dto::SongList {
+		paths: page
+			.songs
+			.iter()
+			.map(|r| r.song.virtual_path.clone())
+			.collect(),
+		first_songs: page
+			.songs
+			.into_iter()
+			.take(SONG_LIST_CAPACITY)
+			.map(|r| r.song.into())
+			.collect(),
+	};
+
+	match api_version {
+		APIMajorVersion::V7 => Json(
+			song_list
+				.paths
+				.iter()
+				.map(|p| dto::v7::CollectionFile::Song(p.into()))
+				.collect::<Vec<_>>(),
+		)
+		.into_response(),
+		APIMajorVersion::V8 => Json(dto::SearchResults {
+			songs: song_list,
+			scores,
+			total: page.total,
+			next_cursor,
+		})
+		.into_response(),
+	}
+}
+
+#[utoipa::path(
+	get,
+	path = "/search_all/{*query}",
+	tag = "Collection",
+	description = "Returns artists, albums, genres and playlists whose name matches a search query, alongside the most relevant matching songs. Playlists are limited to those owned by the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(
+		("query", allow_reserved, example = "pink floyd"),
+		dto::SearchAllParameters,
+	),
+	responses(
+		(status = 200, body = dto::SearchAllResults),
+		(status = 400, body = dto::SearchQueryError),
+	)
+)]
+async fn get_search_all(
+	auth: Auth,
+	State(config_manager): State<config::Manager>,
+	State(index_manager): State<index::Manager>,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(query): Path<String>,
+	Query(options): Query<dto::SearchAllParameters>,
+) -> Result<Json<dto::SearchAllResults>, APIError> {
+	let playlists_limit = options.playlists.unwrap_or(SEARCH_PLAYLISTS_LIMIT);
+	let collapse_duplicates = collapses_duplicates(&auth, &config_manager).await;
+	let groups = index_manager
+		.search_all(query.clone(), options.into(), collapse_duplicates)
+		.await?;
+
+	// Shared and folder playlists are left out
+	let mut caller_playlists = playlist_manager
+		.list_playlists(auth.get_username())
+		.await?
+		.into_iter()
+		.filter(|p| p.owner.as_deref() == Some(auth.get_username()))
+		.map(Some)
+		.collect::<Vec<_>>();
+	let names = caller_playlists
+		.iter()
+		.flatten()
+		.map(|p| p.name.clone())
+		.collect();
+	let playlists = index_manager
+		.rank_names(query, names)
+		.await
+		.into_iter()
+		.take(playlists_limit)
+		.filter_map(|i| caller_playlists[i].take())
+		.map(|p| p.into())
+		.collect();
+
+	let scores = groups.songs.iter().map(|r| r.score).collect();
+	let songs = dto::SearchResults {
+		songs: dto::SongList {
+			paths: groups
+				.songs
+				.iter()
+				.map(|r| r.song.virtual_path.clone())
+				.collect(),
+			first_songs: groups.songs.into_iter().map(|r| r.song.into()).collect(),
+		},
+		scores,
+		total: groups.total_songs,
+		next_cursor: None,
+	};
+
+	Ok(Json(dto::SearchAllResults {
+		artists: groups.artists.into_iter().map(|a| a.into()).collect(),
+		albums: groups.albums.into_iter().map(|a| a.into()).collect(),
+		genres: groups.genres.into_iter().map(|g| g.into()).collect(),
+		playlists,
+		songs,
+	}))
+}
+
+#[utoipa::path(
+	get,
+	path = "/playlists",
+	tag = "Playlists",
+	description = "Lists playlists owned by or shared with the current user, including smart playlists, and read-only playlist files found in the collection.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	responses(
+		(status = 200, body = Vec<dto::PlaylistHeader>),
+	)
+)]
+async fn get_playlists(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+) -> Result<Json<Vec<dto::PlaylistHeader>>, APIError> {
+	let playlists = playlist_manager.list_playlists(auth.get_username()).await?;
+	let playlists = playlists.into_iter().map(|p| p.into()).collect();
+
+	Ok(Json(playlists))
+}
+
+#[utoipa::path(
+	put,
+	path = "/playlist/{name}",
+	tag = "Playlists",
+	description = "Creates or updates a playlist for the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz")),
+	request_body = dto::SavePlaylistInput,
+)]
+async fn put_playlist(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	State(index_manager): State<index::Manager>,
+	Path(name): Path<String>,
+	playlist: Json<dto::SavePlaylistInput>,
+) -> Result<(), APIError> {
+	let songs = index_manager
+		.get_songs(playlist.tracks.clone())
+		.await
+		.into_iter()
+		.filter_map(|s| s.ok())
+		.collect();
+	playlist_manager
+		.save_playlist(&name, auth.get_username(), songs)
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	patch,
+	path = "/playlist/{name}",
+	tag = "Playlists",
+	description = "Edits the songs of a playlist owned by the current user, or shared with them as a collaborative playlist, without replacing its whole content. Songs which are not in the collection are ignored.\n\nWhen a `version` is provided, the edit is rejected if the playlist was modified since that version. The current version of a playlist is listed in its header, and the updated header is returned after each edit.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz"), dto::PlaylistOwnerParameters),
+	request_body = dto::PlaylistEditInput,
+	responses(
+		(status = 200, body = dto::PlaylistHeader),
+		(status = 400),
+		(status = 403),
+		(status = 404),
+		(status = 409),
+	)
+)]
+async fn patch_playlist(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Query(options): Query<dto::PlaylistOwnerParameters>,
+	Json(edit): Json<dto::PlaylistEditInput>,
+) -> Result<Json<dto::PlaylistHeader>, APIError> {
+	let owner = options.owner.as_deref().unwrap_or(auth.get_username());
+	playlist_manager
+		.authorize(&name, owner, auth.get_username(), playlist::Access::Write)
+		.await?;
+	let header = playlist_manager
+		.edit_playlist(&name, owner, edit.operation.into(), edit.version)
+		.await?;
+	Ok(Json(header.into()))
+}
+
+#[utoipa::path(
+	put,
+	path = "/playlist/{name}/sharing",
+	tag = "Playlists",
+	description = "Sets which users can access a playlist owned by the current user. Members of `shared_read` playlists can read them, while members of `collaborative` playlists can also edit their songs.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Road Trip")),
+	request_body = dto::PlaylistSharing,
+	responses(
+		(status = 200),
+		(status = 404),
+	)
+)]
+async fn put_playlist_sharing(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Json(sharing): Json<dto::PlaylistSharing>,
+) -> Result<(), APIError> {
+	playlist_manager
+		.set_playlist_sharing(&name, auth.get_username(), sharing.into())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	get,
+	path = "/playlist/{name}/unresolved",
+	tag = "Playlists",
+	description = "Lists songs of a playlist owned by the current user which are no longer in the collection.\n\nSongs which are moved or renamed within the collection are automatically relinked after each scan, when their new location can be identified with confidence. Remaining entries can be removed or replaced by the playlist owner.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz")),
+	responses(
+		(status = 200, body = Vec<String>),
+		(status = 404),
+	)
+)]
+async fn get_playlist_unresolved(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+) -> Result<Json<Vec<PathBuf>>, APIError> {
+	let songs = playlist_manager
+		.get_unresolved_songs(&name, auth.get_username())
+		.await?;
+	Ok(Json(songs))
+}
+
+#[utoipa::path(
+	put,
+	path = "/playlist/{name}/metadata",
+	tag = "Playlists",
+	description = "Sets the description, folder and pinned status of a playlist owned by the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz")),
+	request_body = dto::PlaylistMetadata,
+	responses(
+		(status = 200),
+		(status = 404),
+	)
+)]
+async fn put_playlist_metadata(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Json(metadata): Json<dto::PlaylistMetadata>,
+) -> Result<(), APIError> {
+	playlist_manager
+		.set_playlist_metadata(&name, auth.get_username(), metadata.into())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	get,
+	path = "/playlist/{name}/cover",
+	tag = "Playlists",
+	description = "Serves the cover of a playlist owned by or shared with the current user. Playlists without an uploaded cover use album art from their songs, laid out as a 2x2 mosaic when the first songs come from at least four different albums.\n\nThis endpoint supports HTTP range requests to facilitate streaming.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(
+		("name", example = "Chill Jazz"),
+		dto::PlaylistOwnerParameters,
+		dto::ThumbnailOptions,
+	),
+	responses(
+		(status = 206, body = [u8]),
+		(status = 200, body = [u8]),
+		(status = 404),
+	)
+)]
+async fn get_playlist_cover(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	State(thumbnails_manager): State<thumbnail::Manager>,
+	Path(name): Path<String>,
+	Query(owner_options): Query<dto::PlaylistOwnerParameters>,
+	Query(options_input): Query<dto::ThumbnailOptions>,
+	range: Option<TypedHeader<Range>>,
+) -> Result<impl IntoResponse, APIError> {
+	let owner = owner_options
+		.owner
+		.as_deref()
+		.unwrap_or(auth.get_username());
+	playlist_manager
+		.authorize(&name, owner, auth.get_username(), playlist::Access::Read)
+		.await?;
+
+	let options = thumbnail::Options::from(options_input);
+	let thumbnail_path = match playlist_manager.get_playlist_cover(&name, owner).await? {
+		playlist::Cover::Custom(image_path) | playlist::Cover::Artwork(image_path) => {
+			thumbnails_manager
+				.get_thumbnail(&image_path, &options)
+				.await?
+		}
+		playlist::Cover::Mosaic(image_paths) => {
+			thumbnails_manager
+				.get_mosaic(&image_paths, &options)
+				.await?
+		}
+	};
+
+	let Ok(file) = tokio::fs::File::open(thumbnail_path).await else {
+		return Err(APIError::ThumbnailFileIOError);
+	};
+
+	let Ok(body) = KnownSize::file(file).await else {
+		return Err(APIError::ThumbnailFileIOError);
+	};
+
+	let range = range.map(|TypedHeader(r)| r);
+	Ok(Ranged::new(range, body))
+}
+
+#[utoipa::path(
+	put,
+	path = "/playlist/{name}/cover",
+	tag = "Playlists",
+	description = "Uploads an image to use as the cover of a playlist owned by the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz")),
+	request_body(content = [u8], content_type = "image/*"),
+	responses(
+		(status = 200),
+		(status = 400),
+		(status = 404),
+	)
+)]
+async fn put_playlist_cover(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	content: Bytes,
+) -> Result<(), APIError> {
+	playlist_manager
+		.set_playlist_cover(&name, auth.get_username(), content.to_vec())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	delete,
+	path = "/playlist/{name}/cover",
+	tag = "Playlists",
+	description = "Removes the uploaded cover of a playlist owned by the current user, so that album art from its songs is used instead.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz")),
+)]
+async fn delete_playlist_cover(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+) -> Result<(), APIError> {
+	playlist_manager
+		.remove_playlist_cover(&name, auth.get_username())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	put,
+	path = "/smart_playlist/{name}",
+	tag = "Playlists",
+	description = "Creates or updates a smart playlist for the current user. The content of smart playlists is the result of a search query, which is re-evaluated every time the playlist is read.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Jazz Classics")),
+	request_body = dto::SmartQuery,
+	responses(
+		(status = 200),
+		(status = 400, body = dto::SearchQueryError),
+	)
+)]
+async fn put_smart_playlist(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Json(smart_query): Json<dto::SmartQuery>,
+) -> Result<(), APIError> {
+	playlist_manager
+		.save_smart_playlist(&name, auth.get_username(), smart_query.into())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	post,
+	path = "/playlist/{name}/freeze",
+	tag = "Playlists",
+	description = "Turns a smart playlist owned by the current user into a regular playlist containing its current songs.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Jazz Classics")),
+)]
+async fn freeze_playlist(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+) -> Result<(), APIError> {
+	playlist_manager
+		.freeze_playlist(&name, auth.get_username())
+		.await?;
+	Ok(())
+}
+
+#[utoipa::path(
+	post,
+	path = "/playlist/{name}/import",
+	tag = "Playlists",
+	description = "Creates or updates a playlist for the current user from the content of an M3U, M3U8, PLS or XSPF playlist file.\n\nAbsolute paths are resolved through the mount directories, and relative paths are matched against the content of each mount. Entries which do not match any song are listed in the response.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz"), dto::PlaylistImportParameters),
+	request_body(content = String, content_type = "text/plain"),
+	responses(
+		(status = 200, body = dto::PlaylistImportReport),
+		(status = 400),
+	)
+)]
+async fn post_playlist_import(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Query(options): Query<dto::PlaylistImportParameters>,
+	content: Bytes,
+) -> Result<Json<dto::PlaylistImportReport>, APIError> {
+	let report = playlist_manager
+		.import_playlist(&name, auth.get_username(), options.format.into(), &content)
+		.await?;
+	Ok(Json(report.into()))
+}
+
+#[utoipa::path(
+	get,
+	path = "/playlist/{name}/export",
+	tag = "Playlists",
+	description = "Exports a playlist owned by or shared with the current user as an M3U, M3U8, PLS or XSPF playlist file.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("name", example = "Chill Jazz"), dto::PlaylistExportParameters),
+	responses(
+		(status = 200, body = String),
+	)
+)]
+async fn get_playlist_export(
+	auth: Auth,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Query(options): Query<dto::PlaylistExportParameters>,
+) -> Result<Response, APIError> {
+	let owner = options.owner.as_deref().unwrap_or(auth.get_username());
+	playlist_manager
+		.authorize(&name, owner, auth.get_username(), playlist::Access::Read)
+		.await?;
+	let format = playlist::format::Format::from(options.format);
+	let content = playlist_manager
+		.export_playlist(&name, owner, format, options.base_url.as_deref())
+		.await?;
+	Ok(([(header::CONTENT_TYPE, format.mime_type())], content).into_response())
+}
+
+#[utoipa::path(
+	get,
+	path = "/playlist/{name}",
+	tag = "Playlists",
+	description = "Retrieves a playlist owned by or shared with the current user.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(
+		("Accept-Version" = Option<i32>, Header, minimum = 7, maximum = 8),
+		("name", example = "Chill Jazz"),
+		dto::PlaylistOwnerParameters,
+	),
+	responses(
+		(status = 200, body = dto::Playlist),
+	)
+)]
+async fn get_playlist(
+	auth: Auth,
+	api_version: APIMajorVersion,
+	State(index_manager): State<index::Manager>,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(name): Path<String>,
+	Query(options): Query<dto::PlaylistOwnerParameters>,
+) -> Response {
+	let owner = options.owner.as_deref().unwrap_or(auth.get_username());
+	if let Err(e) = playlist_manager
+		.authorize(&name, owner, auth.get_username(), playlist::Access::Read)
+		.await
+	{
+		return APIError::from(e).into_response();
+	}
+
+	let mut playlist = match playlist_manager.read_playlist(&name, owner).await {
+		Ok(s) => s,
+		Err(e) => return APIError::from(e).into_response(),
+	};
+	// Playlists are pinned by their owner
+	if owner != auth.get_username() {
+		playlist.header.metadata.pinned = false;
+	}
+
+	match api_version {
+		APIMajorVersion::V7 => Json(playlist.songs).into_response(),
+		APIMajorVersion::V8 => Json(dto::Playlist {
+			header: playlist.header.into(),
 			songs: make_song_list(playlist.songs, &index_manager).await,
 		})
 		.into_response(),
 	}
 }
 
+#[utoipa::path(
+	get,
+	path = "/folder_playlist/{*path}",
+	tag = "Playlists",
+	description = "Retrieves a read-only playlist file found in the collection. Valid paths can be obtained from the `.path` field of `PlaylistHeader` models.",
+	security(
+		("auth_token" = []),
+		("auth_query_param" = []),
+	),
+	params(("path", allow_reserved, example = "my_music/favorites.m3u")),
+	responses(
+		(status = 200, body = dto::Playlist),
+	)
+)]
+async fn get_folder_playlist(
+	_auth: Auth,
+	State(index_manager): State<index::Manager>,
+	State(playlist_manager): State<playlist::Manager>,
+	Path(path): Path<PathBuf>,
+) -> Result<Json<dto::Playlist>, APIError> {
+	let playlist = playlist_manager.read_folder_playlist(path).await?;
+	Ok(Json(dto::Playlist {
+		header: playlist.header.into(),
+		songs: make_song_list(playlist.songs, &index_manager).await,
+	}))
+}
+
 #[utoipa::path(
 	delete,
 	path = "/playlist/{name}",
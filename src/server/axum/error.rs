@@ -1,10 +1,19 @@
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use axum::Json;
 
-use crate::server::error::APIError;
+use crate::server::{dto, error::APIError};
 
 impl IntoResponse for APIError {
 	fn into_response(self) -> Response {
+		if let APIError::SearchQueryParseError(e) = self {
+			return (
+				StatusCode::BAD_REQUEST,
+				Json(dto::SearchQueryError::from(e)),
+			)
+				.into_response();
+		}
+
 		let message = self.to_string();
 		let status_code = match self {
 			APIError::InvalidAPIVersionHeader => StatusCode::BAD_REQUEST,
@@ -25,6 +34,9 @@ impl IntoResponse for APIError {
 			APIError::ArtistNotFound => StatusCode::NOT_FOUND,
 			APIError::AlbumNotFound => StatusCode::NOT_FOUND,
 			APIError::GenreNotFound => StatusCode::NOT_FOUND,
+			APIError::LabelNotFound => StatusCode::NOT_FOUND,
+			APIError::DecadeNotFound => StatusCode::NOT_FOUND,
+			APIError::InvalidDecade => StatusCode::BAD_REQUEST,
 			APIError::SongNotFound => StatusCode::NOT_FOUND,
 			APIError::EmbeddedArtworkNotFound => StatusCode::NOT_FOUND,
 			APIError::EmptyPassword => StatusCode::BAD_REQUEST,
@@ -32,12 +44,23 @@ impl IntoResponse for APIError {
 			APIError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
 			APIError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
 			APIError::InvalidAlbumArtPattern => StatusCode::BAD_REQUEST,
+			APIError::InvalidArtistArtPattern => StatusCode::BAD_REQUEST,
 			APIError::InvalidDDNSURL => StatusCode::BAD_REQUEST,
 			APIError::Io(_, _) => StatusCode::INTERNAL_SERVER_ERROR,
 			APIError::OwnAdminPrivilegeRemoval => StatusCode::CONFLICT,
 			APIError::PasswordHashing => StatusCode::INTERNAL_SERVER_ERROR,
 			APIError::PlaylistNotFound => StatusCode::NOT_FOUND,
-			APIError::SearchQueryParseError => StatusCode::BAD_REQUEST,
+			APIError::PlaylistFileParseError(_) => StatusCode::BAD_REQUEST,
+			APIError::PlaylistVersionConflict(_) => StatusCode::CONFLICT,
+			APIError::PlaylistPositionOutOfRange => StatusCode::BAD_REQUEST,
+			APIError::SmartPlaylistNotEditable => StatusCode::CONFLICT,
+			APIError::PlaylistAccessDenied => StatusCode::FORBIDDEN,
+			APIError::PlaylistCoverNotFound => StatusCode::NOT_FOUND,
+			APIError::PlaylistCoverDecoding => StatusCode::BAD_REQUEST,
+			APIError::InvalidSearchCursor => StatusCode::BAD_REQUEST,
+			APIError::InvalidSearchLimit => StatusCode::BAD_REQUEST,
+			APIError::SearchCursorExpired => StatusCode::GONE,
+			APIError::SearchQueryParseError(_) => StatusCode::BAD_REQUEST,
 			APIError::ThumbnailFlacDecoding(_, _) => StatusCode::INTERNAL_SERVER_ERROR,
 			APIError::ThumbnailFileIOError => StatusCode::NOT_FOUND,
 			APIError::ThumbnailId3Decoding(_, _) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -18,12 +18,14 @@ pub struct AxumTestService {
 pub type ServiceType = AxumTestService;
 
 impl TestService for AxumTestService {
-	async fn new(test_name: &str) -> Self {
+	async fn new_with_config(test_name: &str, config: &str) -> Self {
 		let output_dir = prepare_test_directory(test_name);
+		let config_file_path = output_dir.join("polaris.toml");
+		std::fs::write(&config_file_path, config).unwrap();
 
 		let paths = Paths {
 			cache_dir_path: ["test-output", test_name].iter().collect(),
-			config_file_path: output_dir.join("polaris.toml"),
+			config_file_path,
 			data_dir_path: ["test-output", test_name].iter().collect(),
 			db_file_path: output_dir.join("db.sqlite"),
 			#[cfg(unix)]
@@ -49,11 +51,16 @@ impl TestService for AxumTestService {
 	) -> (Builder, Option<Bytes>) {
 		let url = request.uri().to_string();
 		let body = request.body().clone();
+		let is_text = request
+			.headers()
+			.get(http::header::CONTENT_TYPE)
+			.is_some_and(|c| c == "text/plain");
 
 		let mut axum_request = match *request.method() {
 			Method::GET => self.server.get(&url),
 			Method::POST => self.server.post(&url),
 			Method::PUT => self.server.put(&url),
+			Method::PATCH => self.server.patch(&url),
 			Method::DELETE => self.server.delete(&url),
 			_ => unimplemented!(),
 		};
@@ -66,7 +73,10 @@ impl TestService for AxumTestService {
 			axum_request = axum_request.authorization_bearer(authorization.token.clone());
 		}
 
-		let axum_response = axum_request.json(&body).await;
+		let axum_response = match serde_json::to_value(&body) {
+			Ok(serde_json::Value::String(text)) if is_text => axum_request.text(text).await,
+			_ => axum_request.json(&body).await,
+		};
 
 		let mut response_builder = Response::builder().status(axum_response.status_code());
 		let headers = response_builder.headers_mut().unwrap();
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
-use crate::app::{config, index, peaks, playlist, scanner, thumbnail};
+use crate::app::{config, formats, index, peaks, playlist, scanner, thumbnail};
 use std::{collections::HashMap, convert::From, path::PathBuf, time::UNIX_EPOCH};
 
 #[derive(PartialEq, Eq, Debug, Serialize, Deserialize, ToSchema)]
@@ -51,6 +51,9 @@ pub struct ThumbnailOptions {
 	pub size: Option<ThumbnailSize>,
 	#[schema(examples(true, false))]
 	pub pad: Option<bool>,
+	/// Index of the embedded picture to use when the path is an audio file. Defaults to its front cover.
+	#[schema(examples(0, 1))]
+	pub picture: Option<u32>,
 }
 
 impl From<ThumbnailOptions> for thumbnail::Options {
@@ -58,6 +61,7 @@ impl From<ThumbnailOptions> for thumbnail::Options {
 		let mut options = thumbnail::Options::default();
 		options.max_dimension = dto.size.map_or(options.max_dimension, Into::into);
 		options.pad_to_square = dto.pad.unwrap_or(options.pad_to_square);
+		options.picture_index = dto.picture;
 		options
 	}
 }
@@ -94,6 +98,10 @@ impl From<peaks::Peaks> for Peaks {
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct PlaylistHeader {
+	/// User who owns the playlist, absent for folder playlists
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("alice"))]
+	pub owner: Option<String>,
 	#[schema(examples("Hotel Lounge Jazz", "Chill Beats 🏝️"))]
 	pub name: String,
 	#[schema(examples(json!({ "Jazz": 2, "Classical": 11 })))]
@@ -101,14 +109,187 @@ pub struct PlaylistHeader {
 	#[schema(examples(2309))]
 	/// Playlist duration in seconds
 	pub duration: u64,
+	/// Search query defining the content of this playlist, for smart playlists only
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub smart_query: Option<SmartQuery>,
+	pub source: PlaylistSource,
+	/// Virtual path of the playlist file, for folder playlists only
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(value_type = Option<String>, examples("my_music/favorites.m3u"))]
+	pub path: Option<PathBuf>,
+	/// Incremented every time the playlist is modified
+	#[serde(default)]
+	#[schema(examples(4))]
+	pub version: u64,
+	/// Who besides the owner can access the playlist, absent for folder playlists
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub visibility: Option<PlaylistVisibility>,
+	/// Users the playlist is shared with
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	#[schema(examples(json!(["bob"])))]
+	pub members: Vec<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("Songs for late evenings"))]
+	pub description: Option<String>,
+	/// Slash-separated folder to organize playlists in
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("Road Trips/Summer"))]
+	pub folder: Option<String>,
+	/// Pinned playlists are listed first
+	#[serde(default)]
+	pub pinned: bool,
+	/// Whether the playlist cover is an image uploaded by its owner, rather than album art from its songs
+	#[serde(default)]
+	pub custom_cover: bool,
 }
 
 impl From<playlist::PlaylistHeader> for PlaylistHeader {
 	fn from(header: playlist::PlaylistHeader) -> Self {
+		let (source, path, visibility) = match header.source {
+			playlist::PlaylistSource::User => (
+				PlaylistSource::User,
+				None,
+				Some(header.sharing.visibility.into()),
+			),
+			playlist::PlaylistSource::Folder(p) => (PlaylistSource::Folder, Some(p), None),
+		};
 		Self {
+			owner: header.owner,
 			name: header.name.to_string(),
 			num_songs_by_genre: header.num_songs_by_genre,
 			duration: header.duration.as_secs(),
+			smart_query: header.smart_query.map(|q| q.into()),
+			source,
+			path,
+			version: header.version,
+			visibility,
+			members: header.sharing.members,
+			description: header.metadata.description,
+			folder: header.metadata.folder,
+			pinned: header.metadata.pinned,
+			custom_cover: header.custom_cover,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistVisibility {
+	/// Only the owner can access the playlist
+	Private,
+	/// Members can read the playlist
+	SharedRead,
+	/// Members can read and edit the playlist
+	Collaborative,
+}
+
+impl From<playlist::Visibility> for PlaylistVisibility {
+	fn from(v: playlist::Visibility) -> Self {
+		match v {
+			playlist::Visibility::Private => Self::Private,
+			playlist::Visibility::SharedRead => Self::SharedRead,
+			playlist::Visibility::Collaborative => Self::Collaborative,
+		}
+	}
+}
+
+impl From<PlaylistVisibility> for playlist::Visibility {
+	fn from(v: PlaylistVisibility) -> Self {
+		match v {
+			PlaylistVisibility::Private => Self::Private,
+			PlaylistVisibility::SharedRead => Self::SharedRead,
+			PlaylistVisibility::Collaborative => Self::Collaborative,
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistSharing {
+	pub visibility: PlaylistVisibility,
+	/// Users the playlist is shared with
+	#[schema(examples(json!(["bob", "carol"])))]
+	pub members: Vec<String>,
+}
+
+impl From<PlaylistSharing> for playlist::Sharing {
+	fn from(s: PlaylistSharing) -> Self {
+		Self {
+			visibility: s.visibility.into(),
+			members: s.members,
+		}
+	}
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistMetadata {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("Songs for late evenings"))]
+	pub description: Option<String>,
+	/// Slash-separated folder to organize playlists in
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("Road Trips/Summer"))]
+	pub folder: Option<String>,
+	/// Pinned playlists are listed first
+	#[serde(default)]
+	pub pinned: bool,
+}
+
+impl From<PlaylistMetadata> for playlist::Metadata {
+	fn from(m: PlaylistMetadata) -> Self {
+		Self {
+			description: m.description,
+			folder: m.folder,
+			pinned: m.pinned,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct PlaylistOwnerParameters {
+	/// Owner of the playlist, when accessing a playlist shared by another user
+	pub owner: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistSource {
+	/// Playlist saved by a user
+	User,
+	/// Read-only playlist file found in the collection
+	Folder,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SmartQuery {
+	/// Search query, in the same syntax as the `/search` endpoint
+	#[schema(examples("genre = jazz && year < 1970"))]
+	pub query: String,
+	/// Sort order for the results, in the syntax of an `ORDER BY` clause
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("year DESC, title"))]
+	pub sort: Option<String>,
+	/// Maximum number of songs in the playlist
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples(100))]
+	pub limit: Option<usize>,
+}
+
+impl From<playlist::SmartQuery> for SmartQuery {
+	fn from(q: playlist::SmartQuery) -> Self {
+		Self {
+			query: q.query,
+			sort: q.sort,
+			limit: q.limit,
+		}
+	}
+}
+
+impl From<SmartQuery> for playlist::SmartQuery {
+	fn from(q: SmartQuery) -> Self {
+		Self {
+			query: q.query,
+			sort: q.sort,
+			limit: q.limit,
 		}
 	}
 }
@@ -126,6 +307,110 @@ pub struct SavePlaylistInput {
 	pub tracks: Vec<PathBuf>,
 }
 
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistEditInput {
+	/// Version of the playlist this edit is based on. The edit is rejected if the playlist was modified since. Without a version, edits which refer to positions in the playlist are rejected if another edit is applied concurrently.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples(4))]
+	pub version: Option<u64>,
+	pub operation: PlaylistEditOperation,
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlaylistEditOperation {
+	/// Adds songs at the end of the playlist
+	Append {
+		#[schema(value_type = Vec<String>, examples(json!(["my_music/destiny.mp3"])))]
+		tracks: Vec<PathBuf>,
+	},
+	/// Adds songs before the song at `position`
+	Insert {
+		position: usize,
+		#[schema(value_type = Vec<String>, examples(json!(["my_music/destiny.mp3"])))]
+		tracks: Vec<PathBuf>,
+	},
+	/// Removes `count` songs starting at `position`
+	Remove { position: usize, count: usize },
+	/// Moves `count` songs starting at `from`, so that the first of them ends up at position `to`
+	Move {
+		from: usize,
+		count: usize,
+		to: usize,
+	},
+	/// Removes repeated occurrences of the same song, keeping the first one
+	Dedupe,
+}
+
+impl From<PlaylistEditOperation> for playlist::PlaylistEdit {
+	fn from(o: PlaylistEditOperation) -> Self {
+		match o {
+			PlaylistEditOperation::Append { tracks } => Self::Append(tracks),
+			PlaylistEditOperation::Insert { position, tracks } => Self::Insert {
+				position,
+				songs: tracks,
+			},
+			PlaylistEditOperation::Remove { position, count } => Self::Remove { position, count },
+			PlaylistEditOperation::Move { from, count, to } => Self::Move { from, count, to },
+			PlaylistEditOperation::Dedupe => Self::Dedupe,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "m3u8")]
+pub enum PlaylistFileFormat {
+	M3u,
+	M3u8,
+	Pls,
+	Xspf,
+}
+
+impl From<PlaylistFileFormat> for playlist::format::Format {
+	fn from(f: PlaylistFileFormat) -> Self {
+		match f {
+			PlaylistFileFormat::M3u => Self::M3U,
+			PlaylistFileFormat::M3u8 => Self::M3U8,
+			PlaylistFileFormat::Pls => Self::PLS,
+			PlaylistFileFormat::Xspf => Self::XSPF,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct PlaylistImportParameters {
+	pub format: PlaylistFileFormat,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PlaylistImportReport {
+	/// Number of songs added to the playlist
+	#[schema(examples(12))]
+	pub num_songs: usize,
+	/// Entries of the playlist file which did not match any song in the collection
+	#[schema(examples(json!(["/home/alice/Music/missing.mp3"])))]
+	pub unresolved: Vec<String>,
+}
+
+impl From<playlist::ImportReport> for PlaylistImportReport {
+	fn from(r: playlist::ImportReport) -> Self {
+		Self {
+			num_songs: r.num_songs,
+			unresolved: r.unresolved,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct PlaylistExportParameters {
+	pub format: PlaylistFileFormat,
+	/// Address of this Polaris server (eg. `http://localhost:5050`). When set, songs are listed as streaming URLs instead of virtual paths.
+	pub base_url: Option<String>,
+	/// Owner of the playlist, when exporting a playlist shared by another user
+	pub owner: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct User {
 	#[schema(examples("alice"))]
@@ -161,6 +446,27 @@ pub struct UserUpdate {
 	pub new_is_admin: Option<bool>,
 }
 
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Preferences {
+	/// Whether album and search results only list the best copy of songs which exist in several formats
+	#[schema(examples(true, false))]
+	pub collapse_duplicates: bool,
+}
+
+impl From<config::User> for Preferences {
+	fn from(u: config::User) -> Self {
+		Self {
+			collapse_duplicates: u.collapses_duplicates(),
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PreferencesUpdate {
+	#[schema(examples(true, false))]
+	pub new_collapse_duplicates: Option<bool>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize, ToSchema)]
 pub struct MountDir {
 	#[schema(value_type = String, examples("/home/alice/music", "C:\\Users\\alice\\Documents\\Music"))]
@@ -191,6 +497,8 @@ impl From<config::MountDir> for MountDir {
 pub struct NewSettings {
 	#[schema(examples("Folder.(jpeg|jpg|png)"))]
 	pub album_art_pattern: Option<String>,
+	#[schema(examples("artist.(jpeg|jpg|png)"))]
+	pub artist_art_pattern: Option<String>,
 	#[schema(examples("https://myddnsprovider.com?token=abcdef"))]
 	pub ddns_update_url: Option<String>,
 }
@@ -199,6 +507,8 @@ pub struct NewSettings {
 pub struct Settings {
 	#[schema(examples("Folder.(jpeg|jpg|png)"))]
 	pub album_art_pattern: String,
+	#[schema(examples("artist.(jpeg|jpg|png)"))]
+	pub artist_art_pattern: String,
 	#[schema(examples("https://myddnsprovider.com?token=abcdef"))]
 	pub ddns_update_url: String,
 }
@@ -325,6 +635,167 @@ pub struct SongList {
 	pub first_songs: Vec<Song>,
 }
 
+#[derive(Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "relevance")]
+pub enum SearchOrder {
+	Alphabetical,
+	Relevance,
+}
+
+impl From<SearchOrder> for index::SearchOrder {
+	fn from(o: SearchOrder) -> Self {
+		match o {
+			SearchOrder::Alphabetical => Self::Alphabetical,
+			SearchOrder::Relevance => Self::Relevance,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct SearchParameters {
+	/// Defaults to `alphabetical`
+	pub order: Option<SearchOrder>,
+	/// Number of matching songs to skip. Ignored when `cursor` is set.
+	#[schema(examples(0))]
+	pub offset: Option<usize>,
+	/// Maximum number of songs to return, must be greater than zero. Defaults to returning every matching song.
+	#[schema(examples(100))]
+	pub limit: Option<usize>,
+	/// Value of `.next_cursor` from a previous page of results for the same query and order
+	pub cursor: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SearchResults {
+	#[serde(flatten)]
+	pub songs: SongList,
+	/// Relevance score of each song listed in `.paths`. Higher scores denote better matches. Scores are only computed when ordering by relevance, and are 0 otherwise.
+	#[schema(examples(json!([240, 80])))]
+	pub scores: Vec<u32>,
+	/// Number of songs matching the query, across all pages
+	#[schema(examples(2))]
+	pub total: usize,
+	/// Opaque token to request the next page of results. Absent on the last page. Cursors stop working when the collection is re-indexed.
+	pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SearchQueryError {
+	/// Human-readable description of the problem
+	#[schema(examples("unknown field `yaer`, did you mean `year`?"))]
+	pub hint: String,
+	/// Position of the first offending character in the query, counted in characters
+	#[schema(examples(0))]
+	pub start: usize,
+	/// Position after the last offending character in the query, counted in characters
+	#[schema(examples(4))]
+	pub end: usize,
+	/// Tokens which would have been valid at `start`
+	#[schema(examples(json!(["`(`", "`)`"])))]
+	pub expected: Vec<String>,
+}
+
+impl From<index::QueryError> for SearchQueryError {
+	fn from(e: index::QueryError) -> Self {
+		Self {
+			hint: e.hint,
+			start: e.span.start,
+			end: e.span.end,
+			expected: e.expected,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct SuggestParameters {
+	/// Partial search query typed by the user
+	#[schema(examples("pink fl"))]
+	pub prefix: String,
+	/// Maximum number of suggestions to return. Defaults to 10.
+	#[schema(examples(10))]
+	pub count: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "artist")]
+pub enum SuggestionKind {
+	Keyword,
+	Artist,
+	Album,
+	Title,
+}
+
+impl From<index::SuggestionKind> for SuggestionKind {
+	fn from(k: index::SuggestionKind) -> Self {
+		match k {
+			index::SuggestionKind::Keyword => Self::Keyword,
+			index::SuggestionKind::Artist => Self::Artist,
+			index::SuggestionKind::Album => Self::Album,
+			index::SuggestionKind::Title => Self::Title,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Suggestion {
+	pub kind: SuggestionKind,
+	/// Text completing the prefix. Keywords are field names usable in search queries (eg. `year`).
+	#[schema(examples("Pink Floyd"))]
+	pub text: String,
+}
+
+impl From<index::Suggestion> for Suggestion {
+	fn from(s: index::Suggestion) -> Self {
+		Self {
+			kind: s.kind.into(),
+			text: s.text,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct SearchAllParameters {
+	/// Maximum number of artists to return. Defaults to 5.
+	#[schema(examples(5))]
+	pub artists: Option<usize>,
+	/// Maximum number of albums to return. Defaults to 5.
+	#[schema(examples(5))]
+	pub albums: Option<usize>,
+	/// Maximum number of genres to return. Defaults to 5.
+	#[schema(examples(5))]
+	pub genres: Option<usize>,
+	/// Maximum number of playlists to return. Defaults to 5.
+	#[schema(examples(5))]
+	pub playlists: Option<usize>,
+	/// Maximum number of songs to return. Defaults to 20.
+	#[schema(examples(20))]
+	pub songs: Option<usize>,
+}
+
+impl From<SearchAllParameters> for index::SearchLimits {
+	fn from(p: SearchAllParameters) -> Self {
+		let default = Self::default();
+		Self {
+			artists: p.artists.unwrap_or(default.artists),
+			albums: p.albums.unwrap_or(default.albums),
+			genres: p.genres.unwrap_or(default.genres),
+			songs: p.songs.unwrap_or(default.songs),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SearchAllResults {
+	pub artists: Vec<ArtistHeader>,
+	pub albums: Vec<AlbumHeader>,
+	pub genres: Vec<GenreHeader>,
+	pub playlists: Vec<PlaylistHeader>,
+	/// Matching songs, from most to least relevant
+	pub songs: SearchResults,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct BrowserEntry {
 	#[schema(value_type = String, examples("my_music/stratovarius/destiny"))]
@@ -352,16 +823,27 @@ impl From<index::File> for BrowserEntry {
 pub struct GenreHeader {
 	#[schema(examples("Jazz", "Classical"))]
 	pub name: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples("Metal"))]
+	pub parent: Option<String>,
 }
 
 impl From<index::GenreHeader> for GenreHeader {
 	fn from(g: index::GenreHeader) -> Self {
 		Self {
 			name: g.name.to_string(),
+			parent: g.parent,
 		}
 	}
 }
 
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct GenreParameters {
+	/// Also include content from subgenres, as configured in the genre hierarchy
+	#[schema(examples(true, false))]
+	pub include_subgenres: Option<bool>,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct Genre {
 	#[serde(flatten)]
@@ -408,10 +890,78 @@ impl From<index::Genre> for Genre {
 	}
 }
 
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct LabelHeader {
+	#[schema(examples("Nuclear Blast", "Deutsche Grammophon"))]
+	pub name: String,
+}
+
+impl From<index::LabelHeader> for LabelHeader {
+	fn from(l: index::LabelHeader) -> Self {
+		Self { name: l.name }
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Label {
+	#[serde(flatten)]
+	pub header: LabelHeader,
+	pub albums: Vec<AlbumHeader>,
+	pub artists: Vec<ArtistHeader>,
+}
+
+impl From<index::Label> for Label {
+	fn from(l: index::Label) -> Self {
+		Self {
+			header: l.header.into(),
+			albums: l.albums.into_iter().map(|a| a.into()).collect(),
+			artists: l.artists.into_iter().map(|a| a.into()).collect(),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DecadeHeader {
+	/// First year of the decade
+	#[schema(examples(1990, 2010))]
+	pub decade: i64,
+}
+
+impl From<index::DecadeHeader> for DecadeHeader {
+	fn from(d: index::DecadeHeader) -> Self {
+		Self { decade: d.decade }
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Decade {
+	#[serde(flatten)]
+	pub header: DecadeHeader,
+	pub albums: Vec<AlbumHeader>,
+	pub artists: Vec<ArtistHeader>,
+}
+
+impl From<index::Decade> for Decade {
+	fn from(d: index::Decade) -> Self {
+		Self {
+			header: d.header.into(),
+			albums: d.albums.into_iter().map(|a| a.into()).collect(),
+			artists: d.artists.into_iter().map(|a| a.into()).collect(),
+		}
+	}
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
 pub struct ArtistHeader {
 	#[schema(examples("Stratovarius", "Parov Stelar"))]
 	pub name: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(value_type = String, examples("my_music/stratovarius/artist.jpg"))]
+	pub artwork: Option<PathBuf>,
+	/// Album covers to display as a mosaic for artists without artwork of their own
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	#[schema(value_type = Vec<String>, examples(json!(["my_music/stratovarius/destiny/Folder.jpg", "my_music/stratovarius/elysium/Folder.jpg"])))]
+	pub mosaic: Vec<PathBuf>,
 	#[schema(examples(0, 5))]
 	pub num_albums_as_performer: u32,
 	#[schema(examples(0, 5))]
@@ -424,18 +974,27 @@ pub struct ArtistHeader {
 	pub num_songs_by_genre: HashMap<String, u32>,
 	#[schema(examples(12))]
 	pub num_songs: u32,
+	#[schema(examples(1, 4))]
+	pub num_discs: u32,
+	/// Total duration of the artist's songs in seconds
+	#[schema(examples(2309))]
+	pub duration: u64,
 }
 
 impl From<index::ArtistHeader> for ArtistHeader {
 	fn from(a: index::ArtistHeader) -> Self {
 		Self {
 			name: a.name.to_string(),
+			artwork: a.artwork,
+			mosaic: a.mosaic,
 			num_albums_as_performer: a.num_albums_as_performer,
 			num_albums_as_additional_performer: a.num_albums_as_additional_performer,
 			num_albums_as_composer: a.num_albums_as_composer,
 			num_albums_as_lyricist: a.num_albums_as_lyricist,
 			num_songs_by_genre: a.num_songs_by_genre,
 			num_songs: a.num_songs,
+			num_discs: a.num_discs,
+			duration: a.duration.max(0) as u64,
 		}
 	}
 }
@@ -500,6 +1059,15 @@ pub struct AlbumHeader {
 	#[serde(default, skip_serializing_if = "Option::is_none")]
 	#[schema(examples(2010, 2024))]
 	pub year: Option<i64>,
+	#[schema(examples(json!({ "Jazz": 2, "Classical": 11 })))]
+	pub num_songs_by_genre: HashMap<String, u32>,
+	#[schema(examples(12))]
+	pub num_songs: u32,
+	#[schema(examples(1, 2))]
+	pub num_discs: u32,
+	/// Album duration in seconds
+	#[schema(examples(3240))]
+	pub duration: u64,
 }
 
 impl From<index::AlbumHeader> for AlbumHeader {
@@ -509,6 +1077,10 @@ impl From<index::AlbumHeader> for AlbumHeader {
 			artwork: a.artwork,
 			main_artists: a.artists,
 			year: a.year,
+			num_songs_by_genre: a.num_songs_by_genre,
+			num_songs: a.num_songs,
+			num_discs: a.num_discs,
+			duration: a.duration.max(0) as u64,
 		}
 	}
 }
@@ -530,6 +1102,63 @@ impl From<index::Album> for Album {
 	}
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "front")]
+pub enum PictureType {
+	Front,
+	Back,
+	Booklet,
+	Media,
+	Other,
+}
+
+impl From<PictureType> for formats::PictureType {
+	fn from(t: PictureType) -> Self {
+		match t {
+			PictureType::Front => Self::Front,
+			PictureType::Back => Self::Back,
+			PictureType::Booklet => Self::Booklet,
+			PictureType::Media => Self::Media,
+			PictureType::Other => Self::Other,
+		}
+	}
+}
+
+impl From<formats::PictureType> for PictureType {
+	fn from(t: formats::PictureType) -> Self {
+		match t {
+			formats::PictureType::Front => Self::Front,
+			formats::PictureType::Back => Self::Back,
+			formats::PictureType::Booklet => Self::Booklet,
+			formats::PictureType::Media => Self::Media,
+			formats::PictureType::Other => Self::Other,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AlbumImage {
+	/// Path to use with the `/thumbnail` endpoint. This is an audio file for embedded pictures.
+	#[schema(value_type = String, examples("my_music/destiny/back.jpg", "my_music/destiny/sos.mp3"))]
+	pub path: PathBuf,
+	pub picture_type: PictureType,
+	/// Value to pass as the `picture` parameter of the `/thumbnail` endpoint, for embedded pictures
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	#[schema(examples(0, 1))]
+	pub picture: Option<u32>,
+}
+
+impl From<index::Image> for AlbumImage {
+	fn from(i: index::Image) -> Self {
+		Self {
+			path: i.path,
+			picture_type: i.picture_type.into(),
+			picture: i.picture_index,
+		}
+	}
+}
+
 #[derive(Clone, Default, Serialize, Deserialize, ToSchema)]
 pub struct GetSongsBulkInput {
 	#[schema(value_type = Vec<String>, examples(json!(["my_music/destiny.mp3", "my_music/sos.mp3"])))]
@@ -561,3 +1190,210 @@ pub struct GetRecentAlbumsParameters {
 	#[schema(examples(100, 1000))]
 	pub count: Option<usize>,
 }
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct ListingParameters {
+	pub sort: Option<ListingSort>,
+	pub order: Option<ListingOrder>,
+	#[schema(examples(0, 100))]
+	pub offset: Option<usize>,
+	#[schema(examples(100, 1000))]
+	pub count: Option<usize>,
+}
+
+impl From<ListingParameters> for index::ListOptions {
+	fn from(p: ListingParameters) -> Self {
+		Self {
+			sort: p.sort.map(Into::into).unwrap_or_default(),
+			order: p.order.map(Into::into).unwrap_or_default(),
+			offset: p.offset.unwrap_or(0),
+			count: p.count,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "name")]
+pub enum ListingSort {
+	Name,
+	Year,
+	DateAdded,
+	Artist,
+	NumSongs,
+	Duration,
+}
+
+impl From<ListingSort> for index::SortKey {
+	fn from(s: ListingSort) -> Self {
+		match s {
+			ListingSort::Name => Self::Name,
+			ListingSort::Year => Self::Year,
+			ListingSort::DateAdded => Self::DateAdded,
+			ListingSort::Artist => Self::Artist,
+			ListingSort::NumSongs => Self::NumSongs,
+			ListingSort::Duration => Self::Duration,
+		}
+	}
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "asc")]
+pub enum ListingOrder {
+	Asc,
+	Desc,
+}
+
+impl From<ListingOrder> for index::SortOrder {
+	fn from(o: ListingOrder) -> Self {
+		match o {
+			ListingOrder::Asc => Self::Ascending,
+			ListingOrder::Desc => Self::Descending,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct CollectionStats {
+	#[schema(examples(12000))]
+	pub num_songs: u32,
+	#[schema(examples(1000))]
+	pub num_albums: u32,
+	#[schema(examples(400))]
+	pub num_artists: u32,
+	#[schema(examples(60))]
+	pub num_genres: u32,
+	/// Total duration of the collection in seconds
+	#[schema(examples(2592000))]
+	pub duration: u64,
+	#[schema(examples(json!({ "mp3": 9000, "flac": 3000 })))]
+	pub num_songs_by_format: HashMap<String, u32>,
+	/// Song counts keyed by the first year of each decade
+	#[schema(examples(json!({ "1990": 4000, "2000": 8000 })))]
+	pub num_songs_by_decade: HashMap<i64, u32>,
+	#[schema(examples(json!({ "Jazz": 2000, "Classical": 10000 })))]
+	pub num_songs_by_genre: HashMap<String, u32>,
+}
+
+impl From<index::Stats> for CollectionStats {
+	fn from(s: index::Stats) -> Self {
+		Self {
+			num_songs: s.num_songs,
+			num_albums: s.num_albums,
+			num_artists: s.num_artists,
+			num_genres: s.num_genres,
+			duration: s.duration.max(0) as u64,
+			num_songs_by_format: s.num_songs_by_format,
+			num_songs_by_decade: s.num_songs_by_decade,
+			num_songs_by_genre: s.num_songs_by_genre,
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DetailedCollectionStats {
+	#[serde(flatten)]
+	pub stats: CollectionStats,
+	/// Total size of the collection's audio files in bytes
+	#[schema(examples(80000000000u64))]
+	pub size: u64,
+	#[schema(examples(25))]
+	pub num_songs_missing_artwork: u32,
+	#[schema(examples(10))]
+	pub num_songs_missing_year: u32,
+	#[schema(examples(3))]
+	pub num_songs_missing_album: u32,
+	#[schema(examples(12))]
+	pub num_songs_added_last_7_days: u32,
+	#[schema(examples(40))]
+	pub num_songs_added_last_30_days: u32,
+	#[schema(examples(500))]
+	pub num_songs_added_last_365_days: u32,
+}
+
+impl From<index::Stats> for DetailedCollectionStats {
+	fn from(s: index::Stats) -> Self {
+		Self {
+			size: s.size,
+			num_songs_missing_artwork: s.num_songs_missing_artwork,
+			num_songs_missing_year: s.num_songs_missing_year,
+			num_songs_missing_album: s.num_songs_missing_album,
+			num_songs_added_last_7_days: s.num_songs_added_last_7_days,
+			num_songs_added_last_30_days: s.num_songs_added_last_30_days,
+			num_songs_added_last_365_days: s.num_songs_added_last_365_days,
+			stats: s.into(),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[schema(example = "missing_artwork")]
+pub enum HealthRule {
+	MismatchedAlbumArtists,
+	MissingTrackNumbers,
+	DuplicateTrackNumbers,
+	YearDrift,
+	MissingArtwork,
+}
+
+impl From<HealthRule> for index::HealthRule {
+	fn from(r: HealthRule) -> Self {
+		match r {
+			HealthRule::MismatchedAlbumArtists => Self::MismatchedAlbumArtists,
+			HealthRule::MissingTrackNumbers => Self::MissingTrackNumbers,
+			HealthRule::DuplicateTrackNumbers => Self::DuplicateTrackNumbers,
+			HealthRule::YearDrift => Self::YearDrift,
+			HealthRule::MissingArtwork => Self::MissingArtwork,
+		}
+	}
+}
+
+impl From<index::HealthRule> for HealthRule {
+	fn from(r: index::HealthRule) -> Self {
+		match r {
+			index::HealthRule::MismatchedAlbumArtists => Self::MismatchedAlbumArtists,
+			index::HealthRule::MissingTrackNumbers => Self::MissingTrackNumbers,
+			index::HealthRule::DuplicateTrackNumbers => Self::DuplicateTrackNumbers,
+			index::HealthRule::YearDrift => Self::YearDrift,
+			index::HealthRule::MissingArtwork => Self::MissingArtwork,
+		}
+	}
+}
+
+#[derive(Clone, Serialize, Deserialize, IntoParams, ToSchema)]
+pub struct HealthParameters {
+	/// Only report albums failing this rule
+	pub rule: Option<HealthRule>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AlbumHealth {
+	#[serde(flatten)]
+	pub album: AlbumHeader,
+	pub issues: Vec<HealthRule>,
+}
+
+impl From<index::AlbumHealth> for AlbumHealth {
+	fn from(h: index::AlbumHealth) -> Self {
+		Self {
+			album: h.album.into(),
+			issues: h.issues.into_iter().map(|r| r.into()).collect(),
+		}
+	}
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DuplicateGroup {
+	/// Copies of the same song, from best to worst format
+	pub songs: Vec<Song>,
+}
+
+impl From<Vec<index::Song>> for DuplicateGroup {
+	fn from(songs: Vec<index::Song>) -> Self {
+		Self {
+			songs: songs.into_iter().map(|s| s.into()).collect(),
+		}
+	}
+}
@@ -31,6 +31,12 @@ pub enum APIError {
 	AlbumNotFound,
 	#[error("Genre not found")]
 	GenreNotFound,
+	#[error("Label not found")]
+	LabelNotFound,
+	#[error("Decade not found")]
+	DecadeNotFound,
+	#[error("Decades must be written as their first year (eg. `1990s`)")]
+	InvalidDecade,
 	#[error("Song not found")]
 	SongNotFound,
 	#[error("DDNS update query failed with HTTP status {0}")]
@@ -51,6 +57,8 @@ pub enum APIError {
 	Internal,
 	#[error("Could not parse album art pattern")]
 	InvalidAlbumArtPattern,
+	#[error("Could not parse artist art pattern")]
+	InvalidArtistArtPattern,
 	#[error("Could not parse DDNS update URL")]
 	InvalidDDNSURL,
 	#[error("File I/O error for `{0}`:\n\n{1}")]
@@ -61,8 +69,28 @@ pub enum APIError {
 	PasswordHashing,
 	#[error("Playlist not found")]
 	PlaylistNotFound,
-	#[error("Could not parse search query")]
-	SearchQueryParseError,
+	#[error("Could not parse playlist file: {0}")]
+	PlaylistFileParseError(String),
+	#[error("Playlist was modified since it was read (current version is {0})")]
+	PlaylistVersionConflict(u64),
+	#[error("Playlist position is out of range")]
+	PlaylistPositionOutOfRange,
+	#[error("Smart playlists cannot be edited")]
+	SmartPlaylistNotEditable,
+	#[error("Playlist is not shared for editing")]
+	PlaylistAccessDenied,
+	#[error("Playlist has no cover")]
+	PlaylistCoverNotFound,
+	#[error("Could not decode playlist cover")]
+	PlaylistCoverDecoding,
+	#[error("Invalid search cursor")]
+	InvalidSearchCursor,
+	#[error("Search limit must be greater than zero")]
+	InvalidSearchLimit,
+	#[error("Search cursor has expired because the collection was re-indexed")]
+	SearchCursorExpired,
+	#[error("Could not parse search query: {}", .0.hint)]
+	SearchQueryParseError(app::index::QueryError),
 	#[error("Could not decode thumbnail from flac file `{0}`:\n\n{1}")]
 	ThumbnailFlacDecoding(PathBuf, metaflac::Error),
 	#[error("Thumbnail file could not be opened")]
@@ -125,6 +153,9 @@ impl From<app::Error> for APIError {
 			app::Error::MiscSettingsNotFound => APIError::Internal,
 			app::Error::DDNSUpdateURLInvalid => APIError::InvalidDDNSURL,
 			app::Error::IndexAlbumArtPatternInvalid => APIError::InvalidAlbumArtPattern,
+			app::Error::IndexArtistArtPatternInvalid => APIError::InvalidArtistArtPattern,
+			app::Error::GenreHierarchyCycle(_) => APIError::Internal,
+			app::Error::TransliterationInvalid(_) => APIError::Internal,
 
 			app::Error::ConfigDeserialization(_) => APIError::Internal,
 			app::Error::ConfigSerialization(_) => APIError::Internal,
@@ -138,9 +169,18 @@ impl From<app::Error> for APIError {
 			app::Error::ArtistNotFound => APIError::ArtistNotFound,
 			app::Error::AlbumNotFound => APIError::AlbumNotFound,
 			app::Error::GenreNotFound => APIError::GenreNotFound,
+			app::Error::LabelNotFound => APIError::LabelNotFound,
+			app::Error::DecadeNotFound => APIError::DecadeNotFound,
 			app::Error::SongNotFound => APIError::SongNotFound,
 			app::Error::PlaylistNotFound => APIError::PlaylistNotFound,
-			app::Error::SearchQueryParseError => APIError::SearchQueryParseError,
+			app::Error::PlaylistXspfParse(e) => APIError::PlaylistFileParseError(e.to_string()),
+			app::Error::PlaylistVersionConflict(v) => APIError::PlaylistVersionConflict(v),
+			app::Error::PlaylistPositionOutOfRange => APIError::PlaylistPositionOutOfRange,
+			app::Error::SmartPlaylistNotEditable => APIError::SmartPlaylistNotEditable,
+			app::Error::PlaylistAccessDenied => APIError::PlaylistAccessDenied,
+			app::Error::PlaylistCoverNotFound => APIError::PlaylistCoverNotFound,
+			app::Error::PlaylistCoverDecoding(_) => APIError::PlaylistCoverDecoding,
+			app::Error::SearchQueryParseError(e) => APIError::SearchQueryParseError(e),
 			app::Error::EmbeddedArtworkNotFound(_) => APIError::EmbeddedArtworkNotFound,
 
 			app::Error::DuplicateUsername => APIError::DuplicateUsername,
@@ -29,7 +29,12 @@ use protocol::V8;
 pub use crate::server::axum::test::ServiceType;
 
 pub trait TestService {
-	async fn new(test_name: &str) -> Self;
+	async fn new(test_name: &str) -> Self {
+		Self::new_with_config(test_name, "").await
+	}
+
+	/// Starts the service with `config` as the initial content of its config file
+	async fn new_with_config(test_name: &str, config: &str) -> Self;
 
 	async fn execute_request<T: Serialize + Clone + 'static>(
 		&mut self,
@@ -1,10 +1,12 @@
 use http::StatusCode;
+use std::path::PathBuf;
 
 use crate::{
 	server::{
 		dto,
 		test::{
 			add_trailing_slash,
+			constants::TEST_MOUNT_NAME,
 			protocol::{self, V7, V8},
 			ServiceType, TestService,
 		},
@@ -12,6 +14,138 @@ use crate::{
 	test_name,
 };
 
+#[tokio::test]
+async fn albums_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::albums("");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn albums_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::albums("");
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let names = response
+		.body()
+		.iter()
+		.map(|a| a.name.as_str())
+		.collect::<Vec<_>>();
+	assert_eq!(names, vec!["Hunted", "Picnic", "Picnic (Remixes)"]);
+}
+
+#[tokio::test]
+async fn albums_paging() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::albums("sort=name&order=desc&offset=1&count=1");
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let names = response
+		.body()
+		.iter()
+		.map(|a| a.name.as_str())
+		.collect::<Vec<_>>();
+	assert_eq!(names, vec!["Picnic"]);
+}
+
+#[tokio::test]
+async fn albums_bad_sort() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.login().await;
+
+	let request = protocol::albums("sort=loudness");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn album_images_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::album_images("Hunted", &["Khemmis"]);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn album_images_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::album_images("Hunted", &["Khemmis"]);
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumImage>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let images = response.body();
+	assert_eq!(
+		images.first(),
+		Some(&dto::AlbumImage {
+			path: PathBuf::from_iter([TEST_MOUNT_NAME, "Khemmis", "Hunted", "Folder.jpg"]),
+			picture_type: dto::PictureType::Front,
+			picture: None,
+		})
+	);
+}
+
+#[tokio::test]
+async fn album_images_bad_album() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::album_images("Not An Album", &["Khemmis"]);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn artists_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::artists("");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn artists_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::artists("sort=num_songs&order=desc&count=1");
+	let response = service
+		.fetch_json::<_, Vec<dto::ArtistHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let entries = response.body();
+	assert_eq!(entries.len(), 1);
+}
+
 #[tokio::test]
 async fn random_requires_auth() {
 	let mut service = ServiceType::new(&test_name!()).await;
@@ -232,6 +366,42 @@ async fn genre_artists_golden_path() {
 	assert_eq!(entries.len(), 1);
 }
 
+#[tokio::test]
+async fn genre_albums_and_artists_can_include_subgenres() {
+	let config = "[[genres]]\nname = 'Chiptune'\nparent = 'Metal'\n";
+	let mut service = ServiceType::new_with_config(&test_name!(), config).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::genre_albums::<V8>("Metal");
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHeader>>(&request)
+		.await;
+	assert_eq!(response.body().len(), 1);
+
+	let request = protocol::genre_albums_with_subgenres("Metal");
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let mut names = response
+		.body()
+		.iter()
+		.map(|a| a.name.clone())
+		.collect::<Vec<_>>();
+	names.sort();
+	assert_eq!(names, vec!["Hunted".to_owned(), "Picnic".to_owned()]);
+
+	let request = protocol::genre_artists_with_subgenres("Metal");
+	let response = service
+		.fetch_json::<_, Vec<dto::ArtistHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.body().len(), 2);
+}
+
 #[tokio::test]
 async fn genre_songs_requires_auth() {
 	let mut service = ServiceType::new(&test_name!()).await;
@@ -254,3 +424,235 @@ async fn genre_songs_golden_path() {
 	let song_list = response.body();
 	assert_eq!(song_list.paths.len(), 5);
 }
+
+#[tokio::test]
+async fn labels_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::labels();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn labels_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::labels();
+	let response = service
+		.fetch_json::<_, Vec<dto::LabelHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn label_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::label("Not a label");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn composers_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::composers();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn lyricists_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::lyricists();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn decades_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::decades();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn decades_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::decades();
+	let response = service
+		.fetch_json::<_, Vec<dto::DecadeHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	for header in response.body() {
+		let request = protocol::decade(&format!("{}s", header.decade));
+		let response = service.fetch_json::<_, dto::Decade>(&request).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		assert!(!response.body().albums.is_empty());
+	}
+}
+
+#[tokio::test]
+async fn decade_bad_request() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.login().await;
+
+	let request = protocol::decade("eighties");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let request = protocol::decade("1995s");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn compilations_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::compilations();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn compilations_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::compilations();
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHeader>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn stats_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::stats();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn stats_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::stats();
+	let response = service
+		.fetch_json::<_, dto::CollectionStats>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let stats = response.body();
+	assert_eq!(stats.num_songs, 13);
+	assert_eq!(stats.num_albums, 3);
+	assert_eq!(stats.num_genres, 4);
+}
+
+#[tokio::test]
+async fn detailed_stats_requires_admin() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::detailed_stats();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn detailed_stats_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+
+	let request = protocol::detailed_stats();
+	let response = service
+		.fetch_json::<_, dto::DetailedCollectionStats>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let stats = response.body();
+	assert_eq!(stats.stats.num_songs, 13);
+	assert!(stats.size > 0);
+}
+
+#[tokio::test]
+async fn health_requires_admin() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::health("");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn health_can_filter_by_rule() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+
+	let request = protocol::health("rule=missing_artwork");
+	let response = service
+		.fetch_json::<_, Vec<dto::AlbumHealth>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let report = response.body();
+	assert_eq!(report.len(), 1);
+	assert_eq!(report[0].album.name, "Picnic (Remixes)");
+	assert!(report[0].issues.contains(&dto::HealthRule::MissingArtwork));
+}
+
+#[tokio::test]
+async fn duplicates_requires_admin() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::duplicates();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn duplicates_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+
+	let request = protocol::duplicates();
+	let response = service
+		.fetch_json::<_, Vec<dto::DuplicateGroup>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(response.body().is_empty());
+}
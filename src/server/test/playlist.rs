@@ -143,3 +143,609 @@ async fn delete_playlist_golden_path() {
 	let response = service.fetch(&request).await;
 	assert_eq!(response.status(), StatusCode::OK);
 }
+
+#[tokio::test]
+async fn save_smart_playlist_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let smart_query = dto::SmartQuery {
+		query: "khemmis".to_owned(),
+		sort: None,
+		limit: None,
+	};
+	let request = protocol::save_smart_playlist(TEST_PLAYLIST_NAME, smart_query);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn save_smart_playlist_rejects_invalid_query() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let smart_query = dto::SmartQuery {
+		query: "yaer > 2000".to_owned(),
+		sort: None,
+		limit: None,
+	};
+	let request = protocol::save_smart_playlist(TEST_PLAYLIST_NAME, smart_query);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn smart_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let smart_query = dto::SmartQuery {
+		query: "artist = khemmis".to_owned(),
+		sort: Some("title DESC".to_owned()),
+		limit: Some(2),
+	};
+	let request = protocol::save_smart_playlist(TEST_PLAYLIST_NAME, smart_query.clone());
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::playlists();
+	let response = service
+		.fetch_json::<_, Vec<dto::PlaylistHeader>>(&request)
+		.await;
+	let playlists = response.body();
+	assert_eq!(playlists.len(), 1);
+	assert_eq!(playlists[0].smart_query, Some(smart_query));
+
+	let request = protocol::read_playlist::<V8>(TEST_PLAYLIST_NAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let playlist = response.body();
+	assert_eq!(playlist.songs.paths.len(), 2);
+	assert!(playlist.header.smart_query.is_some());
+}
+
+#[tokio::test]
+async fn freeze_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let smart_query = dto::SmartQuery {
+		query: "artist = khemmis".to_owned(),
+		sort: None,
+		limit: None,
+	};
+	let request = protocol::save_smart_playlist(TEST_PLAYLIST_NAME, smart_query);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::freeze_playlist(TEST_PLAYLIST_NAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::read_playlist::<V8>(TEST_PLAYLIST_NAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	let playlist = response.body();
+	assert!(playlist.header.smart_query.is_none());
+	assert!(!playlist.songs.paths.is_empty());
+}
+
+#[tokio::test]
+async fn freeze_playlist_bad_name_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::freeze_playlist(TEST_PLAYLIST_NAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn import_playlist_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::import_playlist(TEST_PLAYLIST_NAME, "m3u8", "");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn import_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let content = format!(
+		"#EXTM3U\n\
+		#EXTINF:221,Khemmis - Above The Water\n\
+		Khemmis/Hunted/01 - Above The Water.mp3\n\
+		{TEST_MOUNT_SOURCE}/Khemmis/Hunted/02 - Candlelight.mp3\n\
+		missing.mp3\n"
+	);
+	let request = protocol::import_playlist(TEST_PLAYLIST_NAME, "m3u8", &content);
+	let response = service
+		.fetch_json::<_, dto::PlaylistImportReport>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(
+		response.body(),
+		&dto::PlaylistImportReport {
+			num_songs: 2,
+			unresolved: vec!["missing.mp3".to_owned()],
+		}
+	);
+
+	let request = protocol::read_playlist::<V8>(TEST_PLAYLIST_NAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	let paths = &response.body().songs.paths;
+	assert_eq!(
+		paths,
+		&vec![
+			Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/01 - Above The Water.mp3"),
+			Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/02 - Candlelight.mp3"),
+		]
+	);
+}
+
+#[tokio::test]
+async fn import_playlist_rejects_malformed_file() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request =
+		protocol::import_playlist(TEST_PLAYLIST_NAME, "xspf", "<playlist><track></playlist>");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn export_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let song_path = Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/05 - Hunted.mp3");
+	let my_playlist = dto::SavePlaylistInput {
+		tracks: vec![song_path],
+	};
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::export_playlist(TEST_PLAYLIST_NAME, "m3u8", None);
+	let response = service.fetch_bytes(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let content = String::from_utf8(response.into_body()).unwrap();
+	assert!(content.starts_with("#EXTM3U\n"));
+	assert!(content.contains("Khemmis - Hunted\n"));
+	assert!(content.contains(&format!(
+		"{TEST_MOUNT_NAME}/Khemmis/Hunted/05 - Hunted.mp3\n"
+	)));
+
+	let base_url = "http://localhost:5050";
+	let request = protocol::export_playlist(TEST_PLAYLIST_NAME, "xspf", Some(base_url));
+	let response = service.fetch_bytes(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let content = String::from_utf8(response.into_body()).unwrap();
+	assert!(content.contains(&format!(
+		"<location>{base_url}/api/audio/{TEST_MOUNT_NAME}/Khemmis/Hunted/05%20-%20Hunted.mp3</location>"
+	)));
+}
+
+#[tokio::test]
+async fn export_playlist_bad_name_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::export_playlist(TEST_PLAYLIST_NAME, "pls", None);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn get_folder_playlist_bad_path_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let path = Path::new(TEST_MOUNT_NAME).join("favorites.m3u");
+	let request = protocol::read_folder_playlist(&path);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_playlists_includes_source() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::playlists();
+	let response = service
+		.fetch_json::<_, Vec<dto::PlaylistHeader>>(&request)
+		.await;
+	let playlists = response.body();
+	assert_eq!(playlists.len(), 1);
+	assert_eq!(playlists[0].source, dto::PlaylistSource::User);
+	assert_eq!(playlists[0].path, None);
+}
+
+#[tokio::test]
+async fn edit_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let path = Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/01 - Above The Water.mp3");
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let edit = dto::PlaylistEditInput {
+		version: Some(1),
+		operation: dto::PlaylistEditOperation::Append {
+			tracks: vec![path.clone(), path],
+		},
+	};
+	let request = protocol::edit_playlist(TEST_PLAYLIST_NAME, edit);
+	let response = service.fetch_json::<_, dto::PlaylistHeader>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert_eq!(response.body().version, 2);
+
+	let edit = dto::PlaylistEditInput {
+		version: None,
+		operation: dto::PlaylistEditOperation::Dedupe,
+	};
+	let request = protocol::edit_playlist(TEST_PLAYLIST_NAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::read_playlist::<V8>(TEST_PLAYLIST_NAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	let playlist = response.body();
+	assert_eq!(playlist.songs.paths.len(), 1);
+	assert_eq!(playlist.header.version, 3);
+}
+
+#[tokio::test]
+async fn edit_playlist_outdated_version_returns_conflict() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist.clone());
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let edit = dto::PlaylistEditInput {
+		version: Some(1),
+		operation: dto::PlaylistEditOperation::Dedupe,
+	};
+	let request = protocol::edit_playlist(TEST_PLAYLIST_NAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn edit_playlist_bad_position_returns_bad_request() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let edit = dto::PlaylistEditInput {
+		version: None,
+		operation: dto::PlaylistEditOperation::Remove {
+			position: 0,
+			count: 1,
+		},
+	};
+	let request = protocol::edit_playlist(TEST_PLAYLIST_NAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn edit_playlist_bad_name_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let edit = dto::PlaylistEditInput {
+		version: None,
+		operation: dto::PlaylistEditOperation::Dedupe,
+	};
+	let request = protocol::edit_playlist(TEST_PLAYLIST_NAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn shared_playlist_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let sharing = dto::PlaylistSharing {
+		visibility: dto::PlaylistVisibility::SharedRead,
+		members: vec![TEST_USERNAME_ADMIN.to_owned()],
+	};
+	let request = protocol::set_playlist_sharing(TEST_PLAYLIST_NAME, sharing);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	service.login_admin().await;
+
+	let request = protocol::playlists();
+	let response = service
+		.fetch_json::<_, Vec<dto::PlaylistHeader>>(&request)
+		.await;
+	let playlists = response.body();
+	assert_eq!(playlists.len(), 1);
+	assert_eq!(playlists[0].owner.as_deref(), Some(TEST_USERNAME));
+	assert_eq!(
+		playlists[0].visibility,
+		Some(dto::PlaylistVisibility::SharedRead)
+	);
+
+	let request = protocol::read_shared_playlist(TEST_PLAYLIST_NAME, TEST_USERNAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let edit = dto::PlaylistEditInput {
+		version: None,
+		operation: dto::PlaylistEditOperation::Dedupe,
+	};
+	let request = protocol::edit_shared_playlist(TEST_PLAYLIST_NAME, TEST_USERNAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn collaborative_playlist_can_be_edited_by_members() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let sharing = dto::PlaylistSharing {
+		visibility: dto::PlaylistVisibility::Collaborative,
+		members: vec![TEST_USERNAME_ADMIN.to_owned()],
+	};
+	let request = protocol::set_playlist_sharing(TEST_PLAYLIST_NAME, sharing);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	service.login_admin().await;
+
+	let path = Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/01 - Above The Water.mp3");
+	let edit = dto::PlaylistEditInput {
+		version: None,
+		operation: dto::PlaylistEditOperation::Append { tracks: vec![path] },
+	};
+	let request = protocol::edit_shared_playlist(TEST_PLAYLIST_NAME, TEST_USERNAME, edit);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	service.login().await;
+
+	let request = protocol::read_playlist::<V8>(TEST_PLAYLIST_NAME);
+	let response = service.fetch_json::<_, dto::Playlist>(&request).await;
+	assert_eq!(response.body().songs.paths.len(), 1);
+}
+
+#[tokio::test]
+async fn private_playlist_of_other_user_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	service.login_admin().await;
+
+	let request = protocol::read_shared_playlist(TEST_PLAYLIST_NAME, TEST_USERNAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+	let request = protocol::playlists();
+	let response = service
+		.fetch_json::<_, Vec<dto::PlaylistHeader>>(&request)
+		.await;
+	assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn set_playlist_sharing_unknown_member_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let sharing = dto::PlaylistSharing {
+		visibility: dto::PlaylistVisibility::SharedRead,
+		members: vec!["nobody".to_owned()],
+	};
+	let request = protocol::set_playlist_sharing(TEST_PLAYLIST_NAME, sharing);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn get_unresolved_songs_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let path = Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/01 - Above The Water.mp3");
+	let my_playlist = dto::SavePlaylistInput { tracks: vec![path] };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::unresolved_playlist_songs(TEST_PLAYLIST_NAME);
+	let response = service
+		.fetch_json::<_, Vec<std::path::PathBuf>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn get_unresolved_songs_bad_name_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::unresolved_playlist_songs(TEST_PLAYLIST_NAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn set_playlist_metadata_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let metadata = dto::PlaylistMetadata {
+		description: Some("Songs for late evenings".to_owned()),
+		folder: Some("Road Trips/Summer".to_owned()),
+		pinned: true,
+	};
+	let request = protocol::set_playlist_metadata(TEST_PLAYLIST_NAME, metadata);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::playlists();
+	let response = service
+		.fetch_json::<_, Vec<dto::PlaylistHeader>>(&request)
+		.await;
+	let playlist = &response.body()[0];
+	assert_eq!(
+		playlist.description.as_deref(),
+		Some("Songs for late evenings")
+	);
+	assert_eq!(playlist.folder.as_deref(), Some("Road Trips/Summer"));
+	assert!(playlist.pinned);
+	assert!(!playlist.custom_cover);
+}
+
+#[tokio::test]
+async fn set_playlist_metadata_bad_name_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::set_playlist_metadata(TEST_PLAYLIST_NAME, Default::default());
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn get_playlist_cover_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let path = Path::new(TEST_MOUNT_NAME).join("Khemmis/Hunted/01 - Above The Water.mp3");
+	let my_playlist = dto::SavePlaylistInput { tracks: vec![path] };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::playlist_cover(TEST_PLAYLIST_NAME);
+	let response = service.fetch_bytes(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn get_playlist_cover_without_artwork_returns_not_found() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::playlist_cover(TEST_PLAYLIST_NAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn upload_playlist_cover_bad_image_returns_bad_request() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let my_playlist = dto::SavePlaylistInput { tracks: Vec::new() };
+	let request = protocol::save_playlist(TEST_PLAYLIST_NAME, my_playlist);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::upload_playlist_cover(TEST_PLAYLIST_NAME, "not an image");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+	let request = protocol::delete_playlist_cover(TEST_PLAYLIST_NAME);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 
 use crate::server::dto;
 use crate::server::dto::ThumbnailSize;
+use crate::server::API_ARRAY_SEPARATOR;
 
 pub trait ProtocolVersion {
 	fn header_value() -> i32;
@@ -116,6 +117,24 @@ pub fn update_user(username: &str, user_update: dto::UserUpdate) -> Request<dto:
 		.unwrap()
 }
 
+pub fn preferences() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/preferences")
+		.body(())
+		.unwrap()
+}
+
+pub fn update_preferences(
+	preferences_update: dto::PreferencesUpdate,
+) -> Request<dto::PreferencesUpdate> {
+	Request::builder()
+		.method(Method::PUT)
+		.uri("/api/preferences")
+		.body(preferences_update)
+		.unwrap()
+}
+
 pub fn delete_user(username: &str) -> Request<()> {
 	Request::builder()
 		.method(Method::DELETE)
@@ -183,6 +202,18 @@ pub fn genre_albums<VERSION: ProtocolVersion>(genre: &str) -> Request<()> {
 		.unwrap()
 }
 
+pub fn genre_albums_with_subgenres(genre: &str) -> Request<()> {
+	let endpoint = format!(
+		"/api/genre/{}/albums?include_subgenres=true",
+		url_encode(genre)
+	);
+	Request::builder()
+		.method(Method::GET)
+		.uri(endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn genre_artists<VERSION: ProtocolVersion>(genre: &str) -> Request<()> {
 	let endpoint = format!("/api/genre/{}/artists", url_encode(genre));
 	Request::builder()
@@ -193,6 +224,18 @@ pub fn genre_artists<VERSION: ProtocolVersion>(genre: &str) -> Request<()> {
 		.unwrap()
 }
 
+pub fn genre_artists_with_subgenres(genre: &str) -> Request<()> {
+	let endpoint = format!(
+		"/api/genre/{}/artists?include_subgenres=true",
+		url_encode(genre)
+	);
+	Request::builder()
+		.method(Method::GET)
+		.uri(endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn genre_songs<VERSION: ProtocolVersion>(genre: &str) -> Request<()> {
 	let endpoint = format!("/api/genre/{}/songs", url_encode(genre));
 	Request::builder()
@@ -203,6 +246,115 @@ pub fn genre_songs<VERSION: ProtocolVersion>(genre: &str) -> Request<()> {
 		.unwrap()
 }
 
+pub fn albums(query: &str) -> Request<()> {
+	let endpoint = format!("/api/albums?{query}");
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn artists(query: &str) -> Request<()> {
+	let endpoint = format!("/api/artists?{query}");
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn stats() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/stats")
+		.body(())
+		.unwrap()
+}
+
+pub fn detailed_stats() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/stats/detailed")
+		.body(())
+		.unwrap()
+}
+
+pub fn duplicates() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/duplicates")
+		.body(())
+		.unwrap()
+}
+
+pub fn health(query: &str) -> Request<()> {
+	let endpoint = format!("/api/health?{query}");
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn labels() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/labels")
+		.body(())
+		.unwrap()
+}
+
+pub fn label(name: &str) -> Request<()> {
+	let endpoint = format!("/api/labels/{}", url_encode(name));
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn compilations() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/compilations")
+		.body(())
+		.unwrap()
+}
+
+pub fn composers() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/composers")
+		.body(())
+		.unwrap()
+}
+
+pub fn lyricists() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/lyricists")
+		.body(())
+		.unwrap()
+}
+
+pub fn decades() -> Request<()> {
+	Request::builder()
+		.method(Method::GET)
+		.uri("/api/decades")
+		.body(())
+		.unwrap()
+}
+
+pub fn decade(decade: &str) -> Request<()> {
+	let endpoint = format!("/api/decades/{}", url_encode(decade));
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn random<VERSION: ProtocolVersion>() -> Request<()> {
 	Request::builder()
 		.header("Accept-Version", VERSION::header_value())
@@ -231,6 +383,61 @@ pub fn search<VERSION: ProtocolVersion>(query: &str) -> Request<()> {
 		.unwrap()
 }
 
+pub fn search_by_relevance(query: &str) -> Request<()> {
+	let endpoint = format!("/api/search/{}?order=relevance", url_encode(query));
+	Request::builder()
+		.header("Accept-Version", V8::header_value())
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn search_page(query: &str, limit: usize, cursor: Option<&str>) -> Request<()> {
+	let mut endpoint = format!("/api/search/{}?limit={limit}", url_encode(query));
+	if let Some(cursor) = cursor {
+		endpoint.push_str(&format!("&cursor={}", url_encode(cursor)));
+	}
+	Request::builder()
+		.header("Accept-Version", V8::header_value())
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn search_all(query: &str) -> Request<()> {
+	let endpoint = format!("/api/search_all/{}", url_encode(query));
+	Request::builder()
+		.header("Accept-Version", V8::header_value())
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn search_suggestions(prefix: &str) -> Request<()> {
+	let endpoint = format!("/api/search_suggestions?prefix={}", url_encode(prefix));
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn album_images(name: &str, artists: &[&str]) -> Request<()> {
+	let endpoint = format!(
+		"/api/album/{}/by/{}/images",
+		url_encode(name),
+		url_encode(&artists.join(API_ARRAY_SEPARATOR))
+	);
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn songs(songs: dto::GetSongsBulkInput) -> Request<dto::GetSongsBulkInput> {
 	Request::builder()
 		.method(Method::POST)
@@ -313,6 +520,72 @@ pub fn save_playlist(
 		.unwrap()
 }
 
+pub fn edit_playlist(name: &str, edit: dto::PlaylistEditInput) -> Request<dto::PlaylistEditInput> {
+	let endpoint = format!("/api/playlist/{}", url_encode(name));
+	Request::builder()
+		.method(Method::PATCH)
+		.uri(&endpoint)
+		.body(edit)
+		.unwrap()
+}
+
+pub fn edit_shared_playlist(
+	name: &str,
+	owner: &str,
+	edit: dto::PlaylistEditInput,
+) -> Request<dto::PlaylistEditInput> {
+	let endpoint = format!(
+		"/api/playlist/{}?owner={}",
+		url_encode(name),
+		url_encode(owner)
+	);
+	Request::builder()
+		.method(Method::PATCH)
+		.uri(&endpoint)
+		.body(edit)
+		.unwrap()
+}
+
+pub fn save_smart_playlist(name: &str, smart_query: dto::SmartQuery) -> Request<dto::SmartQuery> {
+	let endpoint = format!("/api/smart_playlist/{}", url_encode(name));
+	Request::builder()
+		.method(Method::PUT)
+		.uri(&endpoint)
+		.body(smart_query)
+		.unwrap()
+}
+
+pub fn freeze_playlist(name: &str) -> Request<()> {
+	let endpoint = format!("/api/playlist/{}/freeze", url_encode(name));
+	Request::builder()
+		.method(Method::POST)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn import_playlist(name: &str, format: &str, content: &str) -> Request<String> {
+	let endpoint = format!("/api/playlist/{}/import?format={format}", url_encode(name));
+	Request::builder()
+		.header(http::header::CONTENT_TYPE, "text/plain")
+		.method(Method::POST)
+		.uri(&endpoint)
+		.body(content.to_owned())
+		.unwrap()
+}
+
+pub fn export_playlist(name: &str, format: &str, base_url: Option<&str>) -> Request<()> {
+	let mut endpoint = format!("/api/playlist/{}/export?format={format}", url_encode(name));
+	if let Some(base_url) = base_url {
+		endpoint.push_str(&format!("&base_url={}", url_encode(base_url)));
+	}
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn read_playlist<VERSION: ProtocolVersion>(name: &str) -> Request<()> {
 	let endpoint = format!("/api/playlist/{}", url_encode(name));
 	Request::builder()
@@ -323,6 +596,92 @@ pub fn read_playlist<VERSION: ProtocolVersion>(name: &str) -> Request<()> {
 		.unwrap()
 }
 
+pub fn read_shared_playlist(name: &str, owner: &str) -> Request<()> {
+	let endpoint = format!(
+		"/api/playlist/{}?owner={}",
+		url_encode(name),
+		url_encode(owner)
+	);
+	Request::builder()
+		.header("Accept-Version", V8::header_value())
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn set_playlist_sharing(
+	name: &str,
+	sharing: dto::PlaylistSharing,
+) -> Request<dto::PlaylistSharing> {
+	let endpoint = format!("/api/playlist/{}/sharing", url_encode(name));
+	Request::builder()
+		.method(Method::PUT)
+		.uri(&endpoint)
+		.body(sharing)
+		.unwrap()
+}
+
+pub fn unresolved_playlist_songs(name: &str) -> Request<()> {
+	let endpoint = format!("/api/playlist/{}/unresolved", url_encode(name));
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn set_playlist_metadata(
+	name: &str,
+	metadata: dto::PlaylistMetadata,
+) -> Request<dto::PlaylistMetadata> {
+	let endpoint = format!("/api/playlist/{}/metadata", url_encode(name));
+	Request::builder()
+		.method(Method::PUT)
+		.uri(&endpoint)
+		.body(metadata)
+		.unwrap()
+}
+
+pub fn playlist_cover(name: &str) -> Request<()> {
+	let endpoint = format!("/api/playlist/{}/cover", url_encode(name));
+	Request::builder()
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn upload_playlist_cover(name: &str, content: &str) -> Request<String> {
+	let endpoint = format!("/api/playlist/{}/cover", url_encode(name));
+	Request::builder()
+		.method(Method::PUT)
+		.header(http::header::CONTENT_TYPE, "text/plain")
+		.uri(&endpoint)
+		.body(content.to_owned())
+		.unwrap()
+}
+
+pub fn delete_playlist_cover(name: &str) -> Request<()> {
+	let endpoint = format!("/api/playlist/{}/cover", url_encode(name));
+	Request::builder()
+		.method(Method::DELETE)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
+pub fn read_folder_playlist(path: &Path) -> Request<()> {
+	let path = path.to_string_lossy();
+	let endpoint = format!("/api/folder_playlist/{}", url_encode(path.as_ref()));
+	Request::builder()
+		.header("Accept-Version", V8::header_value())
+		.method(Method::GET)
+		.uri(&endpoint)
+		.body(())
+		.unwrap()
+}
+
 pub fn delete_playlist(name: &str) -> Request<()> {
 	let endpoint = format!("/api/playlist/{}", url_encode(name));
 	Request::builder()
@@ -45,6 +45,103 @@ async fn search_with_query() {
 	assert_eq!(songs.paths, vec![path]);
 }
 
+#[tokio::test]
+async fn search_by_relevance() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search_by_relevance("hunted");
+	let response = service.fetch_json::<_, dto::SearchResults>(&request).await;
+	let results = response.body();
+
+	let path: PathBuf = [TEST_MOUNT_NAME, "Khemmis", "Hunted", "05 - Hunted.mp3"]
+		.iter()
+		.collect();
+	assert_eq!(results.songs.paths.len(), 5);
+	assert_eq!(results.songs.paths[0], path);
+	assert_eq!(results.scores.len(), 5);
+	assert!(results.scores.windows(2).all(|s| s[0] >= s[1]));
+}
+
+#[tokio::test]
+async fn search_pagination() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search::<V8>("hunted");
+	let response = service.fetch_json::<_, dto::SearchResults>(&request).await;
+	let all_paths = response.body().songs.paths.clone();
+	assert_eq!(all_paths.len(), 5);
+	assert_eq!(response.body().total, 5);
+	assert!(response.body().next_cursor.is_none());
+
+	let mut paths = Vec::new();
+	let mut cursor = None;
+	loop {
+		let request = protocol::search_page("hunted", 2, cursor.as_deref());
+		let response = service.fetch_json::<_, dto::SearchResults>(&request).await;
+		assert_eq!(response.status(), StatusCode::OK);
+		let page = response.body();
+		assert_eq!(page.total, 5);
+		assert!(page.songs.paths.len() <= 2);
+		paths.extend(page.songs.paths.clone());
+		cursor = page.next_cursor.clone();
+		if cursor.is_none() {
+			break;
+		}
+	}
+	assert_eq!(paths, all_paths);
+}
+
+#[tokio::test]
+async fn search_rejects_invalid_cursor() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search_page("hunted", 2, Some("garbage"));
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn search_rejects_cursor_from_another_query() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search_page("hunted", 2, None);
+	let response = service.fetch_json::<_, dto::SearchResults>(&request).await;
+	let cursor = response.body().next_cursor.clone().unwrap();
+
+	let request = protocol::search_page("picnic", 2, Some(&cursor));
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn search_rejects_empty_pages() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search_page("hunted", 0, None);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn search_with_query_v7() {
 	let mut service = ServiceType::new(&test_name!()).await;
@@ -76,3 +173,140 @@ async fn search_with_query_v7() {
 		})]
 	);
 }
+
+#[tokio::test]
+async fn search_reports_query_errors() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search::<V8>("yaer > 2000");
+	let response = service
+		.fetch_json::<_, dto::SearchQueryError>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+	let error = response.body();
+	assert_eq!((error.start, error.end), (0, 4));
+	assert_eq!(error.hint, "unknown field `yaer`, did you mean `year`?");
+}
+
+#[tokio::test]
+async fn search_all_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::search_all("khemmis");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn search_all_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let my_playlist = protocol::save_playlist(
+		"Khemmis Favorites",
+		dto::SavePlaylistInput { tracks: vec![] },
+	);
+	service.fetch(&my_playlist).await;
+
+	let request = protocol::search_all("hunted");
+	let response = service
+		.fetch_json::<_, dto::SearchAllResults>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let results = response.body();
+	assert!(results.artists.is_empty());
+	assert_eq!(results.albums.len(), 1);
+	assert_eq!(results.albums[0].name, "Hunted");
+	assert!(results.playlists.is_empty());
+	assert_eq!(results.songs.songs.paths.len(), 5);
+
+	let request = protocol::search_all("khemmis");
+	let response = service
+		.fetch_json::<_, dto::SearchAllResults>(&request)
+		.await;
+	let results = response.body();
+	assert_eq!(results.artists.len(), 1);
+	assert_eq!(results.artists[0].name, "Khemmis");
+	assert_eq!(results.playlists.len(), 1);
+	assert_eq!(results.playlists[0].name, "Khemmis Favorites");
+
+	// Playlists shared by other users are not searched
+	let sharing = dto::PlaylistSharing {
+		visibility: dto::PlaylistVisibility::SharedRead,
+		members: vec![TEST_USERNAME_ADMIN.to_owned()],
+	};
+	let request = protocol::set_playlist_sharing("Khemmis Favorites", sharing);
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	service.login_admin().await;
+	let request = protocol::search_all("khemmis");
+	let response = service
+		.fetch_json::<_, dto::SearchAllResults>(&request)
+		.await;
+	assert!(response.body().playlists.is_empty());
+}
+
+#[tokio::test]
+async fn search_suggestions_requires_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	let request = protocol::search_suggestions("khe");
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn search_suggestions_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search_suggestions("khe");
+	let response = service
+		.fetch_json::<_, Vec<dto::Suggestion>>(&request)
+		.await;
+	assert_eq!(response.status(), StatusCode::OK);
+	let suggestions = response.body();
+	assert_eq!(
+		suggestions.first(),
+		Some(&dto::Suggestion {
+			kind: dto::SuggestionKind::Artist,
+			text: "Khemmis".to_owned(),
+		})
+	);
+
+	let request = protocol::search_suggestions("yea");
+	let response = service
+		.fetch_json::<_, Vec<dto::Suggestion>>(&request)
+		.await;
+	let suggestions = response.body();
+	assert_eq!(
+		suggestions.first(),
+		Some(&dto::Suggestion {
+			kind: dto::SuggestionKind::Keyword,
+			text: "year".to_owned(),
+		})
+	);
+}
+
+#[tokio::test]
+async fn search_for_suggest_is_a_regular_search() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login_admin().await;
+	service.index().await;
+	service.login().await;
+
+	let request = protocol::search::<V8>("suggest");
+	let response = service.fetch_json::<_, dto::SearchResults>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(response.body().songs.paths.is_empty());
+}
@@ -63,6 +63,7 @@ async fn put_settings_golden_path() {
 
 	let request = protocol::put_settings(dto::NewSettings {
 		album_art_pattern: Some("test_pattern".to_owned()),
+		artist_art_pattern: Some("test_artist_pattern".to_owned()),
 		ddns_update_url: Some("http://example.com/".to_owned()),
 	});
 	let response = service.fetch(&request).await;
@@ -75,6 +76,7 @@ async fn put_settings_golden_path() {
 		settings,
 		&Settings {
 			album_art_pattern: "test_pattern".to_owned(),
+			artist_art_pattern: "test_artist_pattern".to_owned(),
 			ddns_update_url: "http://example.com/".to_owned(),
 		},
 	);
@@ -139,3 +139,35 @@ async fn delete_user_cannot_delete_self() {
 	let response = service.fetch(&request).await;
 	assert_eq!(response.status(), StatusCode::CONFLICT);
 }
+
+#[tokio::test]
+async fn preferences_require_auth() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	let request = protocol::preferences();
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn preferences_golden_path() {
+	let mut service = ServiceType::new(&test_name!()).await;
+	service.complete_initial_setup().await;
+	service.login().await;
+
+	let request = protocol::preferences();
+	let response = service.fetch_json::<_, dto::Preferences>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(!response.body().collapse_duplicates);
+
+	let request = protocol::update_preferences(dto::PreferencesUpdate {
+		new_collapse_duplicates: Some(true),
+	});
+	let response = service.fetch(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+
+	let request = protocol::preferences();
+	let response = service.fetch_json::<_, dto::Preferences>(&request).await;
+	assert_eq!(response.status(), StatusCode::OK);
+	assert!(response.body().collapse_duplicates);
+}